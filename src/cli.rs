@@ -0,0 +1,445 @@
+// Command-line subcommands for scripting against the saved data
+use crate::CubeTimer;
+use clap::{Parser, Subcommand, ValueEnum};
+use lstimer::export::{ExportFormat, ExportScope};
+use lstimer::import::ImportFormat;
+use lstimer::smart_cube::{Reconstruction, ReplaySource, SmartCube};
+use lstimer::scramble::{ScrambleSet, SetScramble};
+use lstimer::stats::{format_result, format_time};
+use lstimer::{CubeEvent, ExportFilter, SolveResult, scramble};
+use serde_json::{Value, json};
+use std::path::Path;
+use std::time::Duration;
+
+// Exit code for commands that failed after their arguments were accepted
+const EXIT_FAILURE: i32 = 1;
+
+#[derive(Parser)]
+#[command(name = "lstimer", version, about = "A minimal and fast speedcubing timer")]
+pub struct Cli {
+    /// Run the timer in the terminal instead of a window
+    #[arg(long)]
+    pub tui: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Print scrambles for an event
+    Scramble {
+        /// Event, e.g. 3x3, 4x4, oh, bld, pyraminx, sq1 or a custom event name
+        #[arg(short, long, default_value = "3x3")]
+        event: String,
+        /// Number of scrambles
+        #[arg(short = 'n', long = "count", default_value_t = 1)]
+        count: usize,
+        /// Print the first scrambles of a shared scramble set, given its code or seed
+        #[arg(long)]
+        set: Option<String>,
+        /// Print JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print best, worst, mean and averages
+    Stats {
+        #[command(flatten)]
+        selection: Selection,
+        /// Print JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Export solves as CSV, a csTimer backup or summary statistics
+    Export {
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = ExportArg::Csv)]
+        format: ExportArg,
+        #[command(flatten)]
+        selection: Selection,
+        /// Only include solves on or after this day (YYYY-MM-DD)
+        #[arg(long)]
+        from: Option<String>,
+        /// Only include solves on or before this day (YYYY-MM-DD)
+        #[arg(long)]
+        to: Option<String>,
+        /// Write to this file instead of standard output
+        #[arg(short, long)]
+        output: Option<String>,
+        /// Report the result as JSON when writing to a file
+        #[arg(long)]
+        json: bool,
+    },
+    /// Import solves from a csTimer, Twisty Timer or CubeDesk backup
+    Import {
+        /// Backup file to import
+        file: String,
+        /// Backup format, detected from the contents when omitted
+        #[arg(short, long, value_enum)]
+        format: Option<ImportArg>,
+        /// Print JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Replay a smart cube move log and print the solve's CFOP reconstruction
+    Reconstruct {
+        /// Move log with one "<milliseconds> <turn>" pair per line, starting from a solved cube
+        log: String,
+        /// Scramble the cube was given before the solve
+        #[arg(short, long)]
+        scramble: String,
+        /// Save the solve, with its moves, to the event's active session
+        #[arg(long)]
+        save: bool,
+        /// Event to save the solve to (defaults to the event last used in the app)
+        #[arg(short, long, requires = "save")]
+        event: Option<String>,
+        /// Print JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+// Which solves a command works on
+#[derive(clap::Args)]
+pub struct Selection {
+    /// Event, e.g. 3x3 or 4x4 (defaults to the event last used in the app)
+    #[arg(short, long)]
+    event: Option<String>,
+    /// Session name or id (defaults to the event's active session)
+    #[arg(short, long, conflicts_with = "all_sessions")]
+    session: Option<String>,
+    /// Use every session of the event
+    #[arg(long)]
+    all_sessions: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ExportArg {
+    Csv,
+    Cstimer,
+    Summary,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ImportArg {
+    Cstimer,
+    Twisty,
+    Cubedesk,
+}
+
+// Runs a subcommand and returns the process exit code
+pub fn run(command: Command) -> i32 {
+    let json = match &command {
+        Command::Scramble { json, .. }
+        | Command::Stats { json, .. }
+        | Command::Export { json, .. }
+        | Command::Import { json, .. }
+        | Command::Reconstruct { json, .. } => *json,
+    };
+
+    let result = match command {
+        Command::Scramble { event, count, set, .. } => scramble(&event, count, set.as_deref(), json),
+        Command::Stats { selection, .. } => stats(&selection, json),
+        Command::Export { format, selection, from, to, output, .. } => {
+            export(format, &selection, from, to, output.as_deref(), json)
+        }
+        Command::Import { file, format, .. } => import(&file, format, json),
+        Command::Reconstruct { log, scramble, save, event, .. } => reconstruct(&log, &scramble, save, event.as_deref(), json),
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(error) => {
+            if json {
+                println!("{}", json!({ "error": error }));
+            } else {
+                eprintln!("error: {}", error);
+            }
+            EXIT_FAILURE
+        }
+    }
+}
+
+// Simplifies an event name for matching, e.g. "3x3 OH" and "3x3-oh" both become "3x3oh"
+fn normalize(name: &str) -> String {
+    name.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+// Finds an event by its display name or a common abbreviation
+fn parse_event(app: &CubeTimer, name: &str) -> Result<CubeEvent, String> {
+    let wanted = normalize(name);
+    let alias = match wanted.as_str() {
+        "2x2" | "222" => "2x2x2",
+        "3x3" | "333" => "3x3x3",
+        "4x4" | "444" => "4x4x4",
+        "5x5" | "555" => "5x5x5",
+        "6x6" | "666" => "6x6x6",
+        "7x7" | "777" => "7x7x7",
+        "oh" | "333oh" => "3x3oh",
+        "bld" | "3bld" | "333bf" => "3x3bld",
+        "feet" | "333ft" => "3x3feet",
+        "pyra" | "pyram" => "pyraminx",
+        "minx" | "mega" => "megaminx",
+        "sq1" => "square1",
+        "clk" => "clock",
+        other => other,
+    };
+    let wanted = normalize(alias);
+
+    app.data.available_events
+        .iter()
+        .find(|event| normalize(&event.to_string()) == wanted)
+        .cloned()
+        .ok_or_else(|| {
+            let names: Vec<String> = app.data.available_events.iter().map(|e| e.to_string()).collect();
+            format!("Unknown event '{}' (available: {})", name, names.join(", "))
+        })
+}
+
+// Refuses to work with the saved data while a data file is damaged, since its solves or
+// sessions would be missing from the results
+fn check_data(app: &CubeTimer) -> Result<(), String> {
+    match app.data.corrupt_files.first() {
+        Some(corrupt) => Err(format!("{} is damaged ({}); open LSTimer to recover it first", corrupt.name, corrupt.error)),
+        None => Ok(()),
+    }
+}
+
+// Points the timer at the selected event and session, as if chosen in the app
+fn select(app: &mut CubeTimer, selection: &Selection) -> Result<(), String> {
+    if let Some(name) = &selection.event {
+        app.current_event = parse_event(app, name)?;
+        app.ensure_active_session();
+    }
+
+    app.ui_state.show_all_sessions = selection.all_sessions;
+    if let Some(wanted) = &selection.session {
+        let session = app.data.sessions.sessions
+            .iter()
+            .filter(|s| s.event == app.current_event)
+            .find(|s| s.name == *wanted || s.id.to_string() == *wanted)
+            .ok_or_else(|| format!("No session '{}' for {}", wanted, app.current_event))?;
+        app.data.sessions.active.insert(app.current_event.to_string(), session.id);
+    }
+
+    app.calculate_statistics();
+    app.recalculate_personal_bests();
+    Ok(())
+}
+
+// JSON value of a time, in milliseconds
+fn millis(time: Duration) -> Value {
+    json!(time.as_millis() as u64)
+}
+
+// JSON value of a result: milliseconds, or "DNF"
+fn result_value(result: SolveResult) -> Value {
+    match result {
+        SolveResult::Time(time) => millis(time),
+        SolveResult::DNF => json!("DNF"),
+    }
+}
+
+// Prints scrambles for an event, random or the first ones of a scramble set
+fn scramble(event: &str, count: usize, set: Option<&str>, json: bool) -> Result<(), String> {
+    let app = CubeTimer::load();
+    let event = parse_event(&app, event)?;
+    let settings = app.event_settings(&event);
+    let set = set.map(ScrambleSet::from_code).transpose()?;
+    let scrambles: Vec<SetScramble> = (0..count)
+        .map(|index| match &set {
+            Some(set) => set.scramble(&event, index, &app.data.custom_events),
+            None => scramble::generate_case(&event, &settings, &app.data.custom_events),
+        })
+        .collect();
+
+    if json {
+        let mut output = json!({
+            "event": event.to_string(),
+            "scrambles": scrambles.iter().map(|s| s.scramble.as_str()).collect::<Vec<_>>(),
+        });
+        // Trainer scrambles also tell which case they set up
+        if matches!(event, CubeEvent::Training(_)) {
+            output["cases"] = json!(scrambles.iter().map(|s| s.case.as_deref()).collect::<Vec<_>>());
+        }
+        println!("{}", output);
+    } else {
+        for (i, scramble) in scrambles.iter().enumerate() {
+            let text = match &scramble.case {
+                Some(case) => format!("{} ({})", scramble.scramble, case),
+                None => scramble.scramble.clone(),
+            };
+            // Megaminx scrambles span several lines, so number every scramble when printing more than one
+            if count > 1 {
+                println!("{}. {}", i + 1, text.replace('\n', "\n   "));
+            } else {
+                println!("{}", text);
+            }
+        }
+    }
+    Ok(())
+}
+
+// Prints the statistics of the selected solves
+fn stats(selection: &Selection, json: bool) -> Result<(), String> {
+    let mut app = CubeTimer::load();
+    check_data(&app)?;
+    select(&mut app, selection)?;
+    let solves = app.data.records.iter().filter(|r| app.is_in_scope(r)).count();
+    let statistics = &app.statistics;
+
+    if json {
+        let averages: serde_json::Map<String, Value> = statistics.current
+            .iter()
+            .map(|(column, value)| (column.label(), value.map_or(Value::Null, result_value)))
+            .collect();
+        let personal_bests: serde_json::Map<String, Value> = statistics.personal_bests
+            .iter()
+            .map(|pb| (pb.column.label(), json!({ "time": millis(pb.average), "first_solve": pb.first_solve, "solves": app.personal_best_solves(pb) })))
+            .collect();
+        println!("{}", json!({
+            "event": app.current_event.to_string(),
+            "scope": app.scope_description(),
+            "solves": solves,
+            "best": statistics.best.map(millis),
+            "worst": statistics.worst.map(result_value),
+            "mean": statistics.mean.map(millis),
+            "averages": averages,
+            "personal_bests": personal_bests,
+        }));
+        return Ok(());
+    }
+
+    println!("{}", app.scope_description());
+    println!("Solves: {}", solves);
+    let or_dash = |text: Option<String>| text.unwrap_or_else(|| "-".to_string());
+    println!("Best: {}", or_dash(statistics.best.map(format_time)));
+    println!("Worst: {}", or_dash(statistics.worst.map(format_result)));
+    println!("Mean: {}", or_dash(statistics.mean.map(format_time)));
+    for (column, value) in &statistics.current {
+        println!("{}: {}", column.label(), or_dash(value.map(format_result)));
+    }
+    for pb in &statistics.personal_bests {
+        println!("PB {}: {} ({})", pb.column.label(), format_time(pb.average), app.personal_best_solves(pb));
+    }
+    Ok(())
+}
+
+// Exports the selected solves to standard output or a file
+fn export(format: ExportArg, selection: &Selection, from: Option<String>, to: Option<String>, output: Option<&str>, json: bool) -> Result<(), String> {
+    let mut app = CubeTimer::load();
+    check_data(&app)?;
+    select(&mut app, selection)?;
+
+    let format = match format {
+        ExportArg::Csv => ExportFormat::Csv,
+        ExportArg::Cstimer => ExportFormat::CsTimer,
+        ExportArg::Summary => ExportFormat::Summary,
+    };
+    let scope = if from.is_some() || to.is_some() {
+        ExportScope::DateRange
+    } else if selection.all_sessions {
+        ExportScope::Event
+    } else {
+        ExportScope::Session
+    };
+    let filter = ExportFilter {
+        from: from.unwrap_or_default(),
+        to: to.unwrap_or_default(),
+        selection: Vec::new(),
+        scramble_type: app.scramble_type(),
+    };
+
+    let (contents, count) = app.data.export_contents(&app.current_event, format, scope, &filter)?;
+    match output {
+        Some(path) => {
+            std::fs::write(path, contents).map_err(|e| format!("Could not write {}: {}", path, e))?;
+            if json {
+                println!("{}", json!({ "exported": count, "path": path }));
+            } else {
+                println!("Exported {} solves to {}", count, path);
+            }
+        }
+        None => print!("{}", contents),
+    }
+    Ok(())
+}
+
+// Imports a backup from another timer into the saved data
+fn import(file: &str, format: Option<ImportArg>, json: bool) -> Result<(), String> {
+    let mut app = CubeTimer::load();
+    check_data(&app)?;
+
+    let format = format.map(|format| match format {
+        ImportArg::Cstimer => ImportFormat::CsTimer,
+        ImportArg::Twisty => ImportFormat::TwistyTimer,
+        ImportArg::Cubedesk => ImportFormat::CubeDesk,
+    });
+    let (imported, skipped) = app.import_file(file, format)?;
+    if let Some(error) = app.save_error.take() {
+        return Err(error);
+    }
+
+    if json {
+        println!("{}", json!({ "imported": imported, "skipped": skipped }));
+    } else {
+        println!("Imported {} solves, skipped {} already imported", imported, skipped);
+    }
+    Ok(())
+}
+
+// Replays a smart cube move log through the cube tracker and prints the solve's reconstruction
+fn reconstruct(log: &str, scramble: &str, save: bool, event: Option<&str>, json: bool) -> Result<(), String> {
+    let mut source = ReplaySource::from_file(Path::new(log))?;
+    let mut cube = SmartCube::new();
+    if !cube.set_scramble(scramble) {
+        return Err("Only scrambles of face turns (U, R, F, D, L, B) can be followed".to_string());
+    }
+
+    let mut app = CubeTimer::load();
+    let solve = cube
+        .poll(&mut source, &mut app.timer)
+        .into_iter()
+        .next()
+        .ok_or("The log never reaches the scramble and then a solved cube")?;
+    let reconstruction = Reconstruction::analyze(scramble, &solve.moves).ok_or("The solve could not be reconstructed")?;
+
+    if save {
+        check_data(&app)?;
+        if let Some(name) = event {
+            app.current_event = parse_event(&app, name)?;
+        }
+        app.ensure_active_session();
+        app.current_scramble = scramble.to_string();
+        app.save_time_record(solve);
+        if let Some(error) = app.save_error.take() {
+            return Err(error);
+        }
+    }
+
+    if json {
+        let steps: Vec<Value> = reconstruction.steps
+            .iter()
+            .map(|step| json!({ "name": step.name, "moves": step.moves.join(" "), "count": step.moves.len(), "time": millis(step.duration) }))
+            .collect();
+        println!("{}", json!({
+            "time": millis(reconstruction.time),
+            "moves": reconstruction.move_count(),
+            "tps": reconstruction.tps(),
+            "steps": steps,
+            "saved": save,
+        }));
+        return Ok(());
+    }
+
+    for step in &reconstruction.steps {
+        let moves = if step.moves.is_empty() { "skip".to_string() } else { step.moves.join(" ") };
+        println!("{:<6}{} ({} moves, {})", step.name, moves, step.moves.len(), format_time(step.duration));
+    }
+    println!("{} moves in {}, {:.2} TPS", reconstruction.move_count(), format_time(reconstruction.time), reconstruction.tps());
+    if save {
+        println!("Saved to {}", app.scope_description());
+    }
+    Ok(())
+}
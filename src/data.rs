@@ -0,0 +1,725 @@
+// Solves, sessions and event settings of every front end, and the data files they are kept in
+use crate::export::{self, ExportFormat, ExportScope};
+use crate::import::{self, ImportFormat};
+use crate::model::DEFAULT_SCRAMBLE_LENGTH;
+use crate::scramble::{self, ScrambleType};
+use crate::storage::{self, Loaded};
+use crate::trainer::AlgSet;
+use crate::{CubeEvent, CustomEvent, EventSettings, SessionData, StandardEvent, StatColumn, TimeRecord};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+// Data files owned by this module, in the order they are loaded and saved
+pub const DATA_FILES: [&str; 4] = ["records.json", "custom_events.json", "event_settings.json", "sessions.json"];
+
+// A data file that exists but could not be loaded
+#[derive(Debug, Clone)]
+pub struct CorruptFile {
+    pub name: String,  // File name within the data directory
+    pub error: String, // Why it could not be loaded
+}
+
+// Narrows down the solves an export scope picks
+#[derive(Debug, Clone, Default)]
+pub struct ExportFilter {
+    pub from: String,          // First day of a date range (YYYY-MM-DD), empty for no limit
+    pub to: String,            // Last day of a date range (YYYY-MM-DD), empty for no limit
+    pub selection: Vec<usize>, // Indices of the selected records, in any order
+    pub scramble_type: ScrambleType, // Scramble type of the solves, except for a selection
+}
+
+// Everything that is saved, apart from the front ends' own appearance settings
+#[derive(Debug, Clone)]
+pub struct TimerData {
+    pub dir: Option<PathBuf>,                       // Directory of the data files, None to keep everything in memory
+    pub records: Vec<TimeRecord>,                   // Every solve, in the order they were done
+    pub sessions: SessionData,                      // Named solve sessions
+    pub custom_events: HashMap<String, CustomEvent>, // Custom event definitions keyed by name
+    pub event_settings: HashMap<String, EventSettings>, // Per-event settings keyed by event name
+    pub available_events: Vec<CubeEvent>,           // Built-in events followed by the custom ones
+    pub corrupt_files: Vec<CorruptFile>,            // Damaged data files waiting to be recovered
+}
+
+impl Default for TimerData {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            records: Vec::new(),
+            sessions: SessionData::default(),
+            custom_events: HashMap::new(),
+            event_settings: HashMap::new(),
+            available_events: StandardEvent::ALL
+                .into_iter()
+                .map(CubeEvent::Standard)
+                .chain(AlgSet::ALL.into_iter().map(CubeEvent::Training))
+                .collect(),
+            corrupt_files: Vec::new(),
+        }
+    }
+}
+
+impl TimerData {
+    // Loads every data file in a directory, noting the ones that are damaged
+    pub fn load(dir: PathBuf) -> Self {
+        let mut data = Self { dir: Some(dir), ..Self::default() };
+        for name in DATA_FILES {
+            data.reload_file(name);
+        }
+        data
+    }
+
+    // Reads a data file, recording it as damaged if it exists but cannot be parsed
+    pub fn load_file<T: DeserializeOwned>(&mut self, name: &str) -> Option<T> {
+        let path = self.dir.as_ref()?.join(name);
+        match storage::load(&path) {
+            Loaded::Data(value) => Some(value),
+            Loaded::Missing => None,
+            Loaded::Corrupt(error) => {
+                self.corrupt_files.retain(|f| f.name != name);
+                self.corrupt_files.push(CorruptFile { name: name.to_string(), error });
+                None
+            }
+        }
+    }
+
+    // Loads one of the data files again, e.g. after restoring it from a backup
+    pub fn reload_file(&mut self, name: &str) {
+        match name {
+            // Solves done before a recovery are kept
+            "records.json" => {
+                if let Some(mut records) = self.load_file::<Vec<TimeRecord>>(name) {
+                    records.append(&mut self.records);
+                    self.records = records;
+                }
+            }
+            "custom_events.json" => {
+                if let Some(mut custom_events) = self.load_file::<HashMap<String, CustomEvent>>(name) {
+                    // Broken definitions are left out, and the file kept until it is recovered
+                    let mut errors: Vec<String> = custom_events.values().filter_map(|event| event.validate().err()).collect();
+                    if !errors.is_empty() {
+                        errors.sort();
+                        custom_events.retain(|_, event| event.validate().is_ok());
+                        self.corrupt_files.retain(|f| f.name != name);
+                        self.corrupt_files.push(CorruptFile { name: name.to_string(), error: errors.join("; ") });
+                    }
+                    self.custom_events = custom_events;
+                    for name in self.custom_events.keys() {
+                        let custom_event = CubeEvent::Custom(name.clone());
+                        if !self.available_events.contains(&custom_event) {
+                            self.available_events.push(custom_event);
+                        }
+                    }
+                }
+            }
+            "event_settings.json" => {
+                if let Some(event_settings) = self.load_file(name) {
+                    self.event_settings = event_settings;
+                }
+            }
+            "sessions.json" => {
+                if let Some(sessions) = self.load_file(name) {
+                    self.sessions = sessions;
+                }
+            }
+            _ => return,
+        }
+        self.sessions.restore_missing(&self.records);
+        self.sessions.assign_unsessioned(&mut self.records);
+    }
+
+    // Writes one data file after backing up its previous contents, leaving damaged files
+    // untouched until they have been recovered
+    pub fn save_file<T: Serialize>(&self, name: &str, value: &T) -> Result<(), String> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        if self.corrupt_files.iter().any(|f| f.name == name) {
+            return Ok(());
+        }
+        storage::backup_if_due(dir, name).map_err(|e| format!("Could not back up {}: {}", name, e))?;
+        storage::save(&dir.join(name), value)
+    }
+
+    // Saves every data file, returning the first error
+    pub fn save(&self) -> Result<(), String> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        std::fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+
+        let results = [
+            self.save_file("records.json", &self.records),
+            self.save_file("custom_events.json", &self.custom_events),
+            self.save_file("event_settings.json", &self.event_settings),
+            self.save_file("sessions.json", &self.sessions),
+        ];
+        results.into_iter().collect()
+    }
+
+    // Replaces a damaged data file with one of its backups and loads it
+    pub fn restore_backup(&mut self, name: &str, backup: &Path) -> Result<(), String> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        std::fs::read(backup)
+            .and_then(|contents| storage::write_atomic(&dir.join(name), &contents))
+            .map_err(|e| format!("Could not restore {}: {}", backup.display(), e))?;
+        self.corrupt_files.retain(|f| f.name != name);
+        self.reload_file(name);
+        Ok(())
+    }
+
+    // Moves a damaged data file aside and continues without it
+    pub fn discard_corrupt_file(&mut self, name: &str) -> Result<(), String> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        storage::set_aside(&dir.join(name)).map_err(|e| format!("Could not move {} aside: {}", name, e))?;
+        self.corrupt_files.retain(|f| f.name != name);
+        Ok(())
+    }
+
+    // Returns the settings for an event, falling back to its defaults
+    pub fn event_settings(&self, event: &CubeEvent) -> EventSettings {
+        self.event_settings
+            .get(&event.to_string())
+            .cloned()
+            .unwrap_or_else(|| EventSettings::for_event(event))
+    }
+
+    // Part of the cube an event's scrambles currently randomize
+    pub fn scramble_type(&self, event: &CubeEvent) -> ScrambleType {
+        self.event_settings
+            .get(&event.to_string())
+            .map_or(ScrambleType::RandomState, |settings| scramble::scramble_type(event, settings))
+    }
+
+    // Returns the stat columns configured for an event
+    pub fn stat_columns(&self, event: &CubeEvent) -> Vec<StatColumn> {
+        let columns = self.event_settings(event).stat_columns;
+        if columns.is_empty() {
+            StatColumn::defaults_for(event)
+        } else {
+            columns
+        }
+    }
+
+    // Makes one of an event's sessions the active one, returning false for sessions of other events
+    pub fn switch_session(&mut self, event: &CubeEvent, id: u64) -> bool {
+        if !self.sessions.get(id).is_some_and(|s| s.event == *event) {
+            return false;
+        }
+        self.sessions.active.insert(event.to_string(), id);
+        true
+    }
+
+    // Renames a session
+    pub fn rename_session(&mut self, id: u64, name: String) {
+        if let Some(session) = self.sessions.sessions.iter_mut().find(|s| s.id == id) {
+            session.name = name;
+        }
+    }
+
+    // Archives or restores a session. An archived active session stays active when
+    // `stay_active` is set, otherwise its event moves to another session.
+    pub fn set_session_archived(&mut self, id: u64, archived: bool, stay_active: bool) {
+        let Some(session) = self.sessions.sessions.iter_mut().find(|s| s.id == id) else {
+            return;
+        };
+        session.archived = archived;
+        let event = session.event.clone();
+        if archived && !stay_active && self.sessions.active_id(&event) == id {
+            let next = self.sessions.sessions
+                .iter()
+                .find(|s| s.event == event && !s.archived)
+                .map(|s| s.id);
+            let next = next.unwrap_or_else(|| self.sessions.create(&event, "New Session".to_string()));
+            self.sessions.active.insert(event.to_string(), next);
+        }
+    }
+
+    // Moves every solve of one session into another and removes the emptied session
+    pub fn merge_sessions(&mut self, from: u64, into: u64) -> bool {
+        if from == into || self.sessions.get(into).is_none() {
+            return false;
+        }
+        for record in self.records.iter_mut().filter(|r| r.session == from) {
+            record.session = into;
+        }
+        self.sessions.sessions.retain(|s| s.id != from);
+        for active in self.sessions.active.values_mut() {
+            if *active == from {
+                *active = into;
+            }
+        }
+        true
+    }
+
+    // Adds a custom event, or replaces the definition of the one with its name
+    pub fn add_custom_event(&mut self, custom_event: CustomEvent) -> Result<String, String> {
+        let built_in = self.available_events.iter().filter(|event| !matches!(event, CubeEvent::Custom(_)));
+        if built_in.map(CubeEvent::to_string).any(|name| name == custom_event.name) {
+            return Err(format!("{} is already a built-in event", custom_event.name));
+        }
+
+        let name = custom_event.name.clone();
+        let event = CubeEvent::Custom(name.clone());
+        let replaced = self.custom_events.insert(name.clone(), custom_event).is_some();
+        if !self.available_events.contains(&event) {
+            self.available_events.push(event);
+        }
+        Ok(if replaced { format!("Updated {}", name) } else { format!("Added {}", name) })
+    }
+
+    // Removes a custom event
+    pub fn remove_custom_event(&mut self, name: &str) {
+        self.custom_events.remove(name);
+        self.available_events.retain(|event| !matches!(event, CubeEvent::Custom(custom_name) if custom_name == name));
+    }
+
+    // Imports the solves of another timer's backup file, see `import`
+    pub fn import_file(&mut self, path: &str, format: Option<ImportFormat>) -> Result<(usize, usize), String> {
+        let contents = std::fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+        self.import(&contents, format)
+    }
+
+    // Imports the solves of another timer's backup, skipping solves that were already imported,
+    // and returns how many solves were imported and skipped
+    pub fn import(&mut self, contents: &str, format: Option<ImportFormat>) -> Result<(usize, usize), String> {
+        let solves = import::parse(contents, format)?;
+        let total = solves.len();
+
+        // Solves are identified by event, time and the second they were done, or by event, time
+        // and scramble when the backup has no date for them
+        let key = |r: &TimeRecord| (r.event.to_string(), r.timestamp.timestamp(), r.time.as_millis());
+        let undated_key = |r: &TimeRecord| (r.event.to_string(), r.time.as_millis(), r.scramble.clone());
+        let mut existing: HashSet<_> = self.records.iter().map(key).collect();
+        let mut existing_undated: HashSet<_> = self.records.iter().map(undated_key).collect();
+
+        let mut imported = 0;
+        for solve in solves {
+            let mut record = solve.record;
+            let known = if solve.dated { existing.contains(&key(&record)) } else { existing_undated.contains(&undated_key(&record)) };
+            if known {
+                continue;
+            }
+            existing.insert(key(&record));
+            existing_undated.insert(undated_key(&record));
+
+            if let CubeEvent::Custom(name) = &record.event
+                && !self.custom_events.contains_key(name)
+            {
+                self.custom_events.insert(name.clone(), CustomEvent {
+                    name: name.clone(),
+                    scramble_length: DEFAULT_SCRAMBLE_LENGTH,
+                    ..CustomEvent::default()
+                });
+                self.available_events.push(record.event.clone());
+            }
+
+            // Re-importing reuses the session created by the previous import
+            record.session = match self.sessions.sessions
+                .iter()
+                .find(|s| s.event == record.event && s.name == solve.session_name)
+            {
+                Some(session) => session.id,
+                None => self.sessions.create(&record.event, solve.session_name),
+            };
+            self.records.push(record);
+            imported += 1;
+        }
+
+        // Keep the history in the order the solves were done
+        self.records.sort_by_key(|r| r.timestamp);
+        Ok((imported, total - imported))
+    }
+
+    // Returns the solves of an event covered by an export scope, in solve order. Drill solves
+    // are only exported with solves of the same scramble type.
+    pub fn export_records(&self, event: &CubeEvent, scope: ExportScope, filter: &ExportFilter) -> Result<Vec<&TimeRecord>, String> {
+        let parse_date = |text: &str| -> Result<Option<chrono::NaiveDate>, String> {
+            let text = text.trim();
+            if text.is_empty() {
+                return Ok(None);
+            }
+            chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .map(Some)
+                .map_err(|_| format!("Invalid date: {} (expected YYYY-MM-DD)", text))
+        };
+
+        let of_event = |r: &&TimeRecord| r.event == *event && r.scramble_type == filter.scramble_type;
+        let records: Vec<&TimeRecord> = match scope {
+            ExportScope::Event => self.records.iter().filter(of_event).collect(),
+            ExportScope::Session => {
+                let session = self.sessions.active_id(event);
+                self.records.iter().filter(of_event).filter(|r| r.session == session).collect()
+            }
+            ExportScope::DateRange => {
+                let from = parse_date(&filter.from)?;
+                let to = parse_date(&filter.to)?;
+                self.records
+                    .iter()
+                    .filter(of_event)
+                    .filter(|r| from.is_none_or(|from| r.timestamp.date_naive() >= from))
+                    .filter(|r| to.is_none_or(|to| r.timestamp.date_naive() <= to))
+                    .collect()
+            }
+            ExportScope::Selection => {
+                let mut indices = filter.selection.clone();
+                indices.sort();
+                indices.dedup();
+                indices.iter().filter_map(|&i| self.records.get(i)).collect()
+            }
+        };
+
+        if records.is_empty() {
+            return Err("No solves to export".to_string());
+        }
+        Ok(records)
+    }
+
+    // Builds the exported text and returns it with the number of solves it covers
+    pub fn export_contents(&self, event: &CubeEvent, format: ExportFormat, scope: ExportScope, filter: &ExportFilter) -> Result<(String, usize), String> {
+        let records = self.export_records(event, scope, filter)?;
+        let contents = match format {
+            ExportFormat::Csv => export::to_csv(&records),
+            ExportFormat::CsTimer => export::to_cstimer(&records, &self.sessions.sessions),
+            ExportFormat::Summary => {
+                let title = match scope {
+                    ExportScope::Session => {
+                        let name = self.sessions.get(self.sessions.active_id(event)).map(|s| s.name.clone()).unwrap_or_default();
+                        format!("{} - {}", event, name)
+                    }
+                    _ => format!("{} - {}", event, scope.name().to_lowercase()),
+                };
+                export::to_summary(&title, &records, &self.stat_columns(event))
+            }
+        };
+        Ok((contents, records.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Penalty;
+    use chrono::{Datelike, Local, TimeZone};
+    use std::time::Duration;
+
+    const CUBE_3X3: CubeEvent = CubeEvent::Standard(StandardEvent::Cube3x3);
+
+    fn record(session: u64, millis: u64, day: u32) -> TimeRecord {
+        let timestamp = Local.with_ymd_and_hms(2024, 3, day, 12, 0, 0).unwrap();
+        TimeRecord {
+            scramble: "R U R' U'".to_string(),
+            session,
+            ..TimeRecord::new(CUBE_3X3, Duration::from_millis(millis), timestamp)
+        }
+    }
+
+    // Data with two 3x3 sessions of two solves each, the first one active
+    fn two_sessions() -> (TimerData, u64, u64) {
+        let mut data = TimerData::default();
+        let first = data.sessions.create(&CUBE_3X3, "Main".to_string());
+        let second = data.sessions.create(&CUBE_3X3, "Practice".to_string());
+        data.sessions.active.insert(CUBE_3X3.to_string(), first);
+        data.records = vec![record(first, 10000, 1), record(second, 11000, 2), record(first, 12000, 3), record(second, 13000, 4)];
+        (data, first, second)
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("lstimer-data-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn created_sessions_get_new_ids() {
+        let (mut data, first, second) = two_sessions();
+        assert_ne!(first, second);
+        let third = data.sessions.create(&CUBE_3X3, "Comp sim".to_string());
+        assert!(third > second);
+        assert_eq!(data.sessions.get(third).unwrap().name, "Comp sim");
+        assert!(!data.sessions.get(third).unwrap().archived);
+    }
+
+    #[test]
+    fn merging_moves_solves_and_the_active_session() {
+        let (mut data, first, second) = two_sessions();
+        assert!(data.merge_sessions(first, second));
+        assert!(data.records.iter().all(|r| r.session == second));
+        assert!(data.sessions.get(first).is_none());
+        assert_eq!(data.sessions.active_id(&CUBE_3X3), second);
+
+        assert!(!data.merge_sessions(second, second));
+        assert!(!data.merge_sessions(second, 99));
+    }
+
+    #[test]
+    fn archiving_the_active_session_moves_to_another() {
+        let (mut data, first, second) = two_sessions();
+        data.set_session_archived(first, true, true);
+        assert!(data.sessions.get(first).unwrap().archived);
+        assert_eq!(data.sessions.active_id(&CUBE_3X3), first);
+
+        data.set_session_archived(first, true, false);
+        assert_eq!(data.sessions.active_id(&CUBE_3X3), second);
+
+        // With every session archived a new one is created
+        data.set_session_archived(second, true, false);
+        let active = data.sessions.active_id(&CUBE_3X3);
+        assert!(active != first && active != second);
+        assert!(!data.sessions.get(active).unwrap().archived);
+
+        data.set_session_archived(first, false, false);
+        assert!(!data.sessions.get(first).unwrap().archived);
+    }
+
+    #[test]
+    fn switching_only_accepts_sessions_of_the_event() {
+        let (mut data, _, second) = two_sessions();
+        let pyraminx = data.sessions.create(&CubeEvent::Standard(StandardEvent::Pyraminx), "Pyra".to_string());
+        assert!(!data.switch_session(&CUBE_3X3, pyraminx));
+        assert!(data.switch_session(&CUBE_3X3, second));
+        assert_eq!(data.sessions.active_id(&CUBE_3X3), second);
+
+        data.rename_session(second, "Slow".to_string());
+        assert_eq!(data.sessions.get(second).unwrap().name, "Slow");
+    }
+
+    #[test]
+    fn export_scopes_pick_their_solves() {
+        let (data, first, _) = two_sessions();
+        let times = |scope, filter: &ExportFilter| -> Vec<u128> {
+            data.export_records(&CUBE_3X3, scope, filter).unwrap().iter().map(|r| r.time.as_millis()).collect()
+        };
+
+        assert_eq!(times(ExportScope::Event, &ExportFilter::default()), [10000, 11000, 12000, 13000]);
+        assert_eq!(times(ExportScope::Session, &ExportFilter::default()), [10000, 12000]);
+        let dates = ExportFilter { from: "2024-03-02".to_string(), to: "2024-03-03".to_string(), ..ExportFilter::default() };
+        assert_eq!(times(ExportScope::DateRange, &dates), [11000, 12000]);
+        let selection = ExportFilter { selection: vec![3, 0, 3], ..ExportFilter::default() };
+        assert_eq!(times(ExportScope::Selection, &selection), [10000, 13000]);
+
+        let bad_date = ExportFilter { from: "March".to_string(), ..ExportFilter::default() };
+        assert!(data.export_records(&CUBE_3X3, ExportScope::DateRange, &bad_date).is_err());
+        assert!(data.export_records(&CUBE_3X3, ExportScope::Selection, &ExportFilter::default()).is_err());
+
+        let (summary, count) = data.export_contents(&CUBE_3X3, ExportFormat::Summary, ExportScope::Session, &ExportFilter::default()).unwrap();
+        assert_eq!(count, 2);
+        assert!(summary.starts_with("LSTimer statistics: 3x3x3 - Main"));
+        assert_eq!(data.records.iter().filter(|r| r.session == first).count(), 2);
+    }
+
+    #[test]
+    fn drill_solves_are_exported_apart_from_full_solves() {
+        let (mut data, first, _) = two_sessions();
+        let mut drill = record(first, 2000, 5);
+        drill.scramble_type = ScrambleType::LastLayer;
+        data.records.push(drill);
+
+        let event = data.export_records(&CUBE_3X3, ExportScope::Event, &ExportFilter::default()).unwrap();
+        assert_eq!(event.len(), 4);
+        assert!(event.iter().all(|r| r.scramble_type.is_random_state()));
+        let last_layer = ExportFilter { scramble_type: ScrambleType::LastLayer, ..ExportFilter::default() };
+        let drills = data.export_records(&CUBE_3X3, ExportScope::Session, &last_layer).unwrap();
+        assert_eq!(drills.len(), 1);
+        assert_eq!(drills[0].time, Duration::from_millis(2000));
+    }
+
+    #[test]
+    fn only_3x3_events_have_partial_scramble_types() {
+        let mut data = TimerData::default();
+        let skewb = CubeEvent::Standard(StandardEvent::Skewb);
+        for event in [CUBE_3X3, skewb.clone()] {
+            let settings = EventSettings { scramble_type: ScrambleType::Lse, ..EventSettings::for_event(&event) };
+            data.event_settings.insert(event.to_string(), settings);
+        }
+        assert_eq!(data.scramble_type(&CUBE_3X3), ScrambleType::Lse);
+        assert_eq!(data.scramble_type(&skewb), ScrambleType::RandomState);
+        assert_eq!(data.scramble_type(&CubeEvent::Standard(StandardEvent::OneHanded)), ScrambleType::RandomState);
+    }
+
+    #[test]
+    fn importing_twice_skips_known_solves() {
+        let backup = "\"333\";\"Normal\";\"12340\";\"1700000000000\";\"R U\";\"0\";\"\"\n\
+                      \"333\";\"OH\";\"20000\";\"1700000100000\";\"F2\";\"2\";\"\"\n\
+                      \"clock\";\"Normal\";\"8000\";\"1700000200000\";\"UR3+\";\"0\";\"\"\n";
+        let mut data = TimerData::default();
+        assert_eq!(data.import(backup, None).unwrap(), (3, 0));
+        assert_eq!(data.import(backup, None).unwrap(), (0, 3));
+        assert_eq!(data.records.len(), 3);
+
+        // Each event and session name gets one session, reused by later imports
+        let oh = CubeEvent::Standard(StandardEvent::OneHanded);
+        let oh_record = data.records.iter().find(|r| r.event == oh).unwrap();
+        assert_eq!(oh_record.penalty, Some(Penalty::DNF));
+        assert_eq!(data.sessions.get(oh_record.session).unwrap().name, "OH");
+        assert_eq!(data.sessions.sessions.len(), 3);
+    }
+
+    #[test]
+    fn importing_a_backup_again_skips_all_of_its_solves() {
+        let backups = [
+            include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/import/cstimer.json")),
+            include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/import/twisty_timer.txt")),
+            include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/import/cubedesk.json")),
+        ];
+        let mut data = TimerData::default();
+        for backup in backups {
+            let (imported, skipped) = data.import(backup, None).unwrap();
+            assert_eq!(skipped, 0);
+            assert_eq!(data.import(backup, None).unwrap(), (0, imported));
+        }
+        assert_eq!(data.records.len(), 16);
+    }
+
+    #[test]
+    fn undated_solves_are_recognized_when_the_backup_grows() {
+        let backup = |solves: &str| format!(r#"{{"session1":[{}],"session2":[[[0,4210],"R U'"]]}}"#, solves);
+        let first = backup(r#"[[0,11520],"R U",""],[[0,9870],"D' R2",""]"#);
+        // Solves done since, before and after the ones already imported
+        let grown = backup(r#"[[0,8000],"F2",""],[[0,11520],"R U",""],[[0,9870],"D' R2",""],[[0,7000],"B",""]"#);
+
+        let mut data = TimerData::default();
+        assert_eq!(data.import(&first, None).unwrap(), (3, 0));
+        assert_eq!(data.import(&grown, None).unwrap(), (2, 3));
+        assert_eq!(data.records.len(), 5);
+        // Undated solves are not moved to the start of the history
+        assert!(data.records.iter().all(|r| r.timestamp.year() >= 2025));
+    }
+
+    #[test]
+    fn imported_custom_events_become_available() {
+        let backup = "\"333\";\"Normal\";\"12340\";\"1700000000000\";\"R U\";\"0\";\"\"\n\
+                      \"kilominx\";\"Normal\";\"30000\";\"1700000000000\";\"R++\";\"0\";\"\"\n";
+        let mut data = TimerData::default();
+        data.import(backup, None).unwrap();
+        let kilominx = CubeEvent::Custom("kilominx".to_string());
+        assert!(data.available_events.contains(&kilominx));
+        assert_eq!(data.custom_events["kilominx"].scramble_length, DEFAULT_SCRAMBLE_LENGTH);
+    }
+
+    #[test]
+    fn custom_events_cannot_replace_built_in_ones() {
+        let mut data = TimerData::default();
+        let custom = |name: &str| CustomEvent { name: name.to_string(), scramble_length: 10, moves: vec!["R".to_string()], ..CustomEvent::default() };
+        assert!(data.add_custom_event(custom("Pyraminx")).is_err());
+        assert_eq!(data.add_custom_event(custom("Mirror")).unwrap(), "Added Mirror");
+        assert_eq!(data.add_custom_event(custom("Mirror")).unwrap(), "Updated Mirror");
+        assert_eq!(data.available_events.iter().filter(|e| **e == CubeEvent::Custom("Mirror".to_string())).count(), 1);
+
+        data.remove_custom_event("Mirror");
+        assert!(!data.available_events.contains(&CubeEvent::Custom("Mirror".to_string())));
+        assert!(data.custom_events.is_empty());
+    }
+
+    #[test]
+    fn saved_data_loads_back() {
+        let dir = temp_dir("roundtrip");
+        let (mut data, _, second) = two_sessions();
+        data.dir = Some(dir.clone());
+        data.event_settings.insert(CUBE_3X3.to_string(), EventSettings { inspection: false, ..EventSettings::for_event(&CUBE_3X3) });
+        data.save().unwrap();
+
+        let loaded = TimerData::load(dir.clone());
+        assert!(loaded.corrupt_files.is_empty());
+        assert_eq!(loaded.records.len(), 4);
+        assert_eq!(loaded.records[1].session, second);
+        assert_eq!(loaded.sessions.sessions.len(), 2);
+        assert!(!loaded.event_settings(&CUBE_3X3).inspection);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn lost_sessions_are_recreated_for_their_solves() {
+        let dir = temp_dir("lost-sessions");
+        let (mut data, first, second) = two_sessions();
+        data.dir = Some(dir.clone());
+        data.save().unwrap();
+
+        // Without sessions.json every solve still belongs to an existing session
+        std::fs::remove_file(dir.join("sessions.json")).unwrap();
+        let mut loaded = TimerData::load(dir.clone());
+        assert_eq!(loaded.sessions.sessions.len(), 2);
+        assert!(loaded.records.iter().all(|r| loaded.sessions.get(r.session).is_some_and(|s| s.event == r.event)));
+        assert_eq!(loaded.records[0].session, first);
+        assert_eq!(loaded.records[1].session, second);
+
+        // New sessions do not reuse the recovered ids
+        let created = loaded.sessions.create(&CUBE_3X3, "New".to_string());
+        assert!(created > first && created > second);
+
+        // The same goes for a damaged sessions.json
+        std::fs::write(dir.join("sessions.json"), "[").unwrap();
+        let loaded = TimerData::load(dir.clone());
+        assert_eq!(loaded.corrupt_files[0].name, "sessions.json");
+        assert!(loaded.records.iter().all(|r| loaded.sessions.get(r.session).is_some()));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn every_saved_file_is_backed_up() {
+        let dir = temp_dir("backups");
+        let data = TimerData { dir: Some(dir.clone()), ..TimerData::default() };
+        data.save().unwrap();
+        data.save_file("theme.json", &"dark").unwrap();
+        data.save_file("ui_state.json", &1).unwrap();
+        // Only files that already existed have contents worth backing up
+        assert!(storage::list_backups(&dir, "records.json").is_empty());
+
+        data.save().unwrap();
+        data.save_file("theme.json", &"light").unwrap();
+        data.save_file("ui_state.json", &2).unwrap();
+        for name in DATA_FILES.into_iter().chain(["theme.json", "ui_state.json"]) {
+            assert_eq!(storage::list_backups(&dir, name).len(), 1, "{}", name);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn damaged_files_are_kept_until_recovered() {
+        let dir = temp_dir("recovery");
+        let (mut data, _, _) = two_sessions();
+        data.dir = Some(dir.clone());
+        data.save().unwrap();
+        let backup = dir.join("records-backup.json");
+        std::fs::copy(dir.join("records.json"), &backup).unwrap();
+        std::fs::write(dir.join("records.json"), "{ not json").unwrap();
+
+        let mut loaded = TimerData::load(dir.clone());
+        assert_eq!(loaded.corrupt_files.len(), 1);
+        assert_eq!(loaded.corrupt_files[0].name, "records.json");
+        assert!(loaded.records.is_empty());
+
+        // Saving leaves the damaged file alone
+        loaded.save().unwrap();
+        assert_eq!(std::fs::read_to_string(dir.join("records.json")).unwrap(), "{ not json");
+
+        loaded.restore_backup("records.json", &backup).unwrap();
+        assert!(loaded.corrupt_files.is_empty());
+        assert_eq!(loaded.records.len(), 4);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn broken_custom_events_are_left_out_and_their_file_kept() {
+        let dir = temp_dir("custom-events");
+        let text = r#"{"Good":{"name":"Good","scramble_length":5,"moves":["R"]},"Bad":{"name":"Bad","scramble_length":5,"moves":["R"],"lines":{"length":0,"ends":["U"]}}}"#;
+        std::fs::write(dir.join("custom_events.json"), text).unwrap();
+
+        let data = TimerData::load(dir.clone());
+        assert_eq!(data.custom_events.keys().collect::<Vec<_>>(), ["Good"]);
+        assert!(data.available_events.contains(&CubeEvent::Custom("Good".to_string())));
+        assert!(!data.available_events.contains(&CubeEvent::Custom("Bad".to_string())));
+        assert_eq!(data.corrupt_files.len(), 1);
+        assert!(data.corrupt_files[0].error.starts_with("Bad: Line "), "{}", data.corrupt_files[0].error);
+
+        data.save().unwrap();
+        assert_eq!(std::fs::read_to_string(dir.join("custom_events.json")).unwrap(), text);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,328 @@
+// Exporters for sharing solves with spreadsheets and other timers
+use crate::stats::{self, format_result, format_time};
+use crate::{CubeEvent, Penalty, Session, SolveResult, StandardEvent, StatColumn, TimeRecord};
+use serde_json::{Value, json};
+
+// Formats solves can be exported to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Csv,     // One row per solve
+    CsTimer, // JSON backup that csTimer can import
+    Summary, // Plain-text summary statistics
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 3] = [ExportFormat::Csv, ExportFormat::CsTimer, ExportFormat::Summary];
+
+    // Display name of the format
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::CsTimer => "csTimer backup",
+            ExportFormat::Summary => "Summary statistics",
+        }
+    }
+
+    // File extension used for the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::CsTimer => "json",
+            ExportFormat::Summary => "txt",
+        }
+    }
+}
+
+// Which solves of the current event get exported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportScope {
+    #[default]
+    Event,     // Every solve of the event
+    Session,   // Solves of the active session
+    DateRange, // Solves done between two dates
+    Selection, // The selected or highlighted solves
+}
+
+impl ExportScope {
+    pub const ALL: [ExportScope; 4] = [ExportScope::Event, ExportScope::Session, ExportScope::DateRange, ExportScope::Selection];
+
+    // Display name of the scope
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportScope::Event => "Current event",
+            ExportScope::Session => "Current session",
+            ExportScope::DateRange => "Date range",
+            ExportScope::Selection => "Selection",
+        }
+    }
+}
+
+// Quotes a CSV field when it contains a separator, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// Writes one row per solve: number, time, penalty, scramble, date and comment
+pub fn to_csv(records: &[&TimeRecord]) -> String {
+    let mut csv = String::from("No.,Time,Penalty,Scramble,Date,Comment\n");
+    for (i, record) in records.iter().enumerate() {
+        let penalty = match record.penalty {
+            Some(Penalty::Plus2) => "+2",
+            Some(Penalty::DNF) => "DNF",
+            None => "",
+        };
+        let row = [
+            (i + 1).to_string(),
+            format_time(record.time),
+            penalty.to_string(),
+            record.scramble.clone(),
+            record.timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+            record.comment.clone(),
+        ];
+        let row: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+// Maps an event to the csTimer scramble type that imports back to it
+fn cstimer_scramble_type(event: &CubeEvent) -> &'static str {
+    match event {
+        CubeEvent::Standard(event) => match event {
+            StandardEvent::Cube3x3 => "333",
+            StandardEvent::Cube2x2 => "222so",
+            StandardEvent::Cube4x4 => "444wca",
+            StandardEvent::Cube5x5 => "555wca",
+            StandardEvent::Cube6x6 => "666wca",
+            StandardEvent::Cube7x7 => "777wca",
+            StandardEvent::Pyraminx => "pyrso",
+            StandardEvent::Megaminx => "mgmp",
+            StandardEvent::Skewb => "skbso",
+            StandardEvent::Square1 => "sqrs",
+            StandardEvent::Clock => "clkwca",
+            StandardEvent::OneHanded => "333oh",
+            StandardEvent::Blindfolded => "333ni",
+            StandardEvent::FeetSolving => "333ft",
+        },
+        // Custom events and trainers have no csTimer scrambler, so their scrambles are kept as typed input
+        CubeEvent::Custom(_) | CubeEvent::Training(_) => "input",
+    }
+}
+
+// Writes a csTimer backup with one csTimer session per LSTimer session
+pub fn to_cstimer(records: &[&TimeRecord], sessions: &[Session]) -> String {
+    let mut session_ids: Vec<u64> = Vec::new();
+    for record in records {
+        if !session_ids.contains(&record.session) {
+            session_ids.push(record.session);
+        }
+    }
+
+    let mut backup = serde_json::Map::new();
+    let mut session_data = serde_json::Map::new();
+    for (i, &id) in session_ids.iter().enumerate() {
+        let number = i + 1;
+        let solves: Vec<Value> = records
+            .iter()
+            .filter(|r| r.session == id)
+            .map(|r| {
+                let penalty = match r.penalty {
+                    Some(Penalty::Plus2) => 2000,
+                    Some(Penalty::DNF) => -1,
+                    None => 0,
+                };
+                json!([[penalty, r.time.as_millis() as u64], r.scramble, r.comment, r.timestamp.timestamp()])
+            })
+            .collect();
+        backup.insert(format!("session{}", number), Value::Array(solves));
+
+        let session = sessions.iter().find(|s| s.id == id);
+        let event = records.iter().find(|r| r.session == id).map(|r| r.event.clone());
+        let name = session.map(|s| s.name.clone()).unwrap_or_else(|| number.to_string());
+        let name = match event {
+            Some(event) => json!({ "name": name, "opt": { "scrType": cstimer_scramble_type(&event) }, "rank": number }),
+            None => json!({ "name": name, "rank": number }),
+        };
+        session_data.insert(number.to_string(), name);
+    }
+
+    // csTimer stores its session list as a JSON string inside the properties
+    let properties = json!({
+        "sessionN": session_ids.len(),
+        "sessionData": Value::Object(session_data).to_string(),
+    });
+    backup.insert("properties".to_string(), properties);
+    Value::Object(backup).to_string()
+}
+
+// Writes summary statistics: counts, best, worst, mean and each stat column's latest and best value
+pub fn to_summary(title: &str, records: &[&TimeRecord], columns: &[StatColumn]) -> String {
+    let results: Vec<SolveResult> = records.iter().map(|r| r.result()).collect();
+    let finished: Vec<_> = results
+        .iter()
+        .filter_map(|r| match r {
+            SolveResult::Time(time) => Some(*time),
+            SolveResult::DNF => None,
+        })
+        .collect();
+
+    let mut lines = vec![
+        format!("LSTimer statistics: {}", title),
+        format!("Generated: {}", chrono::Local::now().format("%Y-%m-%d %H:%M")),
+        String::new(),
+        format!("Solves: {}", results.len()),
+        format!("DNFs: {}", results.len() - finished.len()),
+    ];
+    if let (Some(first), Some(last)) = (records.first(), records.last()) {
+        lines.push(format!(
+            "Dates: {} to {}",
+            first.timestamp.format("%Y-%m-%d"),
+            last.timestamp.format("%Y-%m-%d")
+        ));
+    }
+    if let Some(best) = finished.iter().min() {
+        lines.push(format!("Best: {}", format_time(*best)));
+    }
+    if let Some(worst) = results.iter().max() {
+        lines.push(format!("Worst: {}", format_result(*worst)));
+    }
+    if !finished.is_empty() {
+        let mean = finished.iter().sum::<std::time::Duration>() / finished.len() as u32;
+        lines.push(format!("Mean: {}", format_time(mean)));
+    }
+
+    for column in columns {
+        let Some(current) = stats::calculate_last_average(&results, column) else {
+            continue;
+        };
+        let best = stats::best_rolling_average(&results, column)
+            .map_or("DNF".to_string(), |(best, _)| format_time(best));
+        lines.push(format!(
+            "{}: current {}, best {}",
+            column.label(),
+            format_result(current),
+            best
+        ));
+    }
+
+    if !records.is_empty() {
+        lines.push(String::new());
+        lines.push("Times:".to_string());
+        for (i, record) in records.iter().enumerate() {
+            let time = match record.penalty {
+                Some(Penalty::Plus2) => format!("{}+", format_result(record.result())),
+                Some(Penalty::DNF) => format!("DNF({})", format_time(record.time)),
+                None => format_time(record.time),
+            };
+            lines.push(format!("{}. {}   {}", i + 1, time, record.scramble.replace('\n', " ")));
+        }
+    }
+
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import;
+    use crate::scramble::ScrambleType;
+    use chrono::{Local, TimeZone};
+    use std::time::Duration;
+
+    fn record(event: StandardEvent, millis: u64, penalty: Option<Penalty>, scramble: &str, comment: &str, minute: u32, session: u64) -> TimeRecord {
+        let timestamp = Local.with_ymd_and_hms(2024, 3, 1, 10, minute, 0).unwrap();
+        TimeRecord {
+            scramble: scramble.to_string(),
+            penalty,
+            comment: comment.to_string(),
+            session,
+            ..TimeRecord::new(CubeEvent::Standard(event), Duration::from_millis(millis), timestamp)
+        }
+    }
+
+    // Solves whose scrambles and comments hold every character CSV has to quote
+    fn awkward_solves() -> Vec<TimeRecord> {
+        vec![
+            record(StandardEvent::Cube3x3, 12340, None, "R U R' U'", "good, clean", 0, 1),
+            record(StandardEvent::Cube3x3, 10000, Some(Penalty::Plus2), "R++ D--\nU'", "said \"wow\"\nthen left", 1, 1),
+            record(StandardEvent::Square1, 15500, Some(Penalty::DNF), "(1,0) / (-3,3)", "", 2, 2),
+        ]
+    }
+
+    #[test]
+    fn csv_quotes_fields_with_separators_quotes_and_line_breaks() {
+        let solves = awkward_solves();
+        let csv = to_csv(&solves.iter().collect::<Vec<_>>());
+        assert_eq!(
+            csv,
+            "No.,Time,Penalty,Scramble,Date,Comment\n\
+             1,12.340,,R U R' U',2024-03-01 10:00:00,\"good, clean\"\n\
+             2,10.000,+2,\"R++ D--\nU'\",2024-03-01 10:01:00,\"said \"\"wow\"\"\nthen left\"\n\
+             3,15.500,DNF,\"(1,0) / (-3,3)\",2024-03-01 10:02:00,\n"
+        );
+        assert_eq!(to_csv(&[]), "No.,Time,Penalty,Scramble,Date,Comment\n");
+    }
+
+    #[test]
+    fn cstimer_backups_import_back_unchanged() {
+        let solves = awkward_solves();
+        let session = |id, name: &str, event| Session {
+            id,
+            name: name.to_string(),
+            event: CubeEvent::Standard(event),
+            archived: false,
+            created: Local.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+        };
+        let sessions = [session(1, "Main", StandardEvent::Cube3x3), session(2, "Sq", StandardEvent::Square1)];
+        let backup = to_cstimer(&solves.iter().collect::<Vec<_>>(), &sessions);
+
+        let imported = import::parse(&backup, None).unwrap();
+        assert_eq!(imported.len(), solves.len());
+        for (solve, original) in imported.iter().zip(&solves) {
+            let record = &solve.record;
+            assert_eq!(record.event, original.event);
+            assert_eq!((record.time, record.penalty.clone()), (original.time, original.penalty.clone()));
+            assert_eq!((record.scramble.as_str(), record.comment.as_str()), (original.scramble.as_str(), original.comment.as_str()));
+            assert_eq!(record.timestamp, original.timestamp);
+        }
+        let names: Vec<&str> = imported.iter().map(|s| s.session_name.as_str()).collect();
+        assert_eq!(names, ["Main", "Main", "Sq"]);
+    }
+
+    #[test]
+    fn summaries_list_statistics_and_times() {
+        let solves = awkward_solves();
+        let summary = to_summary("3x3x3 - Main", &solves.iter().collect::<Vec<_>>(), &[StatColumn::mean(3), StatColumn::mean(2), StatColumn::average(5)]);
+        let lines: Vec<&str> = summary.lines().collect();
+        assert_eq!(lines[0], "LSTimer statistics: 3x3x3 - Main");
+        assert!(lines[1].starts_with("Generated: "), "{}", lines[1]);
+        assert_eq!(
+            lines[2..],
+            [
+                "",
+                "Solves: 3",
+                "DNFs: 1",
+                "Dates: 2024-03-01 to 2024-03-01",
+                "Best: 12.000",
+                "Worst: DNF",
+                "Mean: 12.170",
+                "Mo3: current DNF, best DNF",
+                "Mo2: current DNF, best 12.170",
+                "",
+                "Times:",
+                "1. 12.340   R U R' U'",
+                "2. 12.000+   R++ D-- U'",
+                "3. DNF(15.500)   (1,0) / (-3,3)",
+            ]
+        );
+
+        let empty = to_summary("Skewb - Main", &[], &[StatColumn::average(5)]);
+        assert_eq!(empty.lines().skip(2).collect::<Vec<_>>(), ["", "Solves: 0", "DNFs: 0"]);
+    }
+}
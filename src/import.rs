@@ -0,0 +1,446 @@
+// Importers for the backup formats of other timers
+use crate::{CubeEvent, Penalty, StandardEvent, TimeRecord};
+use chrono::{DateTime, Local, TimeZone};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+
+// Backup formats that can be imported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    CsTimer,     // csTimer JSON backup
+    TwistyTimer, // Twisty Timer CSV backup
+    CubeDesk,    // CubeDesk JSON export
+}
+
+impl ImportFormat {
+    pub const ALL: [ImportFormat; 3] = [ImportFormat::CsTimer, ImportFormat::TwistyTimer, ImportFormat::CubeDesk];
+
+    // Display name of the format
+    pub fn name(&self) -> &'static str {
+        match self {
+            ImportFormat::CsTimer => "csTimer",
+            ImportFormat::TwistyTimer => "Twisty Timer",
+            ImportFormat::CubeDesk => "CubeDesk",
+        }
+    }
+
+    // Guesses the format of a backup from its contents
+    pub fn detect(contents: &str) -> Option<Self> {
+        match serde_json::from_str::<Value>(contents) {
+            Ok(Value::Object(map)) => {
+                if map.contains_key("solves") {
+                    Some(ImportFormat::CubeDesk)
+                } else if map.keys().any(|key| key.starts_with("session")) {
+                    Some(ImportFormat::CsTimer)
+                } else {
+                    None
+                }
+            }
+            Ok(_) => None,
+            Err(_) => contents.contains(';').then_some(ImportFormat::TwistyTimer),
+        }
+    }
+}
+
+// A solve read from a backup, with the name of the session it came from
+#[derive(Debug, Clone)]
+pub struct ImportedSolve {
+    pub record: TimeRecord, // The solve, not yet assigned to a session
+    pub session_name: String, // Name of the session in the other timer
+    pub dated: bool, // False when the backup has no date for the solve, see `date_undated`
+}
+
+// Parses a backup, detecting its format when none is given
+pub fn parse(contents: &str, format: Option<ImportFormat>) -> Result<Vec<ImportedSolve>, String> {
+    let format = match format {
+        Some(format) => format,
+        None => ImportFormat::detect(contents).ok_or("Unrecognized backup format")?,
+    };
+    let mut solves = match format {
+        ImportFormat::CsTimer => parse_cstimer(contents),
+        ImportFormat::TwistyTimer => parse_twisty_timer(contents),
+        ImportFormat::CubeDesk => parse_cubedesk(contents),
+    }?;
+    date_undated(&mut solves, Local::now());
+    Ok(solves)
+}
+
+// Dates the solves a backup has no date for at the import time, a millisecond apart per
+// session so they keep their order. Their dates are made up, so imports recognize them
+// by their event, time and scramble instead.
+fn date_undated(solves: &mut [ImportedSolve], now: DateTime<Local>) {
+    let mut counts: HashMap<String, i64> = HashMap::new();
+    for solve in solves.iter_mut().filter(|solve| !solve.dated) {
+        let count = counts.entry(solve.session_name.clone()).or_default();
+        solve.record.timestamp = now + chrono::Duration::milliseconds(*count);
+        *count += 1;
+    }
+}
+
+// Builds a record with the fields every format provides
+fn record(event: CubeEvent, time: Duration, penalty: Option<Penalty>, scramble: &str, comment: &str, timestamp: DateTime<Local>) -> TimeRecord {
+    TimeRecord {
+        scramble: scramble.to_string(),
+        penalty,
+        comment: comment.to_string(),
+        ..TimeRecord::new(event, time, timestamp)
+    }
+}
+
+// Reads a number that may also be stored as a string
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+// Converts Unix milliseconds to local time
+fn from_millis(millis: i64) -> Option<DateTime<Local>> {
+    Local.timestamp_millis_opt(millis).single()
+}
+
+// Parses a csTimer backup: `sessionN` keys hold lists of `[[penalty, ms], scramble, comment, unix]`
+// and `properties.sessionData` names the sessions and their scramble types
+fn parse_cstimer(contents: &str) -> Result<Vec<ImportedSolve>, String> {
+    let backup: Value = serde_json::from_str(contents).map_err(|e| format!("Invalid csTimer backup: {}", e))?;
+    let map = backup.as_object().ok_or("Invalid csTimer backup: expected an object")?;
+
+    // Session data is stored as a JSON string inside the properties
+    let session_data = match backup.pointer("/properties/sessionData") {
+        Some(Value::String(text)) => serde_json::from_str(text).unwrap_or(Value::Null),
+        Some(value) => value.clone(),
+        None => Value::Null,
+    };
+
+    let mut keys: Vec<(u64, &String)> = map
+        .keys()
+        .filter_map(|key| Some((key.strip_prefix("session")?.parse().ok()?, key)))
+        .collect();
+    keys.sort();
+
+    let mut solves = Vec::new();
+    for (number_in_backup, key) in keys {
+        let Some(list) = map[key].as_array() else {
+            continue;
+        };
+        let info = &session_data[number_in_backup.to_string()];
+        let session_name = match &info["name"] {
+            Value::String(name) => name.clone(),
+            Value::Number(n) => n.to_string(),
+            _ => number_in_backup.to_string(),
+        };
+        let scramble_type = info["opt"]["scrType"].as_str().unwrap_or("333");
+        let event = cstimer_event(scramble_type).unwrap_or_else(|| CubeEvent::Custom(session_name.clone()));
+
+        for entry in list {
+            let (Some(penalty), Some(millis)) = (number(&entry[0][0]), number(&entry[0][1])) else {
+                continue;
+            };
+            let penalty = match penalty as i64 {
+                -1 => Some(Penalty::DNF),
+                0 => None,
+                _ => Some(Penalty::Plus2),
+            };
+            let date = number(&entry[3]).and_then(|secs| from_millis(secs as i64 * 1000));
+            solves.push(ImportedSolve {
+                record: record(
+                    event.clone(),
+                    Duration::from_millis(millis.max(0.0) as u64),
+                    penalty,
+                    entry[1].as_str().unwrap_or(""),
+                    entry[2].as_str().unwrap_or(""),
+                    date.unwrap_or_default(),
+                ),
+                session_name: session_name.clone(),
+                dated: date.is_some(),
+            });
+        }
+    }
+    Ok(solves)
+}
+
+// Maps a csTimer scramble type to an event
+fn cstimer_event(scramble_type: &str) -> Option<CubeEvent> {
+    let event = match scramble_type {
+        "333" | "333o" | "3gen_F" | "" => StandardEvent::Cube3x3,
+        "222so" | "222o" | "2223" | "222" => StandardEvent::Cube2x2,
+        "444wca" | "444m" | "444o" | "444" => StandardEvent::Cube4x4,
+        "555wca" | "555" => StandardEvent::Cube5x5,
+        "666wca" | "666si" | "666p" => StandardEvent::Cube6x6,
+        "777wca" | "777si" | "777p" => StandardEvent::Cube7x7,
+        "pyrso" | "pyro" | "pyrm" => StandardEvent::Pyraminx,
+        "mgmp" | "mgmo" | "minx" => StandardEvent::Megaminx,
+        "skbso" | "skbo" | "skb" => StandardEvent::Skewb,
+        "sqrs" | "sq1h" | "sq1t" => StandardEvent::Square1,
+        "clkwca" | "clko" | "clk" => StandardEvent::Clock,
+        "333oh" => StandardEvent::OneHanded,
+        "333ni" => StandardEvent::Blindfolded,
+        "333ft" => StandardEvent::FeetSolving,
+        _ => return None,
+    };
+    Some(CubeEvent::Standard(event))
+}
+
+// Splits one CSV line into fields, honoring double-quoted fields with "" escapes
+fn split_csv_line(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+// Parses a Twisty Timer backup with lines of
+// "Puzzle";"Category";"Time(millis)";"Date(millis)";"Scramble";"Penalty";"Comment"
+fn parse_twisty_timer(contents: &str) -> Result<Vec<ImportedSolve>, String> {
+    let mut solves = Vec::new();
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        let fields = split_csv_line(line.trim_end_matches('\r'), ';');
+        if fields.len() < 6 {
+            return Err(format!("Invalid Twisty Timer line: {}", line));
+        }
+        // Skip a header line, if any
+        let Ok(millis) = fields[2].trim().parse::<u64>() else {
+            continue;
+        };
+        let date = fields[3].trim().parse().ok().and_then(from_millis);
+
+        let category = fields[1].trim();
+        let event = twisty_timer_event(fields[0].trim(), category)
+            .unwrap_or_else(|| CubeEvent::Custom(fields[0].trim().to_string()));
+        // Twisty Timer stores +2 solves with the penalty already added
+        let (penalty, time) = match fields[5].trim() {
+            "1" => (Some(Penalty::Plus2), millis.saturating_sub(2000)),
+            "2" => (Some(Penalty::DNF), millis),
+            _ => (None, millis),
+        };
+        let comment = fields.get(6).map(String::as_str).unwrap_or("");
+
+        solves.push(ImportedSolve {
+            record: record(
+                event,
+                Duration::from_millis(time),
+                penalty,
+                &fields[4],
+                comment,
+                date.unwrap_or_default(),
+            ),
+            session_name: category.to_string(),
+            dated: date.is_some(),
+        });
+    }
+    Ok(solves)
+}
+
+// Maps a Twisty Timer puzzle and category to an event; 3x3 categories may name OH, BLD or feet
+fn twisty_timer_event(puzzle: &str, category: &str) -> Option<CubeEvent> {
+    let category = category.to_lowercase();
+    let event = match puzzle {
+        "333" if category.contains("oh") || category.contains("one") => StandardEvent::OneHanded,
+        "333" if category.contains("bld") || category.contains("blind") => StandardEvent::Blindfolded,
+        "333" if category.contains("feet") => StandardEvent::FeetSolving,
+        "333" => StandardEvent::Cube3x3,
+        "222" => StandardEvent::Cube2x2,
+        "444" => StandardEvent::Cube4x4,
+        "555" => StandardEvent::Cube5x5,
+        "666" => StandardEvent::Cube6x6,
+        "777" => StandardEvent::Cube7x7,
+        "pyra" => StandardEvent::Pyraminx,
+        "mega" => StandardEvent::Megaminx,
+        "skewb" => StandardEvent::Skewb,
+        "sq1" => StandardEvent::Square1,
+        "clock" => StandardEvent::Clock,
+        _ => return None,
+    };
+    Some(CubeEvent::Standard(event))
+}
+
+// Parses a CubeDesk export: a `sessions` list of ids and names and a `solves` list
+// with times in seconds and timestamps in Unix milliseconds
+fn parse_cubedesk(contents: &str) -> Result<Vec<ImportedSolve>, String> {
+    let export: Value = serde_json::from_str(contents).map_err(|e| format!("Invalid CubeDesk export: {}", e))?;
+    let list = export["solves"].as_array().ok_or("Invalid CubeDesk export: missing solves")?;
+
+    let session_name = |id: &Value| -> String {
+        export["sessions"]
+            .as_array()
+            .and_then(|sessions| sessions.iter().find(|s| s["id"] == *id))
+            .and_then(|s| s["name"].as_str())
+            .unwrap_or("CubeDesk")
+            .to_string()
+    };
+
+    let mut solves = Vec::new();
+    for solve in list {
+        let Some(seconds) = number(&solve["raw_time"]).or_else(|| number(&solve["time"])) else {
+            continue;
+        };
+        let cube_type = solve["cube_type"].as_str().unwrap_or("333");
+        let event = cubedesk_event(cube_type).unwrap_or_else(|| CubeEvent::Custom(cube_type.to_string()));
+        let penalty = if solve["dnf"].as_bool().unwrap_or(false) {
+            Some(Penalty::DNF)
+        } else if solve["plus_two"].as_bool().unwrap_or(false) {
+            Some(Penalty::Plus2)
+        } else {
+            None
+        };
+        let date = number(&solve["ended_at"])
+            .or_else(|| number(&solve["started_at"]))
+            .and_then(|millis| from_millis(millis as i64))
+            .or_else(|| {
+                let created = solve["created_at"].as_str()?;
+                Some(DateTime::parse_from_rfc3339(created).ok()?.with_timezone(&Local))
+            });
+
+        solves.push(ImportedSolve {
+            record: record(
+                event,
+                Duration::from_millis((seconds.max(0.0) * 1000.0).round() as u64),
+                penalty,
+                solve["scramble"].as_str().unwrap_or(""),
+                solve["notes"].as_str().unwrap_or(""),
+                date.unwrap_or_default(),
+            ),
+            session_name: session_name(&solve["session_id"]),
+            dated: date.is_some(),
+        });
+    }
+    Ok(solves)
+}
+
+// Maps a CubeDesk cube type to an event
+fn cubedesk_event(cube_type: &str) -> Option<CubeEvent> {
+    let event = match cube_type {
+        "333" => StandardEvent::Cube3x3,
+        "222" => StandardEvent::Cube2x2,
+        "444" => StandardEvent::Cube4x4,
+        "555" => StandardEvent::Cube5x5,
+        "666" => StandardEvent::Cube6x6,
+        "777" => StandardEvent::Cube7x7,
+        "pyram" => StandardEvent::Pyraminx,
+        "minx" => StandardEvent::Megaminx,
+        "skewb" => StandardEvent::Skewb,
+        "sq1" => StandardEvent::Square1,
+        "clock" => StandardEvent::Clock,
+        "333oh" => StandardEvent::OneHanded,
+        "333bl" | "333bld" => StandardEvent::Blindfolded,
+        "333ft" => StandardEvent::FeetSolving,
+        _ => return None,
+    };
+    Some(CubeEvent::Standard(event))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! fixture {
+        ($name:literal) => {
+            include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/import/", $name))
+        };
+    }
+
+    // Unix seconds (0 for undated solves), event, time, penalty and session of each solve
+    fn summary(solves: &[ImportedSolve]) -> Vec<(i64, String, u128, Option<Penalty>, &str)> {
+        solves
+            .iter()
+            .map(|s| (if s.dated { s.record.timestamp.timestamp() } else { 0 }, s.record.event.to_string(), s.record.time.as_millis(), s.record.penalty.clone(), s.session_name.as_str()))
+            .collect()
+    }
+
+    fn event(event: StandardEvent) -> String {
+        CubeEvent::Standard(event).to_string()
+    }
+
+    #[test]
+    fn formats_are_detected() {
+        assert_eq!(ImportFormat::detect(fixture!("cstimer.json")), Some(ImportFormat::CsTimer));
+        assert_eq!(ImportFormat::detect(fixture!("twisty_timer.txt")), Some(ImportFormat::TwistyTimer));
+        assert_eq!(ImportFormat::detect(fixture!("cubedesk.json")), Some(ImportFormat::CubeDesk));
+        assert_eq!(ImportFormat::detect("[1, 2]"), None);
+        assert_eq!(parse("just text", None).unwrap_err(), "Unrecognized backup format");
+    }
+
+    #[test]
+    fn cstimer_backups_are_read() {
+        let solves = parse(fixture!("cstimer.json"), None).unwrap();
+        let (cube3x3, cube2x2) = (event(StandardEvent::Cube3x3), event(StandardEvent::Cube2x2));
+        assert_eq!(
+            summary(&solves),
+            [
+                (1700000000, cube3x3.clone(), 11520, None, "Main"),
+                (1700000060, cube3x3.clone(), 13040, Some(Penalty::Plus2), "Main"),
+                (1700000120, cube3x3.clone(), 20110, Some(Penalty::DNF), "Main"),
+                (0, cube3x3, 9870, None, "Main"),
+                (1700001000, cube2x2.clone(), 4210, None, "Twos"),
+                (0, cube2x2, 5330, None, "Twos"),
+            ]
+        );
+        assert_eq!(solves[1].record.comment, "+2, then a \"good\" one");
+        assert_eq!(solves[1].record.scramble, "B2 L' D");
+    }
+
+    #[test]
+    fn twisty_timer_backups_are_read() {
+        let solves = parse(fixture!("twisty_timer.txt"), None).unwrap();
+        assert_eq!(
+            summary(&solves),
+            [
+                (1700000000, event(StandardEvent::Cube3x3), 12340, None, "Normal"),
+                (1700000100, event(StandardEvent::OneHanded), 20000, Some(Penalty::Plus2), "OH"),
+                (1700000200, event(StandardEvent::Clock), 8000, Some(Penalty::DNF), "Normal"),
+                (0, event(StandardEvent::Cube3x3), 11110, None, "Normal"),
+                (0, event(StandardEvent::Skewb), 6000, None, "Normal"),
+            ]
+        );
+        assert_eq!(solves[1].record.comment, "slipped; then \"fixed\"");
+        assert_eq!(parse("\"333\";\"Normal\";\"1000\"", Some(ImportFormat::TwistyTimer)).unwrap_err(), "Invalid Twisty Timer line: \"333\";\"Normal\";\"1000\"");
+    }
+
+    #[test]
+    fn cubedesk_exports_are_read() {
+        let solves = parse(fixture!("cubedesk.json"), None).unwrap();
+        assert_eq!(
+            summary(&solves),
+            [
+                (1700000000, event(StandardEvent::Cube3x3), 10500, None, "Home"),
+                (1700000050, event(StandardEvent::Cube2x2), 7250, Some(Penalty::Plus2), "Home"),
+                (1700000100, event(StandardEvent::OneHanded), 31000, Some(Penalty::DNF), "CubeDesk"),
+                (0, event(StandardEvent::Cube3x3), 9000, None, "Home"),
+                (0, "fto".to_string(), 15000, None, "Home"),
+            ]
+        );
+        assert_eq!(solves[3].record.comment, "no date");
+    }
+
+    #[test]
+    fn undated_solves_are_dated_at_the_import_in_session_order() {
+        let now = Local.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap();
+        let mut solves = parse(fixture!("cstimer.json"), None).unwrap();
+        solves[0].dated = false;
+        date_undated(&mut solves, now);
+        let undated: Vec<(&str, i64)> = solves
+            .iter()
+            .filter(|s| !s.dated)
+            .map(|s| (s.session_name.as_str(), (s.record.timestamp - now).num_milliseconds()))
+            .collect();
+        assert_eq!(undated, [("Main", 0), ("Main", 1), ("Twos", 0)]);
+        assert_eq!(solves[1].record.timestamp.timestamp(), 1700000060);
+    }
+}
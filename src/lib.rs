@@ -0,0 +1,19 @@
+// Timer core shared by the window, terminal and command-line front ends
+pub mod data;
+pub mod export;
+pub mod import;
+pub mod model;
+pub mod puzzle;
+pub mod scramble;
+pub mod smart_cube;
+pub mod smart_timer;
+pub mod stackmat;
+pub mod stats;
+pub mod storage;
+pub mod timing;
+pub mod trainer;
+
+pub use data::{CorruptFile, ExportFilter, TimerData};
+pub use model::{CubeEvent, CustomEvent, EventSettings, Penalty, ScrambleLines, Session, SessionData, SolveResult, StandardEvent, TimeRecord, TimedMove};
+pub use stats::{PersonalBest, StatColumn, StatKind, Statistics};
+pub use timing::{Clock, FinishedSolve, ManualClock, SystemClock, Timer, TimerState};
@@ -1,101 +1,41 @@
-use chrono::{DateTime, Local};
+mod cli;
+mod tui;
+
+use chrono::Local;
 use eframe::egui;
 use egui::{Color32, RichText, Rounding, Stroke, Vec2};
-use egui_plot::{Legend, Line, Plot, PlotPoints};
-use rand::Rng;
+use egui_plot::{Bar, BarChart, Legend, Line, Plot, PlotPoints};
+use lstimer::export::{ExportFormat, ExportScope};
+use lstimer::import::ImportFormat;
+use lstimer::stats::{format_result, format_time};
+#[cfg(feature = "stackmat-audio")]
+use lstimer::stackmat::{self, capture::StackmatCapture};
+use lstimer::puzzle::{self, ColorScheme, Diagram, Puzzle};
+use lstimer::smart_cube::Reconstruction;
+use lstimer::storage;
+use lstimer::scramble::{ScrambleSet, ScrambleType};
+use lstimer::trainer;
+use lstimer::timing::{self, INSPECTION_FIRST_WARNING, INSPECTION_PLUS2_LIMIT, INSPECTION_SECOND_WARNING, INSPECTION_TIME};
+use lstimer::{
+    CubeEvent, CustomEvent, EventSettings, ExportFilter, FinishedSolve, Penalty, PersonalBest, SolveResult, StandardEvent,
+    StatColumn, StatKind, Statistics, TimeRecord, Timer, TimerData, TimerState, scramble,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
-// Represents the possible states of the timer
-#[derive(Debug, Clone, PartialEq)]
-enum TimerState {
-    Ready,      // Timer is idle and ready to start
-    Preparing,  // User is holding space to prepare
-    Running,    // Timer is actively counting
-    Stopped,    // Timer has stopped after a solve
-}
-
-// Defines cube solving events, either standard or custom
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-enum CubeEvent {
-    Standard(StandardEvent),
-    Custom(String),
-}
-
-// Standard cube events as per WCA regulations
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-enum StandardEvent {
-    Cube3x3, Cube2x2, Cube4x4, Cube5x5, Cube6x6, Cube7x7,
-    Pyraminx, Megaminx, Skewb, Square1, Clock,
-    OneHanded, Blindfolded, FeetSolving,
-}
-
-impl std::fmt::Display for StandardEvent {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match self {
-            StandardEvent::Cube3x3 => write!(f, "3x3x3"),
-            StandardEvent::Cube2x2 => write!(f, "2x2x2"),
-            StandardEvent::Cube4x4 => write!(f, "4x4x4"),
-            StandardEvent::Cube5x5 => write!(f, "5x5x5"),
-            StandardEvent::Cube6x6 => write!(f, "6x6x6"),
-            StandardEvent::Cube7x7 => write!(f, "7x7x7"),
-            StandardEvent::Pyraminx => write!(f, "Pyraminx"),
-            StandardEvent::Megaminx => write!(f, "Megaminx"),
-            StandardEvent::Skewb => write!(f, "Skewb"),
-            StandardEvent::Square1 => write!(f, "Square-1"),
-            StandardEvent::Clock => write!(f, "Clock"),
-            StandardEvent::OneHanded => write!(f, "3x3 OH"),
-            StandardEvent::Blindfolded => write!(f, "3x3 BLD"),
-            StandardEvent::FeetSolving => write!(f, "3x3 Feet"),
-        }
-    }
-}
-
-impl std::fmt::Display for CubeEvent {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match self {
-            CubeEvent::Standard(event) => write!(f, "{}", event),
-            CubeEvent::Custom(name) => write!(f, "{}", name),
-        }
-    }
-}
-
-// Stores a single solve record with associated metadata
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct TimeRecord {
-    time: Duration,          // Duration of the solve
-    event: CubeEvent,       // Event type (e.g., 3x3x3, Pyraminx)
-    scramble: String,       // Scramble used for the solve
-    timestamp: DateTime<Local>, // Time and date of the solve
-    penalty: Option<Penalty>,   // Any penalties applied (e.g., +2, DNF)
-    comment: String,        // User comments for the solve
-}
-
-// Represents penalties that can be applied to a solve
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-enum Penalty {
-    Plus2,  // +2 second penalty
-    DNF,    // Did Not Finish
-}
+// Id of the text field times are typed into, so it can be focused
+const TYPED_TIME_ID: &str = "typed_time";
 
-// Aggregates statistical data for solves
-#[derive(Debug, Clone)]
-struct Statistics {
-    best: Option<Duration>,         // Fastest solve time
-    worst: Option<Duration>,        // Slowest solve time
-    current_ao5: Option<Duration>,  // Average of last 5 solves
-    current_ao12: Option<Duration>, // Average of last 12 solves
-    current_ao100: Option<Duration>, // Average of last 100 solves
-    mean: Option<Duration>,         // Mean of all solves
-}
+// Height of the scramble preview next to the scramble text
+const SCRAMBLE_PREVIEW_HEIGHT: f32 = 120.0;
 
-// Defines a custom event with user-specified parameters
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct CustomEvent {
-    name: String,           // Name of the custom event
-    scramble_length: usize, // Length of the scramble
-    moves: Vec<String>,    // Available moves for scrambling
+// Session operation currently being edited in the times panel
+#[derive(Debug, Clone, PartialEq)]
+enum SessionAction {
+    Create, // Naming a new session
+    Rename, // Renaming the active session
+    Merge,  // Choosing a session to merge the active one into
 }
 
 // Customizable UI theme with color and style settings
@@ -122,6 +62,8 @@ struct Theme {
     font_size_timer: f32,       // Timer font size
     enable_animations: bool,    // Enable/disable animations
     animation_speed: f32,       // Animation speed multiplier
+    #[serde(default)]
+    color_scheme: ColorScheme,  // Sticker colors of the scramble preview
 }
 
 impl Default for Theme {
@@ -148,6 +90,7 @@ impl Default for Theme {
             font_size_timer: 88.0,
             enable_animations: true,
             animation_speed: 1.0,
+            color_scheme: ColorScheme::default(),
         }
     }
 }
@@ -184,6 +127,7 @@ impl Theme {
     fn timer_color(&self, state: &TimerState) -> Color32 {
         match state {
             TimerState::Ready => Color32::from_rgb(self.timer_ready[0], self.timer_ready[1], self.timer_ready[2]),
+            TimerState::Inspection => self.text_primary_color(),
             TimerState::Preparing => Color32::from_rgb(self.timer_preparing[0], self.timer_preparing[1], self.timer_preparing[2]),
             TimerState::Running => Color32::from_rgb(self.timer_running[0], self.timer_running[1], self.timer_running[2]),
             TimerState::Stopped => Color32::from_rgb(self.timer_stopped[0], self.timer_stopped[1], self.timer_stopped[2]),
@@ -217,6 +161,10 @@ struct UIState {
     #[serde(skip)]
     selected_time_index: Option<usize>, // Index of the selected time record
     #[serde(skip)]
+    highlighted_indices: Vec<usize>, // Indices of the solves covered by a clicked PB
+    #[serde(skip)]
+    scroll_to_index: Option<usize>, // Time record to scroll the times list to
+    #[serde(skip)]
     editing_comment_index: Option<usize>, // Index of the time being commented
     #[serde(skip)]
     comment_text: String,          // Text for editing comments
@@ -225,6 +173,46 @@ struct UIState {
     #[serde(skip)]
     show_exit_popup: bool,         // Visibility of the exit confirmation popup
     is_first_launch: bool,         // Flag for showing the welcome message
+    #[serde(default)]
+    show_all_sessions: bool,       // Show solves and stats from every session of the event
+    #[serde(skip)]
+    show_archived_sessions: bool,  // List archived sessions in the session selector
+    #[serde(skip)]
+    session_action: Option<SessionAction>, // Session operation being edited
+    #[serde(skip)]
+    session_name_text: String,     // Text for naming sessions
+    #[serde(skip)]
+    show_data_window: bool,        // Visibility of the import and export window
+    #[serde(skip)]
+    import_path: String,           // Path of the backup to import
+    #[serde(skip)]
+    import_format: Option<ImportFormat>, // Format of the backup, None to detect it
+    #[serde(skip)]
+    export_path: String,           // Path the export is written to
+    #[serde(skip)]
+    export_format: ExportFormat,   // Format of the export
+    #[serde(skip)]
+    export_scope: ExportScope,     // Solves included in the export
+    #[serde(skip)]
+    export_from: String,           // First day of a date range export (YYYY-MM-DD)
+    #[serde(skip)]
+    export_to: String,             // Last day of a date range export (YYYY-MM-DD)
+    #[serde(skip)]
+    data_message: Option<Result<String, String>>, // Outcome of the last import or export
+    #[serde(default)]
+    typing_mode: bool,             // Enter times by typing instead of timing them
+    #[serde(skip)]
+    typed_time: String,            // Time being typed in typing mode
+    #[serde(skip)]
+    typed_time_error: Option<String>, // Why the last typed time was rejected
+    #[serde(skip)]
+    show_case_window: bool,        // Visibility of the trainer's case picker
+    #[serde(skip)]
+    set_code: String,              // Code of the scramble set to start
+    #[serde(skip)]
+    set_path: String,              // File scramble sets are imported from and exported to
+    #[serde(skip)]
+    set_count: usize,              // Scrambles of the current event written into exported sets
 }
 
 impl Default for UIState {
@@ -235,90 +223,90 @@ impl Default for UIState {
             show_statistics: false,
             times_panel_width: 300.0,
             selected_time_index: None,
+            highlighted_indices: Vec::new(),
+            scroll_to_index: None,
             editing_comment_index: None,
             comment_text: String::new(),
             confirm_delete_index: None,
             show_exit_popup: false,
             is_first_launch: true,
+            show_all_sessions: false,
+            show_archived_sessions: false,
+            session_action: None,
+            session_name_text: String::new(),
+            show_data_window: false,
+            import_path: String::new(),
+            import_format: None,
+            export_path: String::new(),
+            export_format: ExportFormat::default(),
+            export_scope: ExportScope::default(),
+            export_from: String::new(),
+            export_to: String::new(),
+            data_message: None,
+            typing_mode: false,
+            typed_time: String::new(),
+            typed_time_error: None,
+            show_case_window: false,
+            set_code: String::new(),
+            set_path: String::new(),
+            set_count: 12,
         }
     }
 }
 
 // Main application struct for the cube timer
 struct CubeTimer {
-    state: TimerState,              // Current state of the timer
-    start_time: Option<Instant>,    // Start time of the current solve
-    current_time: Duration,         // Current running time
-    last_time: Option<Duration>,    // Last recorded solve time
+    timer: Timer,                   // Timing state machine
     current_event: CubeEvent,       // Currently selected event
-    available_events: Vec<CubeEvent>, // List of available events
-    custom_events: HashMap<String, CustomEvent>, // Custom event definitions
+    data: TimerData,                // Solves, sessions and event settings
     current_scramble: String,       // Current scramble
-    records: Vec<TimeRecord>,       // List of all solve records
+    current_case: Option<String>,   // Id of the case the current scramble sets up, for trainers
+    scramble_set: Option<ScrambleSet>, // Shared scramble set the scrambles are taken from, if any
+    set_positions: HashMap<String, usize>, // Scramble of the set each event is on, keyed by event name
     statistics: Statistics,         // Statistical data for solves
     theme: Theme,                   // UI theme settings
     ui_state: UIState,             // UI state settings
-    new_custom_event_name: String,  // Name for new custom event
-    new_custom_moves: String,      // Moves for new custom event
-    space_pressed: bool,            // Space key state
-    space_hold_start: Option<Instant>, // Time when space key was pressed
-    key_preparation_time: Duration, // Minimum hold time to start timer
+    new_custom_definition: String, // Definition of the custom event being written
+    custom_event_path: String,     // File custom event definitions are imported from and exported to
+    custom_event_message: Option<Result<String, String>>, // Outcome of the last custom event change
+    new_stat_column: StatColumn,   // Stat column being configured in the settings
     timer_scale: f32,              // Current timer scale for animation
     target_timer_scale: f32,       // Target timer scale for animation
     last_save_time: Instant,
+    save_error: Option<String>,     // Error of the last failed save
+    preview: Option<((CubeEvent, String), Option<Puzzle>)>, // Puzzle the current scramble leads to, keyed by event and scramble
+    #[cfg(feature = "stackmat-audio")]
+    stackmat: Option<Result<StackmatCapture, String>>, // Stackmat on the audio input, or why it could not be opened
 }
 
 impl Default for CubeTimer {
     fn default() -> Self {
-        let available_events = vec![
-            CubeEvent::Standard(StandardEvent::Cube3x3),
-            CubeEvent::Standard(StandardEvent::Cube2x2),
-            CubeEvent::Standard(StandardEvent::Cube4x4),
-            CubeEvent::Standard(StandardEvent::Cube5x5),
-            CubeEvent::Standard(StandardEvent::Cube6x6),
-            CubeEvent::Standard(StandardEvent::Cube7x7),
-            CubeEvent::Standard(StandardEvent::Pyraminx),
-            CubeEvent::Standard(StandardEvent::Megaminx),
-            CubeEvent::Standard(StandardEvent::Skewb),
-            CubeEvent::Standard(StandardEvent::Square1),
-            CubeEvent::Standard(StandardEvent::Clock),
-            CubeEvent::Standard(StandardEvent::OneHanded),
-            CubeEvent::Standard(StandardEvent::Blindfolded),
-            CubeEvent::Standard(StandardEvent::FeetSolving),
-
-        ];
-
-        let current_event = available_events[0].clone();
-        let current_scramble = Self::generate_scramble(&current_event);
+        let data = TimerData::default();
+        let current_event = data.available_events[0].clone();
+        let current_scramble = scramble::generate(&current_event, &EventSettings::for_event(&current_event), &HashMap::new());
 
         Self {
-            state: TimerState::Ready,
-            start_time: None,
-            current_time: Duration::ZERO,
-            last_time: None,
+            timer: Timer::default(),
             current_event,
-            available_events,
-            custom_events: HashMap::new(),
+            data,
             current_scramble,
-            records: Vec::new(),
-            statistics: Statistics {
-                best: None,
-                worst: None,
-                current_ao5: None,
-                current_ao12: None,
-                current_ao100: None,
-                mean: None,
-            },
+            current_case: None,
+            scramble_set: None,
+            set_positions: HashMap::new(),
+            statistics: Statistics::default(),
             theme: Theme::default(),
             ui_state: UIState::default(),
-            new_custom_event_name: String::new(),
-            new_custom_moves: String::new(),
-            space_pressed: false,
-            space_hold_start: None,
-            key_preparation_time: Duration::from_millis(300),
+            new_custom_definition: String::new(),
+            custom_event_path: String::new(),
+            custom_event_message: None,
+            new_stat_column: StatColumn::average(50),
             timer_scale: 1.0,
             target_timer_scale: 1.0,
             last_save_time: Instant::now(),
+            save_error: None,
+            preview: None,
+            #[cfg(feature = "stackmat-audio")]
+            stackmat: None,
         }
     }
 }
@@ -326,358 +314,230 @@ impl Default for CubeTimer {
 impl CubeTimer {
     // Initializes the application with loaded data
     fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        let mut app = Self::default();
-        app.load_data();
-        app.last_save_time = Instant::now();
-        app
-    }
-
-    // Generates a scramble for the given event
-    fn generate_scramble(event: &CubeEvent) -> String {
-        let mut rng = rand::thread_rng();
-
-        match event {
-            CubeEvent::Standard(StandardEvent::Cube3x3) => {
-                Self::generate_cube_scramble(&mut rng, &["R", "U", "F", "L", "D", "B"], 20)
-            },
-            CubeEvent::Standard(StandardEvent::Cube2x2) => {
-                Self::generate_cube_scramble(&mut rng, &["R", "U", "F"], 9)
-            },
-            CubeEvent::Standard(StandardEvent::Pyraminx) => {
-                Self::generate_pyraminx_scramble(&mut rng)
-            },
-            CubeEvent::Custom(name) => {
-                format!("Custom scramble for {}", name)
-            },
-            _ => {
-                Self::generate_cube_scramble(&mut rng, &["R", "U", "F", "L", "D", "B"], 15)
-            }
-        }
-    }
-
-    // Generates a cube scramble with specified moves and length
-    fn generate_cube_scramble(rng: &mut impl Rng, moves: &[&str], length: usize) -> String {
-        let modifiers = ["", "'", "2"];
-        let mut scramble = Vec::new();
-
-        for _ in 0..length {
-            let move_idx = rng.gen_range(0..moves.len());
-            let mod_idx = rng.gen_range(0..modifiers.len());
-            scramble.push(format!("{}{}", moves[move_idx], modifiers[mod_idx]));
-        }
-        scramble.join(" ")
+        Self::load()
     }
 
-    // Generates a Pyraminx scramble
-    fn generate_pyraminx_scramble(rng: &mut impl Rng) -> String {
-        let moves = ["R", "U", "L", "B"];
-        let modifiers = ["", "'"];
-        let mut scramble = Vec::new();
-
-        for _ in 0..10 {
-            let move_idx = rng.gen_range(0..moves.len());
-            let mod_idx = rng.gen_range(0..modifiers.len());
-            scramble.push(format!("{}{}", moves[move_idx], modifiers[mod_idx]));
+    // Creates the timer from the saved data, for either front end
+    fn load() -> Self {
+        let mut app = Self::default();
+        if let Some(app_dir) = storage::app_dir() {
+            app.data = TimerData::load(app_dir);
+            app.load_setting("theme.json");
+            app.load_setting("ui_state.json");
         }
-        scramble.join(" ")
+        app.ensure_active_session();
+        app.calculate_statistics();
+        app.recalculate_personal_bests();
+        app.last_save_time = Instant::now();
+        app
     }
 
     // Updates statistics based on recorded times
     fn calculate_statistics(&mut self) {
-        let current_event_times: Vec<Duration> = self.records
+        let results: Vec<SolveResult> = self.data.records
             .iter()
-            .filter(|r| r.event == self.current_event && r.penalty.is_none())
-            .map(|r| r.time)
+            .filter(|r| self.is_in_scope(r))
+            .map(|r| r.result())
             .collect();
+        let columns = self.stat_columns(&self.current_event);
+        self.statistics.calculate(&results, columns.clone());
 
-        if current_event_times.is_empty() {
-            self.statistics = Statistics {
-                best: None,
-                worst: None,
-                current_ao5: None,
-                current_ao12: None,
-                current_ao100: None,
-                mean: None,
-            };
-            return;
-        }
-
-        self.calculate_basic_stats(&current_event_times);
-        self.calculate_averages(&current_event_times);
+        let records: Vec<&TimeRecord> = self.data.records.iter().filter(|r| self.is_in_scope(r)).collect();
+        let phases = self.event_settings(&self.current_event).phases;
+        self.statistics.calculate_phases(&records, &phases, &columns);
     }
 
-    // Calculates basic statistics (best, worst, mean)
-    fn calculate_basic_stats(&mut self, times: &[Duration]) {
-        self.statistics.best = times.iter().min().copied();
-        self.statistics.worst = times.iter().max().copied();
-
-        let sum: Duration = times.iter().sum();
-        self.statistics.mean = Some(sum / times.len() as u32);
+    // Returns the record indices and results of every solve of the current event
+    fn event_results(&self) -> (Vec<usize>, Vec<SolveResult>) {
+        let scramble_type = self.scramble_type();
+        self.data.records
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| r.event == self.current_event && r.scramble_type == scramble_type)
+            .map(|(i, r)| (i, r.result()))
+            .unzip()
     }
 
-    // Calculates average of 5, 12, and 100 solves
-    fn calculate_averages(&mut self, times: &[Duration]) {
-        if times.len() >= 5 {
-            let last_5: Vec<Duration> = times.iter().rev().take(5).cloned().collect();
-            self.statistics.current_ao5 = Self::calculate_average(&last_5);
-        }
-
-        if times.len() >= 12 {
-            let last_12: Vec<Duration> = times.iter().rev().take(12).cloned().collect();
-            self.statistics.current_ao12 = Self::calculate_average(&last_12);
-        }
-
-        if times.len() >= 100 {
-            let last_100: Vec<Duration> = times.iter().rev().take(100).cloned().collect();
-            self.statistics.current_ao100 = Self::calculate_average(&last_100);
-        }
+    // Recalculates the personal best averages over the current event's full history
+    fn recalculate_personal_bests(&mut self) {
+        let (indices, results) = self.event_results();
+        let columns = self.stat_columns(&self.current_event);
+        self.statistics.recalculate_personal_bests(&results, &indices, columns);
     }
 
-    // Calculates the trimmed mean for a set of times
-    fn calculate_average(times: &[Duration]) -> Option<Duration> {
-        if times.len() < 5 {
-            return None;
-        }
-
-        let mut sorted = times.to_vec();
-        sorted.sort();
-
-        let remove_count = (times.len() as f32 * 0.05).ceil() as usize;
-        if remove_count * 2 >= times.len() {
-            return None;
-        }
-
-        let trimmed = &sorted[remove_count..sorted.len() - remove_count];
-        let sum: Duration = trimmed.iter().sum();
-        Some(sum / trimmed.len() as u32)
+    // Updates the personal bests with the averages ending at the newest solve
+    fn update_personal_bests(&mut self) {
+        let (indices, results) = self.event_results();
+        let columns = self.stat_columns(&self.current_event);
+        self.statistics.update_personal_bests(&results, &indices, &columns);
     }
 
-    // Formats a duration into a readable time string
-    fn format_time(duration: Duration) -> String {
-        let total_millis = duration.as_millis();
-        let minutes = total_millis / 60000;
-        let seconds = (total_millis % 60000) / 1000;
-        let millis = total_millis % 1000;
-
-        if minutes > 0 {
-            format!("{}:{:02}.{:03}", minutes, seconds, millis)
-        } else {
-            format!("{}.{:03}", seconds, millis)
-        }
+    // Saves all application data to disk, remembering the first error for display
+    fn save_data(&mut self) {
+        let results = [
+            self.data.save(),
+            self.data.save_file("theme.json", &self.theme),
+            self.data.save_file("ui_state.json", &self.ui_state),
+        ];
+        self.save_error = results.into_iter().find_map(Result::err);
     }
 
-    // Saves all application data to disk
-    fn save_data(&self) {
-        if let Some(config_dir) = dirs::config_dir() {
-            let app_dir = config_dir.join("cube-timer");
-            if std::fs::create_dir_all(&app_dir).is_err() {
-                return;
+    // Loads the theme or UI state file, which only the window and terminal use
+    fn load_setting(&mut self, name: &str) {
+        match name {
+            "theme.json" => {
+                if let Some(theme) = self.data.load_file(name) {
+                    self.theme = theme;
+                }
             }
-
-            self.save_records(&app_dir);
-            self.save_theme(&app_dir);
-            self.save_custom_events(&app_dir);
-            self.save_ui_state(&app_dir);
-        }
-    }
-
-    // Saves solve records to disk
-    fn save_records(&self, app_dir: &std::path::Path) {
-        if let Ok(json) = serde_json::to_string(&self.records) {
-            let _ = std::fs::write(app_dir.join("records.json"), json);
-        }
-    }
-
-    // Saves theme settings to disk
-    fn save_theme(&self, app_dir: &std::path::Path) {
-        if let Ok(json) = serde_json::to_string(&self.theme) {
-            let _ = std::fs::write(app_dir.join("theme.json"), json);
-        }
-    }
-
-    // Saves custom events to disk
-    fn save_custom_events(&self, app_dir: &std::path::Path) {
-        if let Ok(json) = serde_json::to_string(&self.custom_events) {
-            let _ = std::fs::write(app_dir.join("custom_events.json"), json);
+            "ui_state.json" => {
+                if let Some(ui_state) = self.data.load_file(name) {
+                    self.ui_state = ui_state;
+                }
+            }
+            _ => {}
         }
     }
 
-    // Saves UI state to disk
-    fn save_ui_state(&self, app_dir: &std::path::Path) {
-        if let Ok(json) = serde_json::to_string(&self.ui_state) {
-            let _ = std::fs::write(app_dir.join("ui_state.json"), json);
+    // Replaces a damaged data file with one of its backups and loads it
+    fn restore_backup(&mut self, name: &str, backup: &std::path::Path) {
+        if let Err(e) = self.data.restore_backup(name, backup) {
+            self.save_error = Some(e);
+            return;
         }
-    }
-
-    // Loads all application data from disk
-    fn load_data(&mut self) {
-        let config_dir = match dirs::config_dir() {
-            Some(dir) => dir,
-            None => return,
-        };
-
-        let app_dir = config_dir.join("cube-timer");
-
-        self.load_records(&app_dir);
-        self.load_theme(&app_dir);
-        self.load_custom_events(&app_dir);
-        self.load_ui_state(&app_dir);
+        self.load_setting(name);
+        self.ensure_active_session();
         self.calculate_statistics();
+        self.recalculate_personal_bests();
     }
 
-    // Loads solve records from disk
-    fn load_records(&mut self, app_dir: &std::path::Path) {
-        if let Ok(data) = std::fs::read_to_string(app_dir.join("records.json")) {
-            if let Ok(records) = serde_json::from_str(&data) {
-                self.records = records;
-            }
+    // Moves a damaged data file aside and continues without it
+    fn discard_corrupt_file(&mut self, name: &str) {
+        if let Err(e) = self.data.discard_corrupt_file(name) {
+            self.save_error = Some(e);
         }
     }
 
-    // Loads theme settings from disk
-    fn load_theme(&mut self, app_dir: &std::path::Path) {
-        if let Ok(data) = std::fs::read_to_string(app_dir.join("theme.json")) {
-            if let Ok(theme) = serde_json::from_str(&data) {
-                self.theme = theme;
-            }
+    // Handles space key input for timer control
+    fn handle_space_key(&mut self, pressed: bool) {
+        let settings = self.event_settings(&self.current_event);
+        if let Some(solve) = self.timer.handle_space_key(pressed, &settings) {
+            self.finish_solve(solve);
         }
     }
 
-    // Loads custom events from disk
-    fn load_custom_events(&mut self, app_dir: &std::path::Path) {
-        if let Ok(data) = std::fs::read_to_string(app_dir.join("custom_events.json")) {
-            if let Ok(custom_events) = serde_json::from_str(&data) {
-                self.custom_events = custom_events;
-                for name in self.custom_events.keys() {
-                    let custom_event = CubeEvent::Custom(name.clone());
-                    if !self.available_events.contains(&custom_event) {
-                        self.available_events.push(custom_event);
-                    }
-                }
-            }
+    // Presses and releases space at once, for terminals that cannot report key releases
+    fn tap_space(&mut self) {
+        let settings = self.event_settings(&self.current_event);
+        if let Some(solve) = self.timer.tap(self.timer.now(), &settings) {
+            self.finish_solve(solve);
         }
     }
 
-    // Loads UI state from disk
-    fn load_ui_state(&mut self, app_dir: &std::path::Path) {
-        if let Ok(data) = std::fs::read_to_string(app_dir.join("ui_state.json")) {
-            if let Ok(ui_state) = serde_json::from_str(&data) {
-                self.ui_state = ui_state;
-            }
+    // Records a solve the timer has stopped and moves on to the next scramble
+    fn finish_solve(&mut self, solve: FinishedSolve) {
+        self.save_time_record(solve);
+        if self.scramble_set.is_some() {
+            *self.set_positions.entry(self.current_event.to_string()).or_default() += 1;
         }
+        self.generate_new_scramble();
     }
 
-    // Handles space key input for timer control
-    fn handle_space_key(&mut self, pressed: bool) {
-        let now = Instant::now();
-
-        if pressed && !self.space_pressed {
-            self.handle_space_press(now);
-        } else if !pressed && self.space_pressed {
-            self.handle_space_release(now);
-        }
+    // Returns the settings for an event, falling back to its defaults
+    fn event_settings(&self, event: &CubeEvent) -> EventSettings {
+        self.data.event_settings(event)
     }
 
-    // Processes space key press
-    fn handle_space_press(&mut self, now: Instant) {
-        self.space_pressed = true;
-        self.space_hold_start = Some(now);
-
-        match self.state {
-            TimerState::Ready => {
-                self.state = TimerState::Preparing;
-                self.target_timer_scale = 0.95;
-            }
-            TimerState::Running => {
-                self.stop_timer(now);
-            }
-            TimerState::Stopped => {
-                self.state = TimerState::Preparing;
-                self.target_timer_scale = 0.95;
-            }
-            _ => {}
-        }
+    // Returns the stat columns configured for an event
+    fn stat_columns(&self, event: &CubeEvent) -> Vec<StatColumn> {
+        self.data.stat_columns(event)
     }
 
-    // Processes space key release
-    fn handle_space_release(&mut self, now: Instant) {
-        self.space_pressed = false;
-
-        match self.state {
-            TimerState::Preparing => {
-                self.try_start_timer(now);
-            }
-            TimerState::Stopped => {
-                self.state = TimerState::Ready;
-            }
-            _ => {}
+    // Replaces the stat columns of an event and recalculates everything they drive
+    fn set_stat_columns(&mut self, event: &CubeEvent, columns: Vec<StatColumn>) {
+        let mut settings = self.event_settings(event);
+        settings.stat_columns = columns;
+        self.data.event_settings.insert(event.to_string(), settings);
+        if *event == self.current_event {
+            self.ui_state.highlighted_indices.clear();
+            self.calculate_statistics();
+            self.recalculate_personal_bests();
         }
-
-        self.space_hold_start = None;
-        self.target_timer_scale = 1.0;
     }
 
-    // Stops the timer and records the time
-    fn stop_timer(&mut self, now: Instant) {
-        if let Some(start_time) = self.start_time {
-            self.current_time = now.duration_since(start_time);
-            self.last_time = Some(self.current_time);
-
-            self.save_time_record();
-            self.generate_new_scramble();
-        }
-        self.state = TimerState::Stopped;
-        self.start_time = None;
-        self.target_timer_scale = 1.0;
+    // Color used for the stat column or phase at the given position
+    fn series_color(&self, position: usize) -> Color32 {
+        let colors = [
+            self.theme.accent_primary_color(),
+            self.theme.accent_secondary_color(),
+            self.theme.success_color(),
+            self.theme.warning_color(),
+            self.theme.error_color(),
+        ];
+        colors[position % colors.len()]
     }
 
     // Saves a new time record
-    fn save_time_record(&mut self) {
+    fn save_time_record(&mut self, solve: FinishedSolve) {
         let record = TimeRecord {
-            time: self.current_time,
+            time: solve.time,
             event: self.current_event.clone(),
             scramble: self.current_scramble.clone(),
             timestamp: Local::now(),
-            penalty: None,
+            penalty: solve.penalty,
             comment: String::new(),
+            session: self.active_session_id(),
+            splits: solve.splits,
+            moves: solve.moves,
+            case: self.current_case.clone(),
+            scramble_type: self.scramble_type(),
         };
 
-        self.records.push(record);
+        self.data.records.push(record);
         self.calculate_statistics();
+        self.update_personal_bests();
         self.save_data()
     }
 
-    // Generates a new scramble for the current event
+    // Generates a new scramble for the current event, or takes the event's current one from
+    // the scramble set
     fn generate_new_scramble(&mut self) {
-        self.current_scramble = Self::generate_scramble(&self.current_event);
-    }
+        let event = self.current_event.clone();
+        if let Some(set) = &self.scramble_set {
+            let scramble = set.scramble(&event, self.set_position(&event), &self.data.custom_events);
+            self.current_scramble = scramble.scramble;
+            self.current_case = scramble.case;
+            return;
+        }
 
-    // Attempts to start the timer based on hold duration
-    fn try_start_timer(&mut self, now: Instant) {
-        if let Some(hold_start) = self.space_hold_start {
-            let hold_duration = now.duration_since(hold_start);
+        let scramble = scramble::generate_case(&event, &self.event_settings(&event), &self.data.custom_events);
+        self.current_scramble = scramble.scramble;
+        self.current_case = scramble.case;
+    }
 
-            if hold_duration >= self.key_preparation_time {
-                self.start_timer(now);
-            } else {
-                self.state = TimerState::Ready;
-            }
-        }
+    // Index of the scramble set scramble an event is on
+    fn set_position(&self, event: &CubeEvent) -> usize {
+        self.set_positions.get(&event.to_string()).copied().unwrap_or(0)
     }
 
-    // Starts the timer
-    fn start_timer(&mut self, now: Instant) {
-        self.state = TimerState::Running;
-        self.start_time = Some(now);
-        self.current_time = Duration::ZERO;
-        self.target_timer_scale = 1.0;
+    // Starts taking scrambles from a set, from the first scramble of every event
+    fn start_scramble_set(&mut self, set: ScrambleSet) -> String {
+        let message = format!("Using scramble set {}", set.code);
+        self.scramble_set = Some(set);
+        self.set_positions.clear();
+        self.generate_new_scramble();
+        self.calculate_statistics();
+        self.recalculate_personal_bests();
+        message
     }
 
     // Deletes a time record
     fn delete_time(&mut self, index: usize) {
-        if index < self.records.len() {
-            self.records.remove(index);
+        if index < self.data.records.len() {
+            self.data.records.remove(index);
+            self.ui_state.highlighted_indices.clear();
             self.calculate_statistics();
+            self.recalculate_personal_bests();
             self.ui_state.confirm_delete_index = None;
             self.save_data(); // Ensure data is saved after deletion
         }
@@ -685,28 +545,28 @@ impl CubeTimer {
 
     // Updates the comment for a time record
     fn update_time_comment(&mut self, index: usize, comment: String) {
-        if index < self.records.len() {
-            self.records[index].comment = comment;
+        if index < self.data.records.len() {
+            self.data.records[index].comment = comment;
         }
     }
 
     // Applies a penalty to a time record
     fn apply_penalty(&mut self, index: usize, penalty: Option<Penalty>) {
-        if index < self.records.len() {
-            self.records[index].penalty = penalty;
+        if index < self.data.records.len() {
+            self.data.records[index].penalty = penalty;
             self.calculate_statistics();
+            self.recalculate_personal_bests();
             self.save_data();
         }
     }
 
     // Updates timer state and animations
     fn handle_timer_updates(&mut self, ctx: &egui::Context) {
-        if matches!(self.state, TimerState::Running) {
-            if let Some(start_time) = self.start_time {
-                self.current_time = Instant::now().duration_since(start_time);
-            }
+        self.timer.update_running_time(self.timer.now());
+        if matches!(self.timer.state, TimerState::Running) || self.timer.inspection_start.is_some() {
             ctx.request_repaint();
         }
+        self.target_timer_scale = if matches!(self.timer.state, TimerState::Preparing) { 0.95 } else { 1.0 };
 
         // Smooth animations
         if self.theme.enable_animations {
@@ -723,8 +583,21 @@ impl CubeTimer {
         }
     }
 
+    // Saves the data if the last save is more than two minutes old
+    fn autosave(&mut self, now: Instant) {
+        if now.duration_since(self.last_save_time) > Duration::from_secs(120) {
+            self.save_data();
+            self.last_save_time = now;
+        }
+    }
+
     // Handles keyboard input
     fn handle_input(&mut self, ctx: &egui::Context) {
+        // Space is part of the typed time in typing mode
+        if self.ui_state.typing_mode {
+            return;
+        }
+
         ctx.input(|i| {
             if i.key_pressed(egui::Key::Space) {
                 self.handle_space_key(true);
@@ -734,6 +607,22 @@ impl CubeTimer {
         });
     }
 
+    // Follows the Stackmat's packets with the timer, saving the solves it finishes
+    #[cfg(feature = "stackmat-audio")]
+    fn handle_stackmat(&mut self, ctx: &egui::Context) {
+        let Some(Ok(capture)) = &self.stackmat else {
+            return;
+        };
+
+        for packet in capture.poll() {
+            if let Some(solve) = stackmat::apply_packet(&mut self.timer, &packet) {
+                self.finish_solve(solve);
+            }
+        }
+        // Keep polling, the Stackmat sends several packets a second
+        ctx.request_repaint_after(Duration::from_millis(50));
+    }
+
     // Applies theme settings to the UI
     fn setup_theme(&self, ctx: &egui::Context) {
         let mut visuals = egui::Visuals::dark();
@@ -763,7 +652,9 @@ impl CubeTimer {
             .max_width(500.0)
             .show(ctx, |ui| {
                 self.render_times_panel_header(ui);
+                self.render_session_selector(ui);
                 self.render_times_panel_stats(ui);
+                self.render_personal_bests(ui);
                 ui.separator();
                 self.render_times_list(ui);
                 self.ui_state.times_panel_width = ui.min_size().x;
@@ -791,24 +682,149 @@ impl CubeTimer {
 
         ui.horizontal_wrapped(|ui| {
             if let Some(best) = self.statistics.best {
-                self.render_stat_chip(ui, "Best", &Self::format_time(best), self.theme.success_color());
+                self.render_stat_chip(ui, "Best", &format_time(best), self.theme.success_color());
+            }
+            for (position, (column, value)) in self.statistics.current.iter().enumerate() {
+                if let Some(value) = value {
+                    self.render_stat_chip(ui, &column.label(), &format_result(*value), self.series_color(position));
+                }
+            }
+        });
+    }
+
+    // Renders the personal best averages, each jumping to its solves when clicked
+    fn render_personal_bests(&mut self, ui: &mut egui::Ui) {
+        if self.statistics.personal_bests.is_empty() {
+            return;
+        }
+
+        let mut clicked = None;
+        egui::CollapsingHeader::new(RichText::new("🏆 Personal bests").size(self.theme.font_size_normal))
+            .default_open(true)
+            .show(ui, |ui| {
+                for pb in &self.statistics.personal_bests {
+                    let text = format!("{}: {}  ({})", pb.column.label(), format_time(pb.average), self.personal_best_solves(pb));
+                    let is_highlighted = self.ui_state.highlighted_indices == pb.record_indices;
+                    if ui.selectable_label(is_highlighted, RichText::new(text).size(self.theme.font_size_small).color(self.theme.success_color())).clicked() {
+                        clicked = Some(pb.record_indices.clone());
+                    }
+                }
+            });
+
+        if let Some(indices) = clicked {
+            self.show_solves(indices);
+        }
+    }
+
+    // Highlights the given solves and scrolls the times list to them
+    fn show_solves(&mut self, indices: Vec<usize>) {
+        if self.ui_state.highlighted_indices == indices {
+            self.ui_state.highlighted_indices.clear();
+            return;
+        }
+        // Solves from other sessions are only listed in the all-sessions view
+        if !indices.iter().all(|&i| self.data.records.get(i).is_some_and(|r| self.is_in_scope(r))) {
+            self.ui_state.show_all_sessions = true;
+            self.calculate_statistics();
+        }
+        self.ui_state.scroll_to_index = indices.last().copied();
+        self.ui_state.highlighted_indices = indices;
+    }
+
+    // Renders the session selector and session management buttons
+    fn render_session_selector(&mut self, ui: &mut egui::Ui) {
+        let active_id = self.active_session_id();
+        let active_name = self.data.sessions.get(active_id).map(|s| s.name.clone()).unwrap_or_default();
+        let show_archived = self.ui_state.show_archived_sessions;
+        let sessions: Vec<(u64, String, bool)> = self.data.sessions.sessions
+            .iter()
+            .filter(|s| s.event == self.current_event && (show_archived || !s.archived || s.id == active_id))
+            .map(|s| (s.id, s.name.clone(), s.archived))
+            .collect();
+
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Session:").size(self.theme.font_size_normal).color(self.theme.text_secondary_color()));
+            egui::ComboBox::from_id_source("session_selector")
+                .selected_text(RichText::new(&active_name).size(self.theme.font_size_normal).color(self.theme.accent_primary_color()))
+                .show_ui(ui, |ui| {
+                    for (id, name, archived) in &sessions {
+                        let label = if *archived { format!("🗄 {}", name) } else { name.clone() };
+                        if ui.selectable_label(*id == active_id, label).clicked() {
+                            self.switch_session(*id);
+                        }
+                    }
+                });
+
+            if ui.small_button("➕").on_hover_text("New session").clicked() {
+                self.ui_state.session_action = Some(SessionAction::Create);
+                self.ui_state.session_name_text.clear();
+            }
+            if ui.small_button("✏").on_hover_text("Rename session").clicked() {
+                self.ui_state.session_action = Some(SessionAction::Rename);
+                self.ui_state.session_name_text = active_name.clone();
+            }
+            if ui.small_button("⤵").on_hover_text("Merge into another session").clicked() {
+                self.ui_state.session_action = Some(SessionAction::Merge);
+            }
+            let is_archived = self.data.sessions.get(active_id).is_some_and(|s| s.archived);
+            let archive_text = if is_archived { "Restore session" } else { "Archive session" };
+            if ui.small_button("🗄").on_hover_text(archive_text).clicked() {
+                self.set_session_archived(active_id, !is_archived);
+            }
+        });
+
+        match self.ui_state.session_action.clone() {
+            Some(SessionAction::Create) | Some(SessionAction::Rename) => {
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.ui_state.session_name_text);
+                    if ui.small_button("✓").clicked() {
+                        let name = self.ui_state.session_name_text.trim().to_string();
+                        if !name.is_empty() {
+                            if self.ui_state.session_action == Some(SessionAction::Create) {
+                                let id = self.data.sessions.create(&self.current_event.clone(), name);
+                                self.switch_session(id);
+                            } else {
+                                self.rename_session(active_id, name);
+                            }
+                        }
+                        self.ui_state.session_action = None;
+                    }
+                    if ui.small_button("✕").clicked() {
+                        self.ui_state.session_action = None;
+                    }
+                });
             }
-            if let Some(ao5) = self.statistics.current_ao5 {
-                self.render_stat_chip(ui, "Ao5", &Self::format_time(ao5), self.theme.accent_primary_color());
+            Some(SessionAction::Merge) => {
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("Merge into:").size(self.theme.font_size_small).color(self.theme.text_secondary_color()));
+                    for (id, name, _) in sessions.iter().filter(|(id, _, _)| *id != active_id) {
+                        if ui.small_button(name).clicked() {
+                            self.merge_sessions(active_id, *id);
+                            self.ui_state.session_action = None;
+                        }
+                    }
+                    if ui.small_button("✕").clicked() {
+                        self.ui_state.session_action = None;
+                    }
+                });
             }
-            if let Some(ao12) = self.statistics.current_ao12 {
-                self.render_stat_chip(ui, "Ao12", &Self::format_time(ao12), self.theme.accent_secondary_color());
+            None => {}
+        }
+
+        ui.horizontal(|ui| {
+            if ui.checkbox(&mut self.ui_state.show_all_sessions, "All sessions").changed() {
+                self.calculate_statistics();
             }
+            ui.checkbox(&mut self.ui_state.show_archived_sessions, "Show archived");
         });
     }
 
     // Renders the list of times
     fn render_times_list(&mut self, ui: &mut egui::Ui) {
-        let current_event = self.current_event.clone();
-        let current_event_records: Vec<(usize, TimeRecord)> = self.records
+        let current_event_records: Vec<(usize, TimeRecord)> = self.data.records
             .iter()
             .enumerate()
-            .filter(|(_, r)| r.event == current_event)
+            .filter(|(_, r)| self.is_in_scope(r))
             .map(|(i, r)| (i, r.clone()))
             .rev()
             .collect();
@@ -860,21 +876,29 @@ impl CubeTimer {
     fn render_time_entry(&mut self, ui: &mut egui::Ui, display_index: usize, actual_index: usize, record: &TimeRecord) {
         let is_selected = self.ui_state.selected_time_index == Some(actual_index);
         let is_editing = self.ui_state.editing_comment_index == Some(actual_index);
+        let is_highlighted = self.ui_state.highlighted_indices.contains(&actual_index);
 
         let entry_response = ui.allocate_response(
             Vec2::new(ui.available_width(), 60.0),
             egui::Sense::click()
         );
 
-        self.render_time_entry_background(ui, &entry_response, is_selected);
+        if self.ui_state.scroll_to_index == Some(actual_index) {
+            entry_response.scroll_to_me(Some(egui::Align::Center));
+            self.ui_state.scroll_to_index = None;
+        }
+
+        self.render_time_entry_background(ui, &entry_response, is_selected, is_highlighted);
         self.handle_time_entry_click(&entry_response, actual_index, is_selected);
         self.render_time_entry_content(ui, &entry_response, display_index, actual_index, record, is_editing);
     }
 
     // Renders the background of a time entry
-    fn render_time_entry_background(&self, ui: &mut egui::Ui, entry_response: &egui::Response, is_selected: bool) {
+    fn render_time_entry_background(&self, ui: &mut egui::Ui, entry_response: &egui::Response, is_selected: bool, is_highlighted: bool) {
         let bg_color = if is_selected {
             self.theme.accent_primary_color().gamma_multiply(0.1)
+        } else if is_highlighted {
+            self.theme.success_color().gamma_multiply(0.15)
         } else if entry_response.hovered() {
             self.theme.surface_variant_color()
         } else {
@@ -938,6 +962,36 @@ impl CubeTimer {
             ui.label(RichText::new(record.timestamp.format("%H:%M:%S").to_string())
                 .size(self.theme.font_size_small)
                 .color(self.theme.text_secondary_color()));
+
+            if let Some(case) = &record.case {
+                ui.label(RichText::new(case)
+                    .size(self.theme.font_size_small)
+                    .color(self.theme.text_secondary_color()));
+            }
+
+            if !record.splits.is_empty() {
+                let splits: Vec<String> = record.splits.iter().map(|split| format_time(*split)).collect();
+                ui.label(RichText::new(splits.join(" / "))
+                    .size(self.theme.font_size_small)
+                    .color(self.theme.text_secondary_color()));
+            }
+
+            if !record.moves.is_empty()
+                && let Some(reconstruction) = Reconstruction::analyze(&record.scramble, &record.moves)
+            {
+                let steps: Vec<String> = reconstruction.steps
+                    .iter()
+                    .map(|step| if step.moves.is_empty() {
+                        format!("{}: skip", step.name)
+                    } else {
+                        format!("{}: {} ({})", step.name, step.moves.join(" "), format_time(step.duration))
+                    })
+                    .collect();
+                ui.label(RichText::new(format!("{} moves · {:.2} TPS", reconstruction.move_count(), reconstruction.tps()))
+                    .size(self.theme.font_size_small)
+                    .color(self.theme.text_secondary_color()))
+                    .on_hover_text(steps.join("\n"));
+            }
         });
     }
 
@@ -951,8 +1005,8 @@ impl CubeTimer {
 
         let time_text = match record.penalty {
             Some(Penalty::DNF) => "DNF".to_string(),
-            Some(Penalty::Plus2) => format!("{}+", Self::format_time(record.time)),
-            None => Self::format_time(record.time),
+            Some(Penalty::Plus2) => format!("{}+", format_result(record.result())),
+            None => format_time(record.time),
         };
 
         (time_color, time_text)
@@ -1044,13 +1098,39 @@ impl CubeTimer {
             ui.separator();
             self.render_enhanced_event_selector(ui);
 
+            if let Some(set) = &self.scramble_set {
+                let position = self.set_position(&self.current_event) + 1;
+                ui.label(RichText::new(format!("Set {} · scramble {}", set.code, position))
+                    .size(self.theme.font_size_small)
+                    .color(self.theme.accent_primary_color()));
+            }
+
+            if let Some(error) = &self.save_error {
+                ui.label(RichText::new(format!("⚠ {}", error)).size(self.theme.font_size_small).color(self.theme.error_color()));
+            }
+
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 if ui.button("⚙ Settings").clicked() {
                     self.ui_state.show_settings = !self.ui_state.show_settings;
                 }
+                // Only switch modes between solves
+                let idle = matches!(self.timer.state, TimerState::Ready | TimerState::Stopped);
+                let typing = egui::SelectableLabel::new(self.ui_state.typing_mode, "⌨ Typing");
+                if ui.add_enabled(idle, typing).clicked() {
+                    self.ui_state.typing_mode = !self.ui_state.typing_mode;
+                    if self.ui_state.typing_mode {
+                        ui.memory_mut(|memory| memory.request_focus(egui::Id::new(TYPED_TIME_ID)));
+                    }
+                }
                 if ui.button("📈 Stats").clicked() {
                     self.ui_state.show_statistics = !self.ui_state.show_statistics;
                 }
+                if ui.button("📂 Data").clicked() {
+                    self.ui_state.show_data_window = !self.ui_state.show_data_window;
+                }
+                if matches!(self.current_event, CubeEvent::Training(_)) && ui.button("🧩 Cases").clicked() {
+                    self.ui_state.show_case_window = !self.ui_state.show_case_window;
+                }
             });
         });
 
@@ -1060,6 +1140,7 @@ impl CubeTimer {
             self.render_enhanced_scramble(ui);
             ui.add_space(60.0);
             self.render_enhanced_timer(ui);
+            self.render_splits(ui);
             ui.add_space(30.0);
             self.render_enhanced_state_indicator(ui);
             ui.add_space(40.0);
@@ -1070,7 +1151,7 @@ impl CubeTimer {
     // Renders the event selector
     fn render_enhanced_event_selector(&mut self, ui: &mut egui::Ui) {
         let current_event = self.current_event.clone();
-        let available_events = self.available_events.clone();
+        let available_events = self.data.available_events.clone();
 
         ui.horizontal(|ui| {
             ui.label(RichText::new("Event:").size(self.theme.font_size_normal).color(self.theme.text_secondary_color()));
@@ -1081,20 +1162,70 @@ impl CubeTimer {
                     .color(self.theme.accent_primary_color()))
                 .show_ui(ui, |ui| {
                     for event in &available_events {
-                        if ui.selectable_value(&mut self.current_event, event.clone(),
-                                               RichText::new(format!("{}", event)).size(self.theme.font_size_normal)).clicked() {
-                            self.generate_new_scramble();
-                            self.calculate_statistics();
+                        let selected = current_event == *event;
+                        if ui.selectable_label(selected, RichText::new(format!("{}", event)).size(self.theme.font_size_normal)).clicked() {
+                            self.switch_event(event.clone());
                         }
                     }
                 });
+
+            // 3x3x3 events can scramble only part of the cube
+            if matches!(
+                current_event,
+                CubeEvent::Standard(StandardEvent::Cube3x3 | StandardEvent::OneHanded | StandardEvent::FeetSolving)
+            ) {
+                let mut settings = self.event_settings(&current_event);
+                let previous = settings.scramble_type;
+                egui::ComboBox::from_id_source("scramble_type_selector")
+                    .selected_text(RichText::new(settings.scramble_type.to_string()).size(self.theme.font_size_normal))
+                    .show_ui(ui, |ui| {
+                        for scramble_type in ScrambleType::ALL {
+                            ui.selectable_value(&mut settings.scramble_type, scramble_type, scramble_type.to_string());
+                        }
+                    });
+                if settings.scramble_type != previous {
+                    self.data.event_settings.insert(current_event.to_string(), settings);
+                    self.generate_new_scramble();
+                    self.ui_state.highlighted_indices.clear();
+                    self.calculate_statistics();
+                    self.recalculate_personal_bests();
+                }
+            }
         });
     }
 
+    // Makes an event current, with a fresh scramble and its own session and statistics
+    fn switch_event(&mut self, event: CubeEvent) {
+        self.current_event = event;
+        self.generate_new_scramble();
+        self.ensure_active_session();
+        self.ui_state.highlighted_indices.clear();
+        self.calculate_statistics();
+        self.recalculate_personal_bests();
+    }
+
     // Renders the scramble display
-    fn render_enhanced_scramble(&self, ui: &mut egui::Ui) {
+    fn render_enhanced_scramble(&mut self, ui: &mut egui::Ui) {
+        let width = ui.available_width().min(800.0);
+        let diagram = self.scramble_preview();
+        let preview_size = diagram.as_ref().map(|diagram| {
+            let scale = (SCRAMBLE_PREVIEW_HEIGHT / diagram.height).min(width * 0.4 / diagram.width);
+            Vec2::new(diagram.width, diagram.height) * scale
+        });
+        let text_width = width - preview_size.map_or(0.0, |size| size.x + 16.0);
+
+        // Grow the box for multi-line (Megaminx) and long (6x6, 7x7) scrambles
+        let chars_per_row = ((text_width - 32.0) / (self.theme.font_size_large * 0.6)).max(1.0) as usize;
+        let rows: usize = self.current_scramble
+            .lines()
+            .map(|line| line.chars().count().div_ceil(chars_per_row).max(1))
+            .sum();
+        let height = (rows as f32 * self.theme.font_size_large * 1.4 + 32.0)
+            .max(80.0)
+            .max(preview_size.map_or(0.0, |size| size.y + 32.0));
+
         let scramble_rect = ui.allocate_response(
-            Vec2::new(ui.available_width().min(800.0), 80.0),
+            Vec2::new(width, height),
             egui::Sense::hover()
         ).rect;
 
@@ -1110,7 +1241,17 @@ impl CubeTimer {
             Stroke::new(1.0, self.theme.accent_primary_color().gamma_multiply(0.3))
         );
 
-        ui.allocate_ui_at_rect(scramble_rect.shrink(16.0), |ui| {
+        let mut text_rect = scramble_rect.shrink(16.0);
+        if let (Some(diagram), Some(size)) = (diagram, preview_size) {
+            let preview_rect = egui::Rect::from_min_size(
+                egui::pos2(text_rect.right() - size.x, scramble_rect.center().y - size.y / 2.0),
+                size
+            );
+            text_rect.set_right(preview_rect.left() - 16.0);
+            self.paint_diagram(ui, &diagram, preview_rect);
+        }
+
+        ui.allocate_ui_at_rect(text_rect, |ui| {
             ui.centered_and_justified(|ui| {
                 ui.label(RichText::new(&self.current_scramble)
                     .size(self.theme.font_size_large)
@@ -1120,10 +1261,61 @@ impl CubeTimer {
         });
     }
 
-    // Renders the timer display
-    fn render_enhanced_timer(&self, ui: &mut egui::Ui) {
-        let timer_text = self.get_timer_text();
-        let timer_color = self.get_timer_color();
+    // Draws the state the current scramble leads to, applying the scramble only when it changes
+    fn scramble_preview(&mut self) -> Option<Diagram> {
+        let key = (self.current_event.clone(), self.current_scramble.clone());
+        if self.preview.as_ref().is_none_or(|(cached, _)| *cached != key) {
+            let puzzle = Puzzle::solved(&self.current_event)
+                .and_then(|mut puzzle| puzzle.apply(&self.current_scramble).ok().map(|_| puzzle));
+            self.preview = Some((key, puzzle));
+        }
+        let (_, puzzle) = self.preview.as_ref()?;
+        puzzle.as_ref().map(|puzzle| puzzle.diagram(&self.theme.color_scheme))
+    }
+
+    // Paints a puzzle diagram scaled into the rectangle
+    fn paint_diagram(&self, ui: &egui::Ui, diagram: &Diagram, rect: egui::Rect) {
+        let scale = rect.width() / diagram.width;
+        let outline = Stroke::new(1.0, self.theme.bg_color());
+        for polygon in &diagram.polygons {
+            let points = polygon.points.iter().map(|&[x, y]| rect.min + Vec2::new(x, y) * scale).collect();
+            let [r, g, b] = polygon.color;
+            ui.painter().add(egui::Shape::convex_polygon(points, Color32::from_rgb(r, g, b), outline));
+        }
+    }
+
+    // Renders the sticker color pickers of each puzzle
+    fn render_color_scheme_settings(&mut self, ui: &mut egui::Ui) {
+        let row = |ui: &mut egui::Ui, puzzle: &str, faces: &[&str], colors: &mut [[u8; 3]]| {
+            ui.label(RichText::new(puzzle).strong());
+            ui.horizontal_wrapped(|ui| {
+                for (face, color) in faces.iter().zip(colors.iter_mut()) {
+                    ui.label(*face);
+                    ui.color_edit_button_srgb(color);
+                }
+            });
+            ui.add_space(5.0);
+        };
+        let scheme = &mut self.theme.color_scheme;
+        ui.add_space(5.0);
+        row(ui, "Cube, Skewb, Square-1", &["U", "R", "F", "D", "L", "B"], &mut scheme.cube);
+        row(ui, "Pyraminx", &["F", "L", "R", "D"], &mut scheme.pyraminx);
+        row(ui, "Megaminx", &puzzle::MEGAMINX_FACES, &mut scheme.megaminx);
+        row(ui, "Clock", &["Front", "Back", "Hands", "Pins"], &mut scheme.clock);
+        if ui.button("Reset to defaults").clicked() {
+            *scheme = ColorScheme::default();
+        }
+    }
+
+    // Renders the timer display
+    fn render_enhanced_timer(&mut self, ui: &mut egui::Ui) {
+        if self.ui_state.typing_mode {
+            self.render_time_input(ui);
+            return;
+        }
+
+        let timer_text = self.get_timer_text();
+        let timer_color = self.get_timer_color();
         let scaled_size = self.theme.font_size_timer * self.timer_scale;
 
         let timer_response = ui.allocate_response(
@@ -1131,7 +1323,7 @@ impl CubeTimer {
             egui::Sense::hover()
         );
 
-        if matches!(self.state, TimerState::Running) {
+        if matches!(self.timer.state, TimerState::Running) {
             let glow_rect = timer_response.rect.expand(20.0);
             ui.painter().rect_filled(
                 glow_rect,
@@ -1150,51 +1342,148 @@ impl CubeTimer {
         });
     }
 
+    // Renders the field that replaces the timer in typing mode
+    fn render_time_input(&mut self, ui: &mut egui::Ui) {
+        let id = egui::Id::new(TYPED_TIME_ID);
+        let response = ui.add(egui::TextEdit::singleline(&mut self.ui_state.typed_time)
+            .id(id)
+            .hint_text("0.000")
+            .font(egui::FontId::monospace(self.theme.font_size_timer * 0.6))
+            .horizontal_align(egui::Align::Center)
+            .desired_width(ui.available_width() * 0.6));
+
+        if response.changed() {
+            self.ui_state.typed_time_error = None;
+        }
+        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            match timing::parse_typed_solve(&self.ui_state.typed_time) {
+                Ok(solve) => {
+                    self.ui_state.typed_time.clear();
+                    self.finish_solve(solve);
+                }
+                Err(error) => self.ui_state.typed_time_error = Some(error),
+            }
+            // Keep the field focused for the next result
+            response.request_focus();
+        }
+
+        if let Some(error) = &self.ui_state.typed_time_error {
+            ui.label(RichText::new(error)
+                .size(self.theme.font_size_small)
+                .color(self.theme.error_color()));
+        }
+    }
+
+    // Describes the phase times of the current or last split solve
+    fn split_text(&self) -> Option<String> {
+        let phases = self.event_settings(&self.current_event).phases;
+        let times = self.timer.phase_times();
+        if phases.len() < 2 || times.is_empty() {
+            return None;
+        }
+        let splits: Vec<String> = phases
+            .iter()
+            .zip(&times)
+            .map(|(name, time)| format!("{} {}", name, format_time(*time)))
+            .collect();
+        Some(splits.join("   "))
+    }
+
+    // Renders the phase times below the timer
+    fn render_splits(&self, ui: &mut egui::Ui) {
+        if self.ui_state.typing_mode {
+            return;
+        }
+        if let Some(text) = self.split_text() {
+            ui.add_space(10.0);
+            ui.label(RichText::new(text)
+                .size(self.theme.font_size_large)
+                .color(self.theme.text_secondary_color())
+                .family(egui::FontFamily::Monospace));
+        }
+    }
+
     // Gets the timer display text
     fn get_timer_text(&self) -> String {
-        if matches!(self.state, TimerState::Running) {
-            Self::format_time(self.current_time)
-        } else if let Some(last_time) = self.last_time {
-            Self::format_time(last_time)
+        self.timer.display_text()
+    }
+
+    // Determines the inspection color from the 8s and 12s warnings
+    fn get_inspection_color(&self, elapsed: Duration) -> Color32 {
+        if elapsed >= INSPECTION_SECOND_WARNING {
+            self.theme.error_color()
+        } else if elapsed >= INSPECTION_FIRST_WARNING {
+            self.theme.warning_color()
         } else {
-            "0.000".to_string()
+            self.theme.timer_color(&TimerState::Inspection)
         }
     }
 
     // Determines the timer text color based on the state and hold time
     fn get_timer_color(&self) -> Color32 {
-        if let TimerState::Preparing = self.state {
-            if let Some(hold_start) = self.space_hold_start {
-                if hold_start.elapsed() >= self.key_preparation_time {
-                    return self.theme.success_color();
-                }
-            }
+        if self.timer.is_held_long_enough() {
+            return self.theme.success_color();
         }
-        self.theme.timer_color(&self.state)
+        if let Some(elapsed) = self.timer.inspection_elapsed() {
+            return self.get_inspection_color(elapsed);
+        }
+        self.theme.timer_color(&self.timer.state)
     }
 
     // Renders the timer state indicator
     fn render_enhanced_state_indicator(&self, ui: &mut egui::Ui) {
-        let (state_text, state_color) = match self.state {
-            TimerState::Ready => ("Press and hold SPACE to start", self.theme.text_secondary_color()),
+        let (state_text, state_color) = if self.ui_state.typing_mode {
+            ("Type a time and press Enter: 12.34, 1:02.50, DNF(12.34) or 12.34+ for a +2", self.theme.text_secondary_color())
+        } else {
+            self.state_indicator()
+        };
+        ui.label(RichText::new(state_text)
+            .size(self.theme.font_size_normal)
+            .color(state_color));
+    }
+
+    // Describes what the timer is doing and what the user should do next
+    fn state_indicator(&self) -> (&'static str, Color32) {
+        match self.timer.state {
+            TimerState::Ready => {
+                if self.event_settings(&self.current_event).inspection {
+                    ("Press SPACE to start inspection", self.theme.text_secondary_color())
+                } else {
+                    ("Press and hold SPACE to start", self.theme.text_secondary_color())
+                }
+            },
+            TimerState::Inspection => {
+                let elapsed = self.timer.inspection_elapsed().unwrap_or_default();
+                let color = self.get_inspection_color(elapsed);
+                if elapsed > INSPECTION_PLUS2_LIMIT {
+                    ("Inspection over - solve will be DNF", color)
+                } else if elapsed > INSPECTION_TIME {
+                    ("Inspection over - solve will be +2", color)
+                } else if elapsed >= INSPECTION_SECOND_WARNING {
+                    ("12 seconds! Hold SPACE to start", color)
+                } else if elapsed >= INSPECTION_FIRST_WARNING {
+                    ("8 seconds! Hold SPACE to start", color)
+                } else {
+                    ("Inspecting - hold SPACE to start", color)
+                }
+            },
             TimerState::Preparing => {
-                if let Some(hold_start) = self.space_hold_start {
-                    if hold_start.elapsed() >= self.key_preparation_time {
-                        ("Release to Start", self.theme.success_color())
-                    } else {
-                        ("Hold SPACE...", self.theme.timer_color(&TimerState::Preparing))
-                    }
+                if self.timer.is_held_long_enough() {
+                    ("Release to Start", self.theme.success_color())
                 } else {
                     ("Hold SPACE...", self.theme.timer_color(&TimerState::Preparing))
                 }
             },
-            TimerState::Running => ("RUNNING - Press SPACE to stop", self.theme.timer_color(&TimerState::Running)),
+            TimerState::Running => {
+                let phases = self.event_settings(&self.current_event).phase_count();
+                if self.timer.splits.len() + 1 < phases {
+                    ("RUNNING - Press SPACE to split", self.theme.timer_color(&TimerState::Running))
+                } else {
+                    ("RUNNING - Press SPACE to stop", self.theme.timer_color(&TimerState::Running))
+                }
+            },
             TimerState::Stopped => ("Press SPACE for next solve", self.theme.success_color()),
-        };
-
-        ui.label(RichText::new(state_text)
-            .size(self.theme.font_size_normal)
-            .color(state_color));
+        }
     }
 
     // Renders quick statistics cards
@@ -1203,16 +1492,15 @@ impl CubeTimer {
             ui.spacing_mut().item_spacing.x = 20.0;
 
             if let Some(best) = self.statistics.best {
-                self.render_stat_card(ui, "Best", &Self::format_time(best), self.theme.success_color());
-            }
-            if let Some(ao5) = self.statistics.current_ao5 {
-                self.render_stat_card(ui, "Ao5", &Self::format_time(ao5), self.theme.accent_primary_color());
+                self.render_stat_card(ui, "Best", &format_time(best), self.theme.success_color());
             }
-            if let Some(ao12) = self.statistics.current_ao12 {
-                self.render_stat_card(ui, "Ao12", &Self::format_time(ao12), self.theme.accent_secondary_color());
+            for (position, (column, value)) in self.statistics.current.iter().enumerate() {
+                if let Some(value) = value {
+                    self.render_stat_card(ui, &column.label(), &format_result(*value), self.series_color(position));
+                }
             }
             if let Some(mean) = self.statistics.mean {
-                self.render_stat_card(ui, "Mean", &Self::format_time(mean), self.theme.text_secondary_color());
+                self.render_stat_card(ui, "Mean", &format_time(mean), self.theme.text_secondary_color());
             }
         });
     }
@@ -1249,9 +1537,62 @@ impl CubeTimer {
     fn render_windows(&mut self, ctx: &egui::Context) {
         self.render_settings_window(ctx);
         self.render_statistics_window(ctx);
+        self.render_data_window(ctx);
+        self.render_case_window(ctx);
         self.render_delete_confirmation(ctx);
         self.render_exit_confirmation(ctx);
         self.render_welcome_popup(ctx);
+        self.render_recovery_window(ctx);
+    }
+
+    // Renders the recovery prompt for data files that could not be loaded
+    fn render_recovery_window(&mut self, ctx: &egui::Context) {
+        if self.data.corrupt_files.is_empty() {
+            return;
+        }
+        let Some(app_dir) = self.data.dir.clone() else {
+            return;
+        };
+
+        let mut restore = None;
+        let mut discard = None;
+        egui::Window::new("⚠ Damaged Data")
+            .default_width(450.0)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(RichText::new("Some data files could not be read. They will not be overwritten until you choose what to do with them.")
+                    .size(self.theme.font_size_normal)
+                    .color(self.theme.warning_color()));
+
+                for file in &self.data.corrupt_files {
+                    ui.add_space(10.0);
+                    ui.separator();
+                    ui.label(RichText::new(&file.name).strong());
+                    ui.label(RichText::new(&file.error).size(self.theme.font_size_small).color(self.theme.text_secondary_color()));
+
+                    let backups = storage::list_backups(&app_dir, &file.name);
+                    if backups.is_empty() {
+                        ui.label("No backups available.");
+                    }
+                    for backup in backups {
+                        let name = backup.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                        if ui.button(format!("Restore {}", name)).clicked() {
+                            restore = Some((file.name.clone(), backup.clone()));
+                        }
+                    }
+                    if ui.button("Move aside and start fresh").clicked() {
+                        discard = Some(file.name.clone());
+                    }
+                }
+            });
+
+        if let Some((name, backup)) = restore {
+            self.restore_backup(&name, &backup);
+        }
+        if let Some(name) = discard {
+            self.discard_corrupt_file(&name);
+        }
     }
 
     // Renders the welcome popup for first-time users
@@ -1377,6 +1718,15 @@ impl CubeTimer {
                     ui.add_space(10.0);
                     ui.separator();
 
+                    // Puzzle Colors Section
+                    egui::CollapsingHeader::new(RichText::new("🧩 Puzzle Colors").strong())
+                        .default_open(false)
+                        .show(ui, |ui| {
+                            self.render_color_scheme_settings(ui);
+                        });
+                    ui.add_space(10.0);
+                    ui.separator();
+
                     // UI Settings Section
                     egui::CollapsingHeader::new(RichText::new("⚙ UI Settings").strong())
                         .default_open(false)
@@ -1407,35 +1757,57 @@ impl CubeTimer {
                     ui.add_space(10.0);
                     ui.separator();
 
-                    // Events Section
-                    egui::CollapsingHeader::new(RichText::new("🎲 Custom Events").strong())
+                    // Inspection Section
+                    egui::CollapsingHeader::new(RichText::new("⏱ Inspection").strong())
                         .default_open(false)
                         .show(ui, |ui| {
                             ui.add_space(5.0);
-                            ui.label("Create New Custom Event:");
-                            ui.horizontal(|ui| {
-                                ui.label("Name:");
-                                ui.text_edit_singleline(&mut self.new_custom_event_name);
-                                ui.label("Moves (comma-separated):");
-                                ui.text_edit_singleline(&mut self.new_custom_moves);
-                            });
-
-                            if ui.button("Add Custom Event").clicked() {
-                                self.add_custom_event();
+                            ui.label("Use 15 second WCA inspection for:");
+                            for event in self.data.available_events.clone() {
+                                let mut settings = self.event_settings(&event);
+                                if ui.checkbox(&mut settings.inspection, format!("{}", event)).changed() {
+                                    self.data.event_settings.insert(event.to_string(), settings);
+                                }
                             }
+                        });
+                    ui.add_space(10.0);
+                    ui.separator();
 
-                            ui.separator();
+                    // Statistics Section
+                    egui::CollapsingHeader::new(RichText::new("📊 Statistics Columns").strong())
+                        .default_open(false)
+                        .show(ui, |ui| {
+                            self.render_stat_column_settings(ui);
+                        });
+                    ui.add_space(10.0);
+                    ui.separator();
 
-                            ui.label("Existing Custom Events:");
-                            let custom_event_names: Vec<String> = self.custom_events.keys().cloned().collect();
-                            for name in custom_event_names {
-                                ui.horizontal(|ui| {
-                                    ui.label(&name);
-                                    if ui.button("Remove").clicked() {
-                                        self.remove_custom_event(&name);
-                                    }
-                                });
-                            }
+                    // Stackmat Section
+                    #[cfg(feature = "stackmat-audio")]
+                    {
+                        egui::CollapsingHeader::new(RichText::new("🎛 Stackmat").strong())
+                            .default_open(false)
+                            .show(ui, |ui| {
+                                self.render_stackmat_settings(ui);
+                            });
+                        ui.add_space(10.0);
+                        ui.separator();
+                    }
+
+                    // Splits Section
+                    egui::CollapsingHeader::new(RichText::new("✂ Splits").strong())
+                        .default_open(false)
+                        .show(ui, |ui| {
+                            self.render_phase_settings(ui);
+                        });
+                    ui.add_space(10.0);
+                    ui.separator();
+
+                    // Events Section
+                    egui::CollapsingHeader::new(RichText::new("🎲 Custom Events").strong())
+                        .default_open(false)
+                        .show(ui, |ui| {
+                            self.render_custom_event_settings(ui);
                         });
                     ui.add_space(10.0);
                     ui.separator();
@@ -1444,6 +1816,252 @@ impl CubeTimer {
         self.ui_state.show_settings = show_settings;
     }
 
+    // Renders the editor for the current event's stat columns
+    fn render_stat_column_settings(&mut self, ui: &mut egui::Ui) {
+        let event = self.current_event.clone();
+        let mut columns = self.stat_columns(&event);
+        let mut changed = false;
+
+        ui.add_space(5.0);
+        ui.label(format!("Averages and means shown for {}:", event));
+        let mut remove = None;
+        for (position, column) in columns.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(column.label()).color(self.series_color(position)));
+                // Keep at least one column so the list never falls back to the defaults unexpectedly
+                if ui.add_enabled(columns.len() > 1, egui::Button::new("Remove")).clicked() {
+                    remove = Some(position);
+                }
+            });
+        }
+        if let Some(position) = remove {
+            columns.remove(position);
+            changed = true;
+        }
+
+        ui.add_space(5.0);
+        ui.horizontal(|ui| {
+            let column = &mut self.new_stat_column;
+            egui::ComboBox::from_id_source("new_stat_column_kind")
+                .selected_text(match column.kind {
+                    StatKind::Average => "Average",
+                    StatKind::Mean => "Mean",
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut column.kind, StatKind::Average, "Average");
+                    ui.selectable_value(&mut column.kind, StatKind::Mean, "Mean");
+                });
+            ui.label("of");
+            ui.add(egui::DragValue::new(&mut column.size).clamp_range(1..=10000));
+            if column.kind == StatKind::Average {
+                ui.label("trimming");
+                ui.add(egui::DragValue::new(&mut column.trim_percent).clamp_range(0..=49).suffix("%"));
+            }
+        });
+
+        // An average has to keep at least one attempt after trimming
+        let mut new_column = self.new_stat_column.clone();
+        if new_column.kind == StatKind::Mean {
+            new_column.trim_percent = 0;
+        }
+        let valid = new_column.size > 0 && new_column.trim_count() * 2 < new_column.size;
+        ui.horizontal(|ui| {
+            if ui.add_enabled(valid && !columns.contains(&new_column), egui::Button::new("Add Column")).clicked() {
+                columns.push(new_column);
+                changed = true;
+            }
+            if ui.button("Reset to Defaults").clicked() {
+                columns = StatColumn::defaults_for(&event);
+                changed = true;
+            }
+        });
+
+        if changed {
+            self.set_stat_columns(&event, columns);
+        }
+    }
+
+    // Renders the custom event definition editor, definition files and the existing events
+    fn render_custom_event_settings(&mut self, ui: &mut egui::Ui) {
+        ui.add_space(5.0);
+        ui.label("Custom Event Definition:");
+        ui.add(
+            egui::TextEdit::multiline(&mut self.new_custom_definition)
+                .code_editor()
+                .desired_rows(6)
+                .desired_width(f32::INFINITY)
+                .hint_text("name Floppy\nlength 12\nmoves R[2]*2, L[2]*2, U[2], D[2]\naxes R L; U D\nprefix z2"),
+        );
+        ui.label(
+            RichText::new(
+                "One directive per line: name, length, moves, axes, lines (e.g. \"lines 10 U U'\"), separator, prefix and suffix. \
+                 Suffixes go in brackets separated by / and default to none, ' and 2; a * gives a move's weight. \
+                 Moves in an axis group are never repeated while only moves of their group come between.",
+            )
+            .small()
+            .color(self.theme.text_secondary_color()),
+        );
+        if ui.button("Save Custom Event").clicked() {
+            let result = scramble::parse_definition(&self.new_custom_definition).and_then(|custom| self.add_custom_event(custom));
+            if result.is_ok() {
+                self.new_custom_definition.clear();
+            }
+            self.custom_event_message = Some(result);
+        }
+
+        ui.add_space(5.0);
+        ui.horizontal(|ui| {
+            ui.label("File:");
+            ui.add(egui::TextEdit::singleline(&mut self.custom_event_path).hint_text(Self::default_definition_path("<name>")));
+            if ui.button("📥 Import").clicked() {
+                let path = self.custom_event_path.trim().to_string();
+                self.custom_event_message = Some(
+                    std::fs::read_to_string(&path)
+                        .map_err(|e| format!("Could not read {}: {}", path, e))
+                        .and_then(|text| scramble::parse_definition(&text))
+                        .and_then(|custom| self.add_custom_event(custom)),
+                );
+            }
+        });
+        match &self.custom_event_message {
+            Some(Ok(message)) => {
+                ui.label(RichText::new(message).color(self.theme.success_color()));
+            }
+            Some(Err(message)) => {
+                ui.label(RichText::new(message).color(self.theme.error_color()));
+            }
+            None => {}
+        }
+
+        ui.separator();
+
+        ui.label("Existing Custom Events:");
+        let mut custom_event_names: Vec<String> = self.data.custom_events.keys().cloned().collect();
+        custom_event_names.sort();
+        let (mut edit, mut export, mut remove) = (None, None, None);
+        for name in custom_event_names {
+            let mut changed = false;
+            ui.horizontal(|ui| {
+                let Some(custom) = self.data.custom_events.get_mut(&name) else {
+                    return;
+                };
+                ui.label(RichText::new(&name).strong());
+                ui.label(RichText::new(format!("{} moves", custom.moves.len())).color(self.theme.text_secondary_color()));
+                ui.label("Length:");
+                changed = ui.add(egui::DragValue::new(&mut custom.scramble_length).clamp_range(1..=200)).changed();
+                if ui.button("Edit").clicked() {
+                    edit = Some(scramble::format_definition(custom));
+                }
+                if ui.button("📤 Export").clicked() {
+                    export = Some(custom.clone());
+                }
+                if ui.button("Remove").clicked() {
+                    remove = Some(name.clone());
+                }
+            });
+            if changed {
+                if self.current_event == CubeEvent::Custom(name) {
+                    self.generate_new_scramble();
+                }
+                self.save_data();
+            }
+        }
+
+        if let Some(definition) = edit {
+            self.new_custom_definition = definition;
+        }
+        if let Some(custom) = export {
+            let mut path = self.custom_event_path.trim().to_string();
+            if path.is_empty() {
+                path = Self::default_definition_path(&custom.name);
+            }
+            self.custom_event_message = Some(
+                std::fs::write(&path, scramble::format_definition(&custom))
+                    .map(|_| format!("Exported {} to {}", custom.name, path))
+                    .map_err(|e| format!("Could not write {}: {}", path, e)),
+            );
+        }
+        if let Some(name) = remove {
+            self.remove_custom_event(&name);
+        }
+    }
+
+    // Renders the editor for the phases the current event's solves are split into
+    fn render_phase_settings(&mut self, ui: &mut egui::Ui) {
+        let event = self.current_event.clone();
+        let mut settings = self.event_settings(&event);
+        let mut changed = false;
+
+        ui.add_space(5.0);
+        ui.label(format!("Press SPACE at the end of each phase while solving {}:", event));
+        let mut count = settings.phase_count();
+        ui.horizontal(|ui| {
+            ui.label("Phases per solve:");
+            if ui.add(egui::DragValue::new(&mut count).clamp_range(1..=10)).changed() {
+                // Keep the names of the phases that remain
+                settings.phases.truncate(count);
+                while settings.phases.len() < count {
+                    settings.phases.push(format!("Phase {}", settings.phases.len() + 1));
+                }
+                if count == 1 {
+                    settings.phases.clear();
+                }
+                changed = true;
+            }
+        });
+
+        for (position, name) in settings.phases.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(format!("{}.", position + 1)).color(self.series_color(position)));
+                changed |= ui.text_edit_singleline(name).changed();
+            });
+        }
+
+        ui.add_space(5.0);
+        ui.horizontal(|ui| {
+            let presets: [(&str, &[&str]); 3] = [
+                ("CFOP", &["Cross", "F2L", "OLL", "PLL"]),
+                ("BLD", &["Memo", "Execution"]),
+                ("No splits", &[]),
+            ];
+            for (label, phases) in presets {
+                if ui.button(label).clicked() {
+                    settings.phases = phases.iter().map(|phase| phase.to_string()).collect();
+                    changed = true;
+                }
+            }
+        });
+
+        if changed {
+            self.data.event_settings.insert(event.to_string(), settings);
+            self.calculate_statistics();
+        }
+    }
+
+    // Renders the switch for timing with a Stackmat plugged into the audio input
+    #[cfg(feature = "stackmat-audio")]
+    fn render_stackmat_settings(&mut self, ui: &mut egui::Ui) {
+        ui.add_space(5.0);
+        let mut enabled = self.stackmat.is_some();
+        if ui.checkbox(&mut enabled, "Use a Stackmat plugged into the audio input").changed() {
+            self.stackmat = enabled.then(StackmatCapture::start);
+        }
+
+        match &self.stackmat {
+            Some(Ok(capture)) => {
+                ui.label(RichText::new(format!("Listening on {}", capture.device_name))
+                    .size(self.theme.font_size_small)
+                    .color(self.theme.text_secondary_color()));
+            }
+            Some(Err(error)) => {
+                ui.label(RichText::new(error)
+                    .size(self.theme.font_size_small)
+                    .color(self.theme.error_color()));
+            }
+            None => {}
+        }
+    }
+
     // Renders the statistics window
         fn render_statistics_window(&mut self, ctx: &egui::Context) {
             if !self.ui_state.show_statistics {
@@ -1457,10 +2075,12 @@ impl CubeTimer {
                 .default_height(800.0)
                 .resizable(true)
                 .show(ctx, |ui| {
-                    let current_event_records: Vec<(usize, TimeRecord)> = self.records
+                    ui.label(RichText::new(self.scope_description()).size(self.theme.font_size_normal).color(self.theme.text_secondary_color()));
+
+                    let current_event_records: Vec<(usize, TimeRecord)> = self.data.records
                         .iter()
                         .enumerate()
-                        .filter(|(_, r)| r.event == self.current_event)
+                        .filter(|(_, r)| self.is_in_scope(r))
                         .map(|(i, r)| (i, r.clone()))
                         .collect();
     
@@ -1471,53 +2091,49 @@ impl CubeTimer {
                         return;
                     }
     
-                    // Prepare plot data
+                    // Prepare plot data; the rolling averages are cached with the statistics
                     let mut solve_points: Vec<egui_plot::PlotPoint> = Vec::new();
-                    let mut ao5_points: Vec<egui_plot::PlotPoint> = Vec::new();
-                    let mut ao12_points: Vec<egui_plot::PlotPoint> = Vec::new();
-    
-                    let mut current_times_for_avg: Vec<Duration> = Vec::new();
                     for (i, (_, record)) in current_event_records.iter().enumerate() {
-                        let solve_time_ms = record.time.as_millis() as f64;
-                        solve_points.push(egui_plot::PlotPoint::new(i as f64, solve_time_ms));
-    
-                        current_times_for_avg.push(record.time);
-    
-                        if current_times_for_avg.len() >= 5 {
-                            let last_5: Vec<Duration> = current_times_for_avg.iter().rev().take(5).cloned().collect();
-                            if let Some(ao5) = Self::calculate_average(&last_5) {
-                                ao5_points.push(egui_plot::PlotPoint::new(i as f64, ao5.as_millis() as f64));
-                            }
-                        }
-    
-                        if current_times_for_avg.len() >= 12 {
-                            let last_12: Vec<Duration> = current_times_for_avg.iter().rev().take(12).cloned().collect();
-                            if let Some(ao12) = Self::calculate_average(&last_12) {
-                                ao12_points.push(egui_plot::PlotPoint::new(i as f64, ao12.as_millis() as f64));
-                            }
+                        // DNFs have no time to plot but still count towards the averages
+                        if let SolveResult::Time(time) = record.result() {
+                            solve_points.push(egui_plot::PlotPoint::new(i as f64, time.as_millis() as f64));
                         }
                     }
+                    let column_points: Vec<(&StatColumn, Vec<egui_plot::PlotPoint>)> = self.statistics.rolling
+                        .iter()
+                        .map(|(column, values)| {
+                            let points = values.iter()
+                                .enumerate()
+                                .filter_map(|(i, value)| match value {
+                                    Some(SolveResult::Time(average)) => Some(egui_plot::PlotPoint::new(i as f64, average.as_millis() as f64)),
+                                    _ => None,
+                                })
+                                .collect();
+                            (column, points)
+                        })
+                        .collect();
     
                     // Convert PlotPoint vectors to [f64; 2] vectors for PlotPoints
                     let solve_coords: Vec<[f64; 2]> = solve_points.iter()
                         .map(|point| [point.x, point.y])
                         .collect();
-                    let ao5_coords: Vec<[f64; 2]> = ao5_points.iter()
-                        .map(|point| [point.x, point.y])
-                        .collect();
-                    let ao12_coords: Vec<[f64; 2]> = ao12_points.iter()
-                        .map(|point| [point.x, point.y])
-                        .collect();
     
                     let solve_line = Line::new(PlotPoints::from(solve_coords))
                         .color(self.theme.accent_primary_color())
                         .name("Solve Times");
-                    let ao5_line = Line::new(PlotPoints::from(ao5_coords))
-                        .color(self.theme.success_color())
-                        .name("Ao5");
-                    let ao12_line = Line::new(PlotPoints::from(ao12_coords))
-                        .color(self.theme.accent_secondary_color())
-                        .name("Ao12");
+                    let column_lines: Vec<Line> = column_points
+                        .into_iter()
+                        .enumerate()
+                        .filter(|(_, (_, points))| !points.is_empty())
+                        .map(|(position, (column, points))| {
+                            let coords: Vec<[f64; 2]> = points.iter()
+                                .map(|point| [point.x, point.y])
+                                .collect();
+                            Line::new(PlotPoints::from(coords))
+                                .color(self.series_color(position))
+                                .name(column.label())
+                        })
+                        .collect();
     
                     let plot = Plot::new("time_graph")
                         .view_aspect(2.0)
@@ -1527,14 +2143,376 @@ impl CubeTimer {
     
                     plot.show(ui, |plot_ui| {
                         plot_ui.line(solve_line);
-                        plot_ui.line(ao5_line);
-                        plot_ui.line(ao12_line);
+                        for line in column_lines {
+                            plot_ui.line(line);
+                        }
                     });
+
+                    if !self.statistics.phases.is_empty() {
+                        self.render_phase_statistics(ui, &current_event_records);
+                    }
                 });
     
             self.ui_state.show_statistics = show_stats;
         }
 
+    // Renders per-phase averages and a graph of each split solve's phases stacked on each other
+    fn render_phase_statistics(&self, ui: &mut egui::Ui, records: &[(usize, TimeRecord)]) {
+        let phases = &self.statistics.phases;
+
+        ui.add_space(10.0);
+        ui.label(RichText::new("Phases").size(self.theme.font_size_large).color(self.theme.text_primary_color()));
+        egui::Grid::new("phase_statistics").striped(true).show(ui, |ui| {
+            ui.label("Phase");
+            ui.label("Mean");
+            for (column, _) in &phases[0].current {
+                ui.label(column.label());
+            }
+            ui.end_row();
+
+            for (position, phase) in phases.iter().enumerate() {
+                ui.label(RichText::new(&phase.name).color(self.series_color(position)));
+                ui.label(phase.mean.map_or("-".to_string(), format_time));
+                for (_, value) in &phase.current {
+                    ui.label(value.map_or("-".to_string(), format_result));
+                }
+                ui.end_row();
+            }
+        });
+
+        // Same solves as the phase averages, placed at their position in the time graph
+        let split_solves: Vec<(usize, &TimeRecord)> = records
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, r))| r.penalty != Some(Penalty::DNF) && r.splits.len() == phases.len())
+            .map(|(i, (_, r))| (i, r))
+            .collect();
+
+        let mut charts: Vec<BarChart> = Vec::new();
+        for (position, phase) in phases.iter().enumerate() {
+            let bars = split_solves
+                .iter()
+                .map(|(i, r)| Bar::new(*i as f64, r.splits[position].as_millis() as f64))
+                .collect();
+            let below: Vec<&BarChart> = charts.iter().collect();
+            let chart = BarChart::new(bars)
+                .color(self.series_color(position))
+                .name(&phase.name)
+                .width(0.8)
+                .stack_on(&below);
+            charts.push(chart);
+        }
+
+        Plot::new("phase_graph")
+            .view_aspect(2.0)
+            .show_axes([false, true])
+            .legend(Legend::default())
+            .show(ui, |plot_ui| {
+                for chart in charts {
+                    plot_ui.bar_chart(chart);
+                }
+            });
+    }
+
+    // Renders the window for importing and exporting solves
+    fn render_data_window(&mut self, ctx: &egui::Context) {
+        if !self.ui_state.show_data_window {
+            return;
+        }
+
+        let mut show_window = self.ui_state.show_data_window;
+        egui::Window::new("📂 Data")
+            .open(&mut show_window)
+            .default_width(450.0)
+            .resizable(true)
+            .show(ctx, |ui| {
+                ui.label(RichText::new("Import from another timer").strong().size(self.theme.font_size_normal));
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    ui.label("File:");
+                    ui.text_edit_singleline(&mut self.ui_state.import_path);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Format:");
+                    egui::ComboBox::from_id_source("import_format")
+                        .selected_text(self.ui_state.import_format.map_or("Detect automatically", |f| f.name()))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.ui_state.import_format, None, "Detect automatically");
+                            for format in ImportFormat::ALL {
+                                ui.selectable_value(&mut self.ui_state.import_format, Some(format), format.name());
+                            }
+                        });
+                });
+                ui.add_space(5.0);
+                if ui.button("📥 Import").clicked() {
+                    let path = self.ui_state.import_path.trim().to_string();
+                    let result = self.import_file(&path, self.ui_state.import_format);
+                    self.ui_state.data_message = Some(result.map(|(imported, skipped)| {
+                        format!("Imported {} solves, skipped {} already imported", imported, skipped)
+                    }));
+                }
+
+                ui.add_space(10.0);
+                ui.separator();
+                ui.label(RichText::new("Export").strong().size(self.theme.font_size_normal));
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    ui.label("Format:");
+                    egui::ComboBox::from_id_source("export_format")
+                        .selected_text(self.ui_state.export_format.name())
+                        .show_ui(ui, |ui| {
+                            for format in ExportFormat::ALL {
+                                ui.selectable_value(&mut self.ui_state.export_format, format, format.name());
+                            }
+                        });
+                    ui.label("Solves:");
+                    egui::ComboBox::from_id_source("export_scope")
+                        .selected_text(self.ui_state.export_scope.name())
+                        .show_ui(ui, |ui| {
+                            for scope in ExportScope::ALL {
+                                ui.selectable_value(&mut self.ui_state.export_scope, scope, scope.name());
+                            }
+                        });
+                });
+                if self.ui_state.export_scope == ExportScope::DateRange {
+                    ui.horizontal(|ui| {
+                        ui.label("From:");
+                        ui.add(egui::TextEdit::singleline(&mut self.ui_state.export_from).hint_text("YYYY-MM-DD").desired_width(100.0));
+                        ui.label("To:");
+                        ui.add(egui::TextEdit::singleline(&mut self.ui_state.export_to).hint_text("YYYY-MM-DD").desired_width(100.0));
+                    });
+                }
+                ui.horizontal(|ui| {
+                    ui.label("File:");
+                    let default_path = Self::default_export_path(self.ui_state.export_format);
+                    ui.add(egui::TextEdit::singleline(&mut self.ui_state.export_path).hint_text(default_path));
+                });
+                ui.add_space(5.0);
+                if ui.button("📤 Export").clicked() {
+                    let mut path = self.ui_state.export_path.trim().to_string();
+                    if path.is_empty() {
+                        path = Self::default_export_path(self.ui_state.export_format);
+                    }
+                    self.ui_state.data_message = Some(self.export_file(&path, self.ui_state.export_format, self.ui_state.export_scope));
+                }
+
+                ui.add_space(10.0);
+                ui.separator();
+                self.render_scramble_set_settings(ui);
+
+                match &self.ui_state.data_message {
+                    Some(Ok(message)) => {
+                        ui.label(RichText::new(message).color(self.theme.success_color()));
+                    }
+                    Some(Err(message)) => {
+                        ui.label(RichText::new(message).color(self.theme.error_color()));
+                    }
+                    None => {}
+                }
+            });
+        self.ui_state.show_data_window = show_window;
+    }
+
+    // Renders the controls for racing on a shared scramble set: starting one from a code or a
+    // file, and sharing the current one
+    fn render_scramble_set_settings(&mut self, ui: &mut egui::Ui) {
+        ui.label(RichText::new("Scramble set").strong().size(self.theme.font_size_normal));
+        ui.add_space(5.0);
+        match &self.scramble_set {
+            Some(set) => {
+                ui.label(format!("Using set {}: everyone with the code gets the same scrambles, whatever their scramble type and case settings", set.code));
+            }
+            None => {
+                ui.label(RichText::new("Scrambles are random; start a set to share them").color(self.theme.text_secondary_color()));
+            }
+        }
+        ui.horizontal(|ui| {
+            ui.label("Code:");
+            ui.add(egui::TextEdit::singleline(&mut self.ui_state.set_code).hint_text("K7F3-9QX2 or any seed").desired_width(160.0));
+            if ui.button("▶ Start").clicked() {
+                let result = ScrambleSet::from_code(&self.ui_state.set_code).map(|set| self.start_scramble_set(set));
+                self.ui_state.data_message = Some(result);
+            }
+            if ui.button("🎲 New set").clicked() {
+                let set = ScrambleSet::random(&mut rand::thread_rng());
+                self.ui_state.set_code = set.code.clone();
+                self.ui_state.data_message = Some(Ok(self.start_scramble_set(set)));
+            }
+            if self.scramble_set.is_some() && ui.button("⏹ Stop").clicked() {
+                self.scramble_set = None;
+                self.generate_new_scramble();
+                self.calculate_statistics();
+                self.recalculate_personal_bests();
+                self.ui_state.data_message = Some(Ok("Back to random scrambles".to_string()));
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("File:");
+            ui.add(egui::TextEdit::singleline(&mut self.ui_state.set_path).hint_text(Self::default_set_path()));
+        });
+        ui.horizontal(|ui| {
+            if ui.button("📥 Import set").clicked() {
+                let path = self.set_file_path();
+                let result = std::fs::read_to_string(&path)
+                    .map_err(|e| format!("Could not read {}: {}", path, e))
+                    .and_then(|contents| ScrambleSet::from_json(&contents))
+                    .map(|set| {
+                        self.ui_state.set_code = set.code.clone();
+                        self.start_scramble_set(set)
+                    });
+                self.ui_state.data_message = Some(result);
+            }
+            if let Some(set) = &self.scramble_set {
+                ui.separator();
+                ui.add(egui::DragValue::new(&mut self.ui_state.set_count).clamp_range(1..=1000));
+                ui.label(format!("{} scrambles", self.current_event));
+                if ui.button("📤 Export set").clicked() {
+                    let path = self.set_file_path();
+                    let mut set = set.clone();
+                    let count = self.ui_state.set_count;
+                    set.write_out(&self.current_event, count, &self.data.custom_events);
+                    let result = std::fs::write(&path, set.to_json())
+                        .map(|_| format!("Wrote {} {} scrambles of set {} to {}", count, self.current_event, set.code, path))
+                        .map_err(|e| format!("Could not write {}: {}", path, e));
+                    self.scramble_set = Some(set);
+                    self.ui_state.data_message = Some(result);
+                }
+            }
+        });
+    }
+
+    // File scramble sets are read from and written to, the default one unless a path is typed in
+    fn set_file_path(&self) -> String {
+        let path = self.ui_state.set_path.trim();
+        if path.is_empty() { Self::default_set_path() } else { path.to_string() }
+    }
+
+    // Default file a scramble set is written to, in the home directory
+    fn default_set_path() -> String {
+        let file = "lstimer-scramble-set.json".to_string();
+        dirs::home_dir()
+            .map(|home| home.join(&file).to_string_lossy().into_owned())
+            .unwrap_or(file)
+    }
+
+    // Renders the trainer's case picker with each case's picture and results, slowest and
+    // most failed cases first
+    fn render_case_window(&mut self, ctx: &egui::Context) {
+        let CubeEvent::Training(set) = self.current_event else {
+            return;
+        };
+        if !self.ui_state.show_case_window {
+            return;
+        }
+
+        let event = self.current_event.clone();
+        let mut settings = self.event_settings(&event);
+        let stats = trainer::case_stats(self.data.records.iter().filter(|record| record.event == event));
+        let mut changed = false;
+        let mut show_window = self.ui_state.show_case_window;
+        egui::Window::new(format!("🧩 {} Cases", set))
+            .open(&mut show_window)
+            .default_width(520.0)
+            .default_height(600.0)
+            .resizable(true)
+            .show(ctx, |ui| {
+                let summary = |stats: &trainer::CaseStats| {
+                    let mean = stats.mean.map_or("DNF".to_string(), format_time);
+                    format!("{} · {} · {} DNF in {}", stats.case, mean, stats.dnfs, stats.solves)
+                };
+                ui.columns(2, |columns| {
+                    columns[0].label(RichText::new("Slowest").strong());
+                    for stats in trainer::slowest(&stats).into_iter().take(5) {
+                        columns[0].label(RichText::new(summary(stats)).size(self.theme.font_size_small));
+                    }
+                    columns[1].label(RichText::new("Most failed").strong());
+                    for stats in trainer::most_failed(&stats).into_iter().take(5) {
+                        columns[1].label(RichText::new(summary(stats)).size(self.theme.font_size_small));
+                    }
+                });
+                ui.separator();
+                if settings.cases.is_empty() {
+                    ui.label(RichText::new("No cases picked, so scrambles use every case")
+                        .color(self.theme.text_secondary_color()));
+                } else {
+                    ui.label(format!("{} of {} cases picked", settings.cases.len(), set.cases().len()));
+                }
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    let cases = set.cases();
+                    let mut groups: Vec<&str> = Vec::new();
+                    for case in cases {
+                        if !groups.contains(&case.group.as_str()) {
+                            groups.push(&case.group);
+                        }
+                    }
+                    for group in groups {
+                        let members: Vec<&trainer::Case> = cases.iter().filter(|case| case.group == group).collect();
+                        let picked = members.iter().filter(|case| settings.cases.contains(&case.id)).count();
+                        egui::CollapsingHeader::new(RichText::new(format!("{} ({}/{})", group, picked, members.len())).strong())
+                            .id_source(("case_group", group))
+                            .show(ui, |ui| {
+                                ui.horizontal(|ui| {
+                                    if ui.button("Select all").clicked() {
+                                        for case in &members {
+                                            if !settings.cases.contains(&case.id) {
+                                                settings.cases.push(case.id.clone());
+                                            }
+                                        }
+                                        changed = true;
+                                    }
+                                    if ui.button("Select none").clicked() {
+                                        settings.cases.retain(|id| members.iter().all(|case| case.id != *id));
+                                        changed = true;
+                                    }
+                                });
+                                ui.horizontal_wrapped(|ui| {
+                                    for case in &members {
+                                        ui.vertical(|ui| {
+                                            ui.set_width(90.0);
+                                            let diagram = case.diagram(&self.theme.color_scheme.cube);
+                                            let scale = 80.0 / diagram.width.max(diagram.height);
+                                            let size = Vec2::new(diagram.width, diagram.height) * scale;
+                                            let rect = ui.allocate_exact_size(size, egui::Sense::hover()).0;
+                                            self.paint_diagram(ui, &diagram, rect);
+
+                                            let mut selected = settings.cases.contains(&case.id);
+                                            let checkbox = ui.checkbox(&mut selected, &case.name);
+                                            if let Some(algorithm) = &case.algorithm {
+                                                checkbox.on_hover_text(algorithm);
+                                            }
+                                            if selected != settings.cases.contains(&case.id) {
+                                                if selected {
+                                                    settings.cases.push(case.id.clone());
+                                                } else {
+                                                    settings.cases.retain(|id| *id != case.id);
+                                                }
+                                                changed = true;
+                                            }
+                                            if let Some(stats) = stats.iter().find(|stats| stats.case == case.id) {
+                                                let mean = stats.mean.map_or("DNF".to_string(), format_time);
+                                                ui.label(RichText::new(format!("{} ({})", mean, stats.solves))
+                                                    .size(self.theme.font_size_small)
+                                                    .color(self.theme.text_secondary_color()));
+                                            }
+                                        });
+                                    }
+                                });
+                            });
+                    }
+                });
+            });
+        self.ui_state.show_case_window = show_window;
+
+        if changed {
+            self.data.event_settings.insert(event.to_string(), settings.clone());
+            // Move on from a case that is no longer picked
+            if self.current_case.as_ref().is_some_and(|case| !settings.cases.is_empty() && !settings.cases.contains(case)) {
+                self.generate_new_scramble();
+            }
+        }
+    }
+
     // Renders the delete confirmation popup
     fn render_delete_confirmation(&mut self, ctx: &egui::Context) {
         if self.ui_state.confirm_delete_index.is_none() {
@@ -1602,45 +2580,195 @@ impl CubeTimer {
         self.ui_state.show_exit_popup = show_popup;
     }
 
-    // Adds a new custom event
-    fn add_custom_event(&mut self) {
-        if self.new_custom_event_name.trim().is_empty() || self.new_custom_moves.trim().is_empty() {
-            return;
+    // Returns the id of the active session for the current event
+    fn active_session_id(&self) -> u64 {
+        self.data.sessions.active_id(&self.current_event)
+    }
+
+    // Part of the cube the current event's scrambles randomize; scramble sets always scramble
+    // the whole cube
+    fn scramble_type(&self) -> ScrambleType {
+        if self.scramble_set.is_some() {
+            ScrambleType::RandomState
+        } else {
+            self.data.scramble_type(&self.current_event)
+        }
+    }
+
+    // Returns true if a record belongs to the current event and scramble type and the session
+    // being viewed, so drill solves never count towards the event's statistics
+    fn is_in_scope(&self, record: &TimeRecord) -> bool {
+        record.event == self.current_event
+            && record.scramble_type == self.scramble_type()
+            && (self.ui_state.show_all_sessions || record.session == self.active_session_id())
+    }
+
+    // Describes which solves a personal best covers. They are numbered like the times list when
+    // it shows them all, and by the event's whole history otherwise.
+    fn personal_best_solves(&self, pb: &PersonalBest) -> String {
+        let listed: Vec<usize> = self.data.records
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| self.is_in_scope(r))
+            .map(|(i, _)| i)
+            .collect();
+        let first = pb.record_indices.first().and_then(|first| listed.iter().position(|i| i == first));
+        match first {
+            Some(position) if pb.record_indices.iter().all(|i| listed.contains(i)) => {
+                format!("solves {}-{}", position + 1, position + pb.column.size)
+            }
+            _ => format!("event solves {}-{}", pb.first_solve, pb.first_solve + pb.column.size - 1),
         }
+    }
 
-        let moves: Vec<String> = self.new_custom_moves.split(',').map(|s| s.trim().to_string()).collect();
-        let custom_event = CustomEvent {
-            name: self.new_custom_event_name.clone(),
-            scramble_length: 20, // Default scramble length for custom events for now
-            moves,
+    // Describes which solves the statistics cover
+    fn scope_description(&self) -> String {
+        let scramble_type = self.scramble_type();
+        let event = if scramble_type.is_random_state() {
+            self.current_event.to_string()
+        } else {
+            format!("{} ({})", self.current_event, scramble_type)
         };
+        if self.ui_state.show_all_sessions {
+            format!("{} - all sessions", event)
+        } else {
+            let name = self.data.sessions.get(self.active_session_id()).map(|s| s.name.clone()).unwrap_or_default();
+            format!("{} - {}", event, name)
+        }
+    }
+
+    // Makes sure the current event has a valid, selected session
+    fn ensure_active_session(&mut self) {
+        self.data.sessions.ensure_active(&self.current_event);
+    }
+
+    // Switches the current event to another of its sessions
+    fn switch_session(&mut self, id: u64) {
+        if self.data.switch_session(&self.current_event, id) {
+            self.ui_state.selected_time_index = None;
+            self.calculate_statistics();
+            self.save_data();
+        }
+    }
+
+    // Renames a session
+    fn rename_session(&mut self, id: u64, name: String) {
+        self.data.rename_session(id, name);
+        self.save_data();
+    }
+
+    // Archives or restores a session, moving away from it if it was active and archived
+    // sessions are hidden
+    fn set_session_archived(&mut self, id: u64, archived: bool) {
+        self.data.set_session_archived(id, archived, self.ui_state.show_archived_sessions);
+        self.calculate_statistics();
+        self.save_data();
+    }
+
+    // Moves every solve of one session into another and removes the emptied session
+    fn merge_sessions(&mut self, from: u64, into: u64) {
+        if self.data.merge_sessions(from, into) {
+            self.calculate_statistics();
+            self.save_data();
+        }
+    }
+
+    // Adds a custom event, or replaces the definition of the one with its name
+    fn add_custom_event(&mut self, custom_event: CustomEvent) -> Result<String, String> {
+        let event = CubeEvent::Custom(custom_event.name.clone());
+        let message = self.data.add_custom_event(custom_event)?;
+        if self.current_event == event {
+            self.generate_new_scramble();
+        }
+        self.save_data();
+        Ok(message)
+    }
+
+    // Default file a custom event definition is exported to, in the home directory
+    fn default_definition_path(name: &str) -> String {
+        let file = format!("{}.txt", name);
+        dirs::home_dir()
+            .map(|home| home.join(&file).to_string_lossy().into_owned())
+            .unwrap_or(file)
+    }
+
+    // Imports the solves of another timer's backup, skipping solves that were already imported,
+    // and returns how many solves were imported and skipped
+    fn import_file(&mut self, path: &str, format: Option<ImportFormat>) -> Result<(usize, usize), String> {
+        let counts = self.data.import_file(path, format)?;
+        self.ui_state.selected_time_index = None;
+        self.ui_state.highlighted_indices.clear();
+        self.calculate_statistics();
+        self.recalculate_personal_bests();
+        self.save_data();
+        Ok(counts)
+    }
+
+    // Default file an export is written to, in the home directory
+    fn default_export_path(format: ExportFormat) -> String {
+        let file = format!("lstimer-export.{}", format.extension());
+        dirs::home_dir()
+            .map(|home| home.join(&file).to_string_lossy().into_owned())
+            .unwrap_or(file)
+    }
+
+    // Date range and selection the data window limits exports to; a clicked PB's solves, or
+    // else the selected solve, make up the selection
+    fn export_filter(&self) -> ExportFilter {
+        let mut selection = self.ui_state.highlighted_indices.clone();
+        if selection.is_empty() {
+            selection.extend(self.ui_state.selected_time_index);
+        }
+        ExportFilter {
+            from: self.ui_state.export_from.clone(),
+            to: self.ui_state.export_to.clone(),
+            selection,
+            scramble_type: self.scramble_type(),
+        }
+    }
 
-        self.custom_events.insert(self.new_custom_event_name.clone(), custom_event);
-        self.available_events.push(CubeEvent::Custom(self.new_custom_event_name.clone()));
+    // Exports solves to a file and describes the result
+    fn export_file(&self, path: &str, format: ExportFormat, scope: ExportScope) -> Result<String, String> {
+        let (contents, count) = self.export_contents(format, scope)?;
+        std::fs::write(path, contents).map_err(|e| format!("Could not write {}: {}", path, e))?;
+        Ok(format!("Exported {} solves to {}", count, path))
+    }
 
-        self.new_custom_event_name.clear();
-        self.new_custom_moves.clear();
+    // Builds the exported text and returns it with the number of solves it covers
+    fn export_contents(&self, format: ExportFormat, scope: ExportScope) -> Result<(String, usize), String> {
+        self.data.export_contents(&self.current_event, format, scope, &self.export_filter())
     }
 
     // Removes a custom event
     fn remove_custom_event(&mut self, name: &str) {
-        self.custom_events.remove(name);
-        self.available_events.retain(|event| {
-            if let CubeEvent::Custom(custom_name) = event {
-                custom_name != name
-            } else {
-                true
-            }
-        });
+        self.data.remove_custom_event(name);
         if let Some(CubeEvent::Custom(current_name)) = Some(self.current_event.clone()) {
             if current_name == name {
-                self.current_event = self.available_events[0].clone();
+                self.current_event = self.data.available_events[0].clone();
                 self.generate_new_scramble();
+                self.ensure_active_session();
+                self.calculate_statistics();
+                self.recalculate_personal_bests();
             }
         }
     }
 }
 fn main() -> Result<(), eframe::Error> {
+    let cli = <cli::Cli as clap::Parser>::parse();
+    if let Some(dir) = storage::app_dir() {
+        scramble::use_cache_dir(dir);
+    }
+    if let Some(command) = cli.command {
+        std::process::exit(cli::run(command));
+    }
+    if cli.tui {
+        if let Err(e) = tui::run() {
+            eprintln!("Terminal error: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([1400.0, 900.0])
@@ -1660,13 +2788,11 @@ impl eframe::App for CubeTimer {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         self.handle_timer_updates(ctx);
         self.handle_input(ctx);
+        #[cfg(feature = "stackmat-audio")]
+        self.handle_stackmat(ctx);
         self.setup_theme(ctx);
 
-        let now = Instant::now();
-        if now.duration_since(self.last_save_time) > Duration::from_secs(120) {
-            self.save_data();
-            self.last_save_time = now;
-        }
+        self.autosave(self.timer.now());
 
         self.render_times_panel(ctx);
 
@@ -1680,4 +2806,47 @@ impl eframe::App for CubeTimer {
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
         self.ui_state.show_exit_popup = true;
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! fixture {
+        ($version:literal, $name:literal) => {
+            include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/", $version, "/", $name))
+        };
+    }
+
+    #[test]
+    fn loads_version_0_settings() {
+        let theme: Theme = storage::parse("theme.json", fixture!("v0", "theme.json")).unwrap();
+        assert_eq!(theme.background, [10, 20, 30]);
+        assert_eq!(theme.font_size_timer, 96.0);
+
+        let ui_state: UIState = storage::parse("ui_state.json", fixture!("v0", "ui_state.json")).unwrap();
+        assert_eq!(ui_state.times_panel_width, 350.0);
+        assert!(!ui_state.is_first_launch);
+        assert!(!ui_state.show_all_sessions);
+    }
+
+    #[test]
+    fn loads_version_1_settings() {
+        let theme: Theme = storage::parse("theme.json", fixture!("v1", "theme.json")).unwrap();
+        assert_eq!(theme.accent_primary, [1, 2, 3]);
+
+        let ui_state: UIState = storage::parse("ui_state.json", fixture!("v1", "ui_state.json")).unwrap();
+        assert!(ui_state.show_all_sessions);
+    }
+
+    #[test]
+    fn loads_version_2_settings() {
+        let theme: Theme = storage::parse("theme.json", fixture!("v2", "theme.json")).unwrap();
+        assert_eq!(theme.color_scheme.cube[0], [255, 255, 0]);
+        assert_eq!(theme.color_scheme.pyraminx, ColorScheme::default().pyraminx);
+
+        let ui_state: UIState = storage::parse("ui_state.json", fixture!("v2", "ui_state.json")).unwrap();
+        assert!(ui_state.typing_mode);
+        let old: UIState = storage::parse("ui_state.json", fixture!("v1", "ui_state.json")).unwrap();
+        assert!(!old.typing_mode);
+    }
+}
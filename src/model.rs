@@ -0,0 +1,322 @@
+// Events, solve records and sessions shared by every front end
+use crate::scramble::ScrambleType;
+use crate::stats::StatColumn;
+use crate::trainer::AlgSet;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+// Defines cube solving events: standard, custom, or a trainer for a set of algorithm cases
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CubeEvent {
+    Standard(StandardEvent),
+    Custom(String),
+    Training(AlgSet),
+}
+
+// Standard cube events as per WCA regulations
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StandardEvent {
+    Cube3x3, Cube2x2, Cube4x4, Cube5x5, Cube6x6, Cube7x7,
+    Pyraminx, Megaminx, Skewb, Square1, Clock,
+    OneHanded, Blindfolded, FeetSolving,
+}
+
+impl StandardEvent {
+    // Every standard event in the order shown in the event selector
+    pub const ALL: [StandardEvent; 14] = [
+        StandardEvent::Cube3x3,
+        StandardEvent::Cube2x2,
+        StandardEvent::Cube4x4,
+        StandardEvent::Cube5x5,
+        StandardEvent::Cube6x6,
+        StandardEvent::Cube7x7,
+        StandardEvent::Pyraminx,
+        StandardEvent::Megaminx,
+        StandardEvent::Skewb,
+        StandardEvent::Square1,
+        StandardEvent::Clock,
+        StandardEvent::OneHanded,
+        StandardEvent::Blindfolded,
+        StandardEvent::FeetSolving,
+    ];
+}
+
+impl std::fmt::Display for StandardEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            StandardEvent::Cube3x3 => write!(f, "3x3x3"),
+            StandardEvent::Cube2x2 => write!(f, "2x2x2"),
+            StandardEvent::Cube4x4 => write!(f, "4x4x4"),
+            StandardEvent::Cube5x5 => write!(f, "5x5x5"),
+            StandardEvent::Cube6x6 => write!(f, "6x6x6"),
+            StandardEvent::Cube7x7 => write!(f, "7x7x7"),
+            StandardEvent::Pyraminx => write!(f, "Pyraminx"),
+            StandardEvent::Megaminx => write!(f, "Megaminx"),
+            StandardEvent::Skewb => write!(f, "Skewb"),
+            StandardEvent::Square1 => write!(f, "Square-1"),
+            StandardEvent::Clock => write!(f, "Clock"),
+            StandardEvent::OneHanded => write!(f, "3x3 OH"),
+            StandardEvent::Blindfolded => write!(f, "3x3 BLD"),
+            StandardEvent::FeetSolving => write!(f, "3x3 Feet"),
+        }
+    }
+}
+
+impl std::fmt::Display for CubeEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CubeEvent::Standard(event) => write!(f, "{}", event),
+            CubeEvent::Custom(name) => write!(f, "{}", name),
+            CubeEvent::Training(set) => write!(f, "{} Trainer", set),
+        }
+    }
+}
+
+// Stores a single solve record with associated metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeRecord {
+    pub time: Duration,          // Duration of the solve
+    pub event: CubeEvent,       // Event type (e.g., 3x3x3, Pyraminx)
+    pub scramble: String,       // Scramble used for the solve
+    pub timestamp: DateTime<Local>, // Time and date of the solve
+    pub penalty: Option<Penalty>,   // Any penalties applied (e.g., +2, DNF)
+    pub comment: String,        // User comments for the solve
+    pub session: u64,           // Id of the session the solve belongs to (0 = not yet assigned)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub splits: Vec<Duration>,  // Time of each phase of a split solve, empty otherwise
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub moves: Vec<TimedMove>,  // Turns a smart cube recorded during the solve, empty otherwise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case: Option<String>,   // Id of the trained case the scramble set up, for trainer solves
+    #[serde(default, skip_serializing_if = "ScrambleType::is_random_state")]
+    pub scramble_type: ScrambleType, // Part of the cube the scramble randomized; drill solves get statistics of their own
+}
+
+impl TimeRecord {
+    // A plain solve of an event, without penalty, comment, splits or session
+    pub fn new(event: CubeEvent, time: Duration, timestamp: DateTime<Local>) -> Self {
+        TimeRecord {
+            time,
+            event,
+            scramble: String::new(),
+            timestamp,
+            penalty: None,
+            comment: String::new(),
+            session: 0,
+            splits: Vec::new(),
+            moves: Vec::new(),
+            case: None,
+            scramble_type: ScrambleType::RandomState,
+        }
+    }
+
+    // Returns the result that counts for statistics (WCA regulation 9f8)
+    pub fn result(&self) -> SolveResult {
+        match self.penalty {
+            None => SolveResult::Time(self.time),
+            Some(Penalty::Plus2) => SolveResult::Time(self.time + Duration::from_secs(2)),
+            Some(Penalty::DNF) => SolveResult::DNF,
+        }
+    }
+}
+
+// A turn of a smart cube and when it happened
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimedMove {
+    pub turn: String, // Face turn in standard notation, e.g. R'
+    pub at: Duration, // Time since the start of the solve, or since the source started
+}
+
+// Represents penalties that can be applied to a solve
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum Penalty {
+    Plus2,  // +2 second penalty
+    DNF,    // Did Not Finish
+}
+
+// Result of a solve or an average once penalties are applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SolveResult {
+    Time(Duration), // Finished result, including any +2
+    DNF,            // Did Not Finish, ranks below every time
+}
+
+// A named group of solves within one event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub id: u64,                    // Unique session id
+    pub name: String,               // Display name
+    pub event: CubeEvent,           // Event the session belongs to
+    pub archived: bool,             // Archived sessions are hidden from the selector
+    pub created: DateTime<Local>,   // Time the session was created
+}
+
+// All sessions together with the active session of each event
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionData {
+    pub sessions: Vec<Session>,     // Every session, including archived ones
+    pub active: HashMap<String, u64>, // Active session id keyed by event name
+    pub next_id: u64,               // Id handed to the next new session
+}
+
+impl SessionData {
+    // Returns the session with the given id
+    pub fn get(&self, id: u64) -> Option<&Session> {
+        self.sessions.iter().find(|s| s.id == id)
+    }
+
+    // Returns the id of an event's active session, 0 if it has none
+    pub fn active_id(&self, event: &CubeEvent) -> u64 {
+        self.active.get(&event.to_string()).copied().unwrap_or(0)
+    }
+
+    // Creates a new session for an event and returns its id
+    pub fn create(&mut self, event: &CubeEvent, name: String) -> u64 {
+        self.next_id = self.next_id.max(1);
+        let id = self.next_id;
+        self.next_id += 1;
+        self.sessions.push(Session {
+            id,
+            name,
+            event: event.clone(),
+            archived: false,
+            created: Local::now(),
+        });
+        id
+    }
+
+    // Makes sure an event has a valid, selected session
+    pub fn ensure_active(&mut self, event: &CubeEvent) {
+        if self.get(self.active_id(event)).is_some_and(|s| s.event == *event) {
+            return;
+        }
+
+        let existing = self.sessions
+            .iter()
+            .find(|s| s.event == *event && !s.archived)
+            .map(|s| s.id);
+        let id = existing.unwrap_or_else(|| self.create(event, "Session 1".to_string()));
+        self.active.insert(event.to_string(), id);
+    }
+
+    // Moves records saved before sessions existed into their event's first session
+    pub fn assign_unsessioned(&mut self, records: &mut [TimeRecord]) {
+        for record in records.iter_mut().filter(|r| r.session == 0) {
+            let existing = self.sessions.iter().find(|s| s.event == record.event).map(|s| s.id);
+            let id = existing.unwrap_or_else(|| self.create(&record.event, "Session 1".to_string()));
+            self.active.entry(record.event.to_string()).or_insert(id);
+            record.session = id;
+        }
+    }
+
+    // Re-creates the sessions records refer to that no longer exist, e.g. after sessions.json
+    // was lost, so their solves stay visible
+    pub fn restore_missing(&mut self, records: &[TimeRecord]) {
+        for record in records.iter().filter(|r| r.session != 0) {
+            if self.get(record.session).is_some() {
+                continue;
+            }
+            self.sessions.push(Session {
+                id: record.session,
+                name: format!("Recovered {}", record.session),
+                event: record.event.clone(),
+                archived: false,
+                created: Local::now(),
+            });
+            self.next_id = self.next_id.max(record.session + 1);
+        }
+    }
+}
+
+// Per-event timer settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventSettings {
+    pub inspection: bool, // Whether solves start with a WCA inspection countdown
+    #[serde(default)]
+    pub stat_columns: Vec<StatColumn>, // Rolling statistics shown for the event, empty for the defaults
+    #[serde(default)]
+    pub phases: Vec<String>, // Names of the phases solves are split into, empty for unsplit solves
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cases: Vec<String>, // Ids of the cases a trainer picks from, empty for every case
+    #[serde(default)]
+    pub scramble_type: ScrambleType, // Part of the cube 3x3x3 scrambles randomize
+}
+
+impl EventSettings {
+    // Default settings for an event (BLD events skip inspection)
+    pub fn for_event(event: &CubeEvent) -> Self {
+        Self {
+            inspection: !matches!(event, CubeEvent::Standard(StandardEvent::Blindfolded)),
+            stat_columns: StatColumn::defaults_for(event),
+            phases: Vec::new(),
+            cases: Vec::new(),
+            scramble_type: ScrambleType::RandomState,
+        }
+    }
+
+    // Number of times the stop key is pressed to finish a solve
+    pub fn phase_count(&self) -> usize {
+        self.phases.len().max(1)
+    }
+}
+
+// Suffixes a custom event's moves take unless it lists others
+pub const DEFAULT_MODIFIERS: [&str; 3] = ["", "'", "2"];
+
+// Scramble length of custom events that do not set one
+pub const DEFAULT_SCRAMBLE_LENGTH: usize = 20;
+
+// Defines a custom event with user-specified parameters, written and shared as a definition
+// file (see scramble::parse_definition)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CustomEvent {
+    pub name: String,           // Name of the custom event
+    pub scramble_length: usize, // Length of the scramble
+    pub moves: Vec<String>,    // Available moves for scrambling
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub modifiers: HashMap<String, Vec<String>>, // Suffixes of the moves that do not take the default ones
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub weights: HashMap<String, u32>, // Relative chance of the moves not picked with weight 1
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub axes: Vec<Vec<String>>, // Groups of moves that turn around the same axis
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lines: Option<ScrambleLines>, // Megaminx-style lines the scramble is broken into
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub separator: Option<String>, // Written between every two moves, like Square-1's slash
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub prefix: Vec<String>, // Moves every scramble starts with
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suffix: Vec<String>, // Moves every scramble ends with
+}
+
+// Breaks a scramble into lines of `length` moves, each ending with one of `ends`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScrambleLines {
+    pub length: usize,
+    pub ends: Vec<String>,
+}
+
+impl CustomEvent {
+    // Suffixes a move can take
+    pub fn modifiers_of(&self, face: &str) -> Vec<&str> {
+        match self.modifiers.get(face) {
+            Some(modifiers) => modifiers.iter().map(String::as_str).collect(),
+            None => DEFAULT_MODIFIERS.to_vec(),
+        }
+    }
+
+    // Relative chance of a move being picked
+    pub fn weight_of(&self, face: &str) -> u32 {
+        self.weights.get(face).copied().unwrap_or(1)
+    }
+
+    // Checks an event read from a file the way its definition file would have been checked,
+    // since edited or older files skip `parse_definition`
+    pub fn validate(&self) -> Result<(), String> {
+        crate::scramble::parse_definition(&crate::scramble::format_definition(self))
+            .map(|_| ())
+            .map_err(|e| format!("{}: {}", self.name, e))
+    }
+}
@@ -0,0 +1,153 @@
+// Clock in WCA notation: "UR3+" raises the named pins and turns their dials, y2 flips the puzzle
+use super::{Polygon, Rgb};
+
+// Pins in UL, UR, DL, DR order with the dials each one connects on the side it is raised on
+const PIN_DIALS: [[usize; 4]; 4] = [[0, 1, 3, 4], [1, 2, 4, 5], [3, 4, 6, 7], [4, 5, 7, 8]];
+const PIN_CORNERS: [usize; 4] = [0, 2, 6, 8];
+// Pins raised by each pin setting
+const PIN_SETTINGS: [(&str, [bool; 4]); 9] = [
+    ("ALL", [true; 4]),
+    ("UR", [false, true, false, false]),
+    ("DR", [false, false, false, true]),
+    ("DL", [false, false, true, false]),
+    ("UL", [true, false, false, false]),
+    ("U", [true, true, false, false]),
+    ("R", [false, true, false, true]),
+    ("D", [false, false, true, true]),
+    ("L", [true, false, true, false]),
+];
+
+const DIAL_RADIUS: f32 = 0.4;
+const PIN_RADIUS: f32 = 0.09;
+const BACK_OFFSET: f32 = 3.6;
+
+// Dials are numbered row by row from the top left as seen from their side, 0 pointing to 12
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Clock {
+    front: [i32; 9],
+    back: [i32; 9],
+    pins: [bool; 4],    // Pins raised on the side facing the solver
+    flipped: bool,      // The back faces the solver
+}
+
+impl Clock {
+    pub fn apply(&mut self, scramble: &str) -> Result<(), String> {
+        let mut setting_pins = false;
+        for token in scramble.split_whitespace() {
+            if token == "y2" {
+                std::mem::swap(&mut self.front, &mut self.back);
+                self.pins = mirror(self.pins).map(|up| !up);
+                self.flipped = !self.flipped;
+                setting_pins = false;
+                continue;
+            }
+
+            let unknown = || format!("Unknown move \"{}\"", token);
+            let (name, pins) = PIN_SETTINGS.iter().find(|(name, _)| token.starts_with(name)).ok_or_else(unknown)?;
+            let amount = &token[name.len()..];
+            if amount.is_empty() {
+                // Trailing pin settings list the pins left up after the scramble
+                if !setting_pins {
+                    self.pins = [false; 4];
+                }
+                setting_pins = true;
+                self.pins.iter_mut().zip(pins).for_each(|(pin, &up)| *pin |= up);
+                continue;
+            }
+
+            let hours: i32 = match amount.split_at(amount.len() - 1) {
+                (hours, "+") => hours.parse().map_err(|_| unknown())?,
+                (hours, "-") => -hours.parse::<i32>().map_err(|_| unknown())?,
+                _ => return Err(unknown()),
+            };
+            self.turn(*pins, hours);
+            setting_pins = false;
+        }
+        Ok(())
+    }
+
+    // Turns the dials the raised pins connect, and on the back the corners geared to them
+    fn turn(&mut self, pins: [bool; 4], hours: i32) {
+        self.pins = pins;
+        let mut front = [false; 9];
+        for (pin, dials) in PIN_DIALS.iter().enumerate() {
+            if pins[pin] {
+                dials.iter().for_each(|&dial| front[dial] = true);
+            }
+        }
+        for (dial, turned) in front.iter().enumerate() {
+            if *turned {
+                self.front[dial] = (self.front[dial] + hours).rem_euclid(12);
+            }
+        }
+        // A corner dial turns the opposite way on the back, where left and right swap
+        for corner in PIN_CORNERS {
+            if front[corner] {
+                let back = corner + 2 - 2 * (corner % 3);
+                self.back[back] = (self.back[back] - hours).rem_euclid(12);
+            }
+        }
+    }
+
+    // Dial hours on the side facing the solver
+    pub fn front(&self) -> [i32; 9] {
+        self.front
+    }
+
+    // Dial hours on the side facing away, seen from that side
+    pub fn back(&self) -> [i32; 9] {
+        self.back
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.front == [0; 9] && self.back == [0; 9]
+    }
+
+    // Draws the side that faced the solver before the scramble on the left and the other side
+    // on the right. Colors are front dials, back dials, hands and raised pins
+    pub fn diagram(&self, colors: &[Rgb; 4]) -> Vec<Polygon> {
+        let (front, back, front_pins) = if self.flipped {
+            (&self.back, &self.front, mirror(self.pins).map(|up| !up))
+        } else {
+            (&self.front, &self.back, self.pins)
+        };
+        let back_pins = mirror(front_pins).map(|up| !up);
+
+        let mut polygons = Vec::new();
+        for (dials, pins, offset, color) in [(front, front_pins, 0.0, colors[0]), (back, back_pins, BACK_OFFSET, colors[1])] {
+            for (dial, &hours) in dials.iter().enumerate() {
+                let center = [offset + (dial % 3) as f32, (dial / 3) as f32];
+                polygons.push(Polygon { points: circle(center, DIAL_RADIUS), color });
+                let (sin, cos) = (hours as f32 * 30.0).to_radians().sin_cos();
+                let (x, y) = (center[0], center[1]);
+                polygons.push(Polygon {
+                    points: vec![
+                        [x + 0.85 * DIAL_RADIUS * sin, y - 0.85 * DIAL_RADIUS * cos],
+                        [x + 0.08 * cos, y + 0.08 * sin],
+                        [x - 0.08 * cos, y - 0.08 * sin],
+                    ],
+                    color: colors[2],
+                });
+            }
+            for (pin, up) in pins.iter().enumerate() {
+                let center = [offset + 0.5 + (pin % 2) as f32, 0.5 + (pin / 2) as f32];
+                polygons.push(Polygon { points: circle(center, PIN_RADIUS), color: if *up { colors[3] } else { color } });
+            }
+        }
+        polygons
+    }
+}
+
+// Pins as seen from the other side, where left and right swap
+fn mirror(pins: [bool; 4]) -> [bool; 4] {
+    [pins[1], pins[0], pins[3], pins[2]]
+}
+
+fn circle(center: [f32; 2], radius: f32) -> Vec<[f32; 2]> {
+    (0..24)
+        .map(|i| {
+            let (sin, cos) = (i as f32 * 15.0).to_radians().sin_cos();
+            [center[0] + radius * cos, center[1] + radius * sin]
+        })
+        .collect()
+}
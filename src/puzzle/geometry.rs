@@ -0,0 +1,244 @@
+// Puzzles whose stickers cover the faces of a convex polyhedron: cubes, Pyraminx, Skewb and Megaminx
+use super::{Polygon, Rgb};
+use std::f64::consts::TAU;
+
+pub type Vector = [f64; 3];
+pub type Point = [f64; 2];
+
+const EPSILON: f64 = 1e-9;
+
+fn add(a: Vector, b: Vector) -> Vector {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn sub(a: Vector, b: Vector) -> Vector {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn scale(a: Vector, k: f64) -> Vector {
+    [a[0] * k, a[1] * k, a[2] * k]
+}
+
+pub fn dot(a: Vector, b: Vector) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: Vector, b: Vector) -> Vector {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+pub fn normalize(a: Vector) -> Vector {
+    scale(a, 1.0 / dot(a, a).sqrt())
+}
+
+fn distance(a: Vector, b: Vector) -> f64 {
+    let d = sub(a, b);
+    dot(d, d).sqrt()
+}
+
+fn average(points: &[Vector]) -> Vector {
+    scale(points.iter().fold([0.0; 3], |sum, &p| add(sum, p)), 1.0 / points.len() as f64)
+}
+
+// Rotates `v` by `angle` radians clockwise, seen from outside, around the unit axis `a`
+fn rotate(v: Vector, a: Vector, angle: f64) -> Vector {
+    let (sin, cos) = angle.sin_cos();
+    add(add(scale(v, cos), scale(cross(a, v), -sin)), scale(a, dot(a, v) * (1.0 - cos)))
+}
+
+// Vertices of the regular polygon stickers are laid out on, counter-clockwise
+pub fn regular_polygon(sides: usize) -> Vec<Point> {
+    (0..sides)
+        .map(|i| {
+            let angle = TAU * i as f64 / sides as f64;
+            [angle.cos(), angle.sin()]
+        })
+        .collect()
+}
+
+// Maps a point of the plane spanned by `from` to the same affine combination of `to`
+fn affine<const N: usize>(p: Point, from: &[Point], to: &[[f64; N]]) -> [f64; N] {
+    let (e1, e2) = ([from[1][0] - from[0][0], from[1][1] - from[0][1]], [from[2][0] - from[0][0], from[2][1] - from[0][1]]);
+    let d = [p[0] - from[0][0], p[1] - from[0][1]];
+    let det = e1[0] * e2[1] - e1[1] * e2[0];
+    let s = (d[0] * e2[1] - d[1] * e2[0]) / det;
+    let t = (e1[0] * d[1] - e1[1] * d[0]) / det;
+    std::array::from_fn(|i| to[0][i] + s * (to[1][i] - to[0][i]) + t * (to[2][i] - to[0][i]))
+}
+
+// A turn of every sticker whose center lies in the slab from < center·axis < to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Turn {
+    pub axis: Vector, // Unit axis the turn is seen from
+    pub from: f64,
+    pub to: f64,
+    pub angle: f64,   // Radians clockwise, seen from the end of the axis
+}
+
+impl Turn {
+    // Turns the layers whose centers lie beyond `depth` along the axis
+    pub fn beyond(axis: Vector, depth: f64, angle: f64) -> Self {
+        Self { axis: normalize(axis), from: depth, to: f64::INFINITY, angle }
+    }
+}
+
+// Where a face goes in the net
+#[derive(Debug, Clone, Copy)]
+pub enum Placement {
+    Root { face: usize, up: Vector, at: Point }, // Centered at `at`, `up` pointing up
+    Attached { face: usize, to: usize },         // Unfolded across its edge with a placed face
+}
+
+#[derive(Debug, Clone)]
+struct Face {
+    normal: Vector,
+    vertices: Vec<Vector>, // Counter-clockwise seen from outside
+}
+
+#[derive(Debug, Clone)]
+struct Sticker {
+    face: usize,
+    outline: Vec<Point>, // Corners on the face's layout polygon
+    center: Vector,
+}
+
+// Faces and stickers of a puzzle, in the orientation scrambles are applied in
+#[derive(Debug, Clone)]
+pub struct Model {
+    faces: Vec<Face>,
+    stickers: Vec<Sticker>,
+}
+
+impl Model {
+    // Builds the polyhedron whose faces have the given unit normals at distance 1 from the center,
+    // cutting each face into the stickers `layout` returns for its regular polygon
+    pub fn new(normals: &[Vector], layout: &dyn Fn(&[Point]) -> Vec<Vec<Point>>) -> Self {
+        // Every vertex is where three face planes meet without lying outside any other face
+        let mut vertices: Vec<Vector> = Vec::new();
+        for i in 0..normals.len() {
+            for j in i + 1..normals.len() {
+                for k in j + 1..normals.len() {
+                    let (a, b, c) = (normals[i], normals[j], normals[k]);
+                    let det = dot(a, cross(b, c));
+                    if det.abs() < EPSILON {
+                        continue;
+                    }
+                    let vertex = scale(add(add(cross(b, c), cross(c, a)), cross(a, b)), 1.0 / det);
+                    let inside = normals.iter().all(|&n| dot(n, vertex) <= 1.0 + EPSILON);
+                    if inside && vertices.iter().all(|&v| distance(v, vertex) > EPSILON) {
+                        vertices.push(vertex);
+                    }
+                }
+            }
+        }
+
+        let faces: Vec<Face> = normals
+            .iter()
+            .map(|&normal| {
+                let mut corners: Vec<Vector> = vertices.iter().copied().filter(|&v| (dot(normal, v) - 1.0).abs() < EPSILON).collect();
+                let center = average(&corners);
+                let u = normalize(sub(corners[0], center));
+                let w = cross(normal, u);
+                let angle = |v: Vector| dot(sub(v, center), w).atan2(dot(sub(v, center), u));
+                corners.sort_by(|&a, &b| angle(a).total_cmp(&angle(b)));
+                Face { normal, vertices: corners }
+            })
+            .collect();
+
+        let mut stickers = Vec::new();
+        for (index, face) in faces.iter().enumerate() {
+            let polygon = regular_polygon(face.vertices.len());
+            for outline in layout(&polygon) {
+                let corners: Vec<Vector> = outline.iter().map(|&p| affine(p, &polygon, &face.vertices)).collect();
+                stickers.push(Sticker { face: index, outline, center: average(&corners) });
+            }
+        }
+        Self { faces, stickers }
+    }
+
+    // Face each sticker belongs to on the solved puzzle
+    pub fn solved(&self) -> Vec<usize> {
+        self.stickers.iter().map(|sticker| sticker.face).collect()
+    }
+
+    // Sticker slot each sticker moves to, None if the turn does not map the puzzle onto itself
+    pub fn permutation(&self, turn: &Turn) -> Option<Vec<usize>> {
+        self.stickers
+            .iter()
+            .enumerate()
+            .map(|(i, sticker)| {
+                let depth = dot(sticker.center, turn.axis);
+                if depth <= turn.from || depth >= turn.to {
+                    return Some(i);
+                }
+                let moved = rotate(sticker.center, turn.axis, turn.angle);
+                self.stickers.iter().position(|other| distance(other.center, moved) < 1e-6)
+            })
+            .collect()
+    }
+
+    // Unfolds the faces into a net, y pointing up, coloring sticker i with palette[colors[i]]
+    pub fn net(&self, placements: &[Placement], colors: &[usize], palette: &[Rgb]) -> Vec<Polygon> {
+        let mut placed: Vec<Option<Vec<Point>>> = vec![None; self.faces.len()];
+        for placement in placements {
+            match *placement {
+                Placement::Root { face, up, at } => {
+                    let face_data = &self.faces[face];
+                    let center = average(&face_data.vertices);
+                    let up = normalize(sub(up, scale(face_data.normal, dot(up, face_data.normal))));
+                    let right = cross(up, face_data.normal);
+                    placed[face] = Some(
+                        face_data
+                            .vertices
+                            .iter()
+                            .map(|&v| [at[0] + dot(sub(v, center), right), at[1] + dot(sub(v, center), up)])
+                            .collect(),
+                    );
+                }
+                Placement::Attached { face, to } => {
+                    placed[face] = placed[to].as_ref().and_then(|parent| self.unfold(face, to, parent));
+                }
+            }
+        }
+
+        self.stickers
+            .iter()
+            .zip(colors)
+            .filter_map(|(sticker, &color)| {
+                let corners = placed[sticker.face].as_ref()?;
+                let polygon = regular_polygon(corners.len());
+                let points = sticker
+                    .outline
+                    .iter()
+                    .map(|&p| {
+                        let [x, y] = affine(p, &polygon, corners);
+                        [x as f32, y as f32]
+                    })
+                    .collect();
+                Some(Polygon { points, color: palette[color] })
+            })
+            .collect()
+    }
+
+    // Places a face in the net next to an already placed neighbour sharing one of its edges
+    fn unfold(&self, face: usize, to: usize, parent: &[Point]) -> Option<Vec<Point>> {
+        let vertices = &self.faces[face].vertices;
+        let neighbour = &self.faces[to].vertices;
+        let find = |v: Vector| neighbour.iter().position(|&n| distance(n, v) < EPSILON);
+        let sides = vertices.len();
+
+        let (start, a, b) = (0..sides).find_map(|i| Some((i, find(vertices[i])?, find(vertices[(i + 1) % sides])?)))?;
+        let mut points = vec![[0.0; 2]; sides];
+        points[start] = parent[a];
+        points[(start + 1) % sides] = parent[b];
+
+        // Each edge of a regular polygon turns by its exterior angle from the one before
+        let (sin, cos) = (TAU / sides as f64).sin_cos();
+        for step in 2..sides {
+            let (p, q) = (points[(start + step - 2) % sides], points[(start + step - 1) % sides]);
+            let d = [q[0] - p[0], q[1] - p[1]];
+            points[(start + step) % sides] = [q[0] + d[0] * cos - d[1] * sin, q[1] + d[0] * sin + d[1] * cos];
+        }
+        Some(points)
+    }
+}
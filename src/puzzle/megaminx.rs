@@ -0,0 +1,97 @@
+// Megaminx in Pochmann notation: R++/R-- turn all but the L face, D++/D-- all but the U face
+// two fifths of a turn, and U/U' turn the U face a fifth
+use super::geometry::{Placement, Point, Turn, Vector};
+use std::f64::consts::TAU;
+
+// Size of the center sticker relative to its face
+const CENTER_SCALE: f64 = 0.45;
+
+// Face names in palette order: U, the five faces around it from F towards R, D, and
+// the five faces around D starting below F and R
+pub const FACES: [&str; 12] = ["U", "F", "R", "BR", "BL", "L", "D", "DR", "DBR", "B", "DBL", "DL"];
+
+// Two flowers: U with its neighbours, F at the bottom, and D with its neighbours, F at the top
+pub const NET: [Placement; 12] = [
+    Placement::Root { face: 0, up: [0.0, 0.0, -1.0], at: [0.0, 0.0] },
+    Placement::Attached { face: 1, to: 0 },
+    Placement::Attached { face: 2, to: 0 },
+    Placement::Attached { face: 3, to: 0 },
+    Placement::Attached { face: 4, to: 0 },
+    Placement::Attached { face: 5, to: 0 },
+    Placement::Root { face: 6, up: [0.0, 0.0, 1.0], at: [4.4, 0.0] },
+    Placement::Attached { face: 7, to: 6 },
+    Placement::Attached { face: 8, to: 6 },
+    Placement::Attached { face: 9, to: 6 },
+    Placement::Attached { face: 10, to: 6 },
+    Placement::Attached { face: 11, to: 6 },
+];
+
+// Face normals, the rings around U and D tilted by atan(1/2) from the horizontal
+pub fn normals() -> Vec<Vector> {
+    let ring = |azimuth: f64, up: f64| {
+        let (sin, cos) = azimuth.to_radians().sin_cos();
+        let horizontal = 2.0 / 5.0_f64.sqrt();
+        [horizontal * sin, up / 5.0_f64.sqrt(), horizontal * cos]
+    };
+    let mut normals = vec![[0.0, 1.0, 0.0]];
+    normals.extend((0..5).map(|i| ring(72.0 * i as f64, 1.0)));
+    normals.push([0.0, -1.0, 0.0]);
+    normals.extend((0..5).map(|i| ring(36.0 + 72.0 * i as f64, -1.0)));
+    normals
+}
+
+// Depth of the cut below each face: it passes through the corners of the neighbouring
+// faces' center stickers, whose faces meet this one at an angle with cosine 1/sqrt(5)
+fn layer_depth() -> f64 {
+    let neighbour = 1.0 / 5.0_f64.sqrt();
+    neighbour + CENTER_SCALE * (1.0 - neighbour)
+}
+
+// Cuts a pentagonal face into a center, five edge and five corner stickers
+pub fn stickers(polygon: &[Point]) -> Vec<Vec<Point>> {
+    let sub = |a: Point, b: Point| [a[0] - b[0], a[1] - b[1]];
+    let cross = |a: Point, b: Point| a[0] * b[1] - a[1] * b[0];
+    // Where the line through `from` along `direction` meets the line through a and b
+    let meet = |from: Point, direction: Point, a: Point, b: Point| {
+        let edge = sub(b, a);
+        let t = cross(sub(from, a), direction) / cross(edge, direction);
+        [a[0] + t * edge[0], a[1] + t * edge[1]]
+    };
+
+    let n = polygon.len();
+    let center: Vec<Point> = polygon.iter().map(|p| [p[0] * CENTER_SCALE, p[1] * CENTER_SCALE]).collect();
+    // Cuts parallel to the edges before and after each corner meet the edges after and before it
+    let (mut after, mut before) = (Vec::with_capacity(n), Vec::with_capacity(n));
+    for i in 0..n {
+        let (prev, corner, next) = (polygon[(i + n - 1) % n], polygon[i], polygon[(i + 1) % n]);
+        after.push(meet(center[i], sub(corner, prev), corner, next));
+        before.push(meet(center[i], sub(next, corner), prev, corner));
+    }
+
+    let mut stickers = vec![center.clone()];
+    for i in 0..n {
+        stickers.push(vec![polygon[i], after[i], center[i], before[i]]);
+        stickers.push(vec![after[i], before[(i + 1) % n], center[(i + 1) % n], center[i]]);
+    }
+    stickers
+}
+
+// Parses a move into a fifth of a turn and how many times to make it
+pub fn parse(token: &str) -> Option<(Turn, usize)> {
+    let normals = normals();
+    let depth = layer_depth();
+    let opposite = |face: usize| normals[face].map(|x| -x);
+    // R and D turn everything but the L and U faces, seen from the faces opposite them
+    let (turn, times) = match token {
+        "R++" => (Turn::beyond(opposite(5), -depth, TAU / 5.0), 2),
+        "R--" => (Turn::beyond(opposite(5), -depth, TAU / 5.0), 3),
+        "D++" => (Turn::beyond(opposite(0), -depth, TAU / 5.0), 2),
+        "D--" => (Turn::beyond(opposite(0), -depth, TAU / 5.0), 3),
+        _ => {
+            let (body, amount) = super::split_amount(token)?;
+            let face = FACES.iter().position(|&face| face == body)?;
+            (Turn::beyond(normals[face], depth, TAU / 5.0), amount.rem_euclid(5) as usize)
+        }
+    };
+    Some((turn, times))
+}
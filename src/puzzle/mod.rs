@@ -0,0 +1,345 @@
+// Facelet models of the WCA puzzles, used to draw the state a scramble leads to
+mod clock;
+mod geometry;
+mod megaminx;
+mod nxn;
+mod pyraminx;
+mod skewb;
+mod square1;
+
+pub use clock::Clock;
+pub use megaminx::FACES as MEGAMINX_FACES;
+pub use square1::SquareOne;
+
+use crate::{CubeEvent, StandardEvent};
+use geometry::{Model, Placement, Point, Turn};
+use serde::{Deserialize, Serialize};
+
+pub type Rgb = [u8; 3];
+
+// Sticker colors of each puzzle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColorScheme {
+    pub cube: [Rgb; 6],      // U R F D L B, also used by Skewb and Square-1
+    pub pyraminx: [Rgb; 4],  // F L R D
+    pub megaminx: [Rgb; 12], // In MEGAMINX_FACES order
+    pub clock: [Rgb; 4],     // Front dials, back dials, hands and raised pins
+}
+
+impl Default for ColorScheme {
+    fn default() -> Self {
+        let (white, red, green, yellow, orange, blue) =
+            ([255, 255, 255], [220, 38, 38], [22, 163, 74], [250, 204, 21], [249, 115, 22], [37, 99, 235]);
+        Self {
+            cube: [white, red, green, yellow, orange, blue],
+            pyraminx: [green, red, blue, yellow],
+            // Each face of the lower half in a lighter shade of the face opposite it
+            megaminx: [
+                white, green, red, blue, yellow, [147, 51, 234],
+                [156, 163, 175], [254, 240, 138], [244, 114, 182], [163, 230, 53], orange, [125, 211, 252],
+            ],
+            clock: [[224, 242, 254], [30, 64, 175], red, yellow],
+        }
+    }
+}
+
+// A filled convex polygon
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon {
+    pub points: Vec<[f32; 2]>,
+    pub color: Rgb,
+}
+
+// A puzzle state drawn in 2D, x to the right and y down from (0, 0) to (width, height)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagram {
+    pub width: f32,
+    pub height: f32,
+    pub polygons: Vec<Polygon>,
+}
+
+impl Diagram {
+    // Moves the polygons to start at the origin, all running clockwise on screen
+    pub(crate) fn new(mut polygons: Vec<Polygon>) -> Self {
+        let points = || polygons.iter().flat_map(|polygon| polygon.points.iter());
+        let (min_x, min_y) = points().fold((f32::MAX, f32::MAX), |(x, y), p| (x.min(p[0]), y.min(p[1])));
+        let (max_x, max_y) = points().fold((f32::MIN, f32::MIN), |(x, y), p| (x.max(p[0]), y.max(p[1])));
+
+        for polygon in &mut polygons {
+            polygon.points.iter_mut().for_each(|p| *p = [p[0] - min_x, p[1] - min_y]);
+            let n = polygon.points.len();
+            let area: f32 = (0..n)
+                .map(|i| {
+                    let (a, b) = (polygon.points[i], polygon.points[(i + 1) % n]);
+                    a[0] * b[1] - b[0] * a[1]
+                })
+                .sum();
+            if area < 0.0 {
+                polygon.points.reverse();
+            }
+        }
+        Self { width: (max_x - min_x).max(0.0), height: (max_y - min_y).max(0.0), polygons }
+    }
+}
+
+// Puzzles built from stickers on the faces of a polyhedron
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    Cube(usize),
+    Pyraminx,
+    Skewb,
+    Megaminx,
+}
+
+impl Shape {
+    fn model(self) -> Model {
+        match self {
+            Shape::Cube(size) => Model::new(&nxn::NORMALS, &|polygon: &[Point]| nxn::grid(polygon, size)),
+            Shape::Pyraminx => Model::new(&pyraminx::normals(), &pyraminx::triangles),
+            Shape::Skewb => Model::new(&nxn::NORMALS, &skewb::stickers),
+            Shape::Megaminx => Model::new(&megaminx::normals(), &megaminx::stickers),
+        }
+    }
+
+    fn parse(self, token: &str) -> Option<(Turn, usize)> {
+        match self {
+            Shape::Cube(size) => nxn::parse(token, size),
+            Shape::Pyraminx => pyraminx::parse(token),
+            Shape::Skewb => skewb::parse(token),
+            Shape::Megaminx => megaminx::parse(token),
+        }
+    }
+
+    fn net(self) -> &'static [Placement] {
+        match self {
+            Shape::Cube(_) | Shape::Skewb => &nxn::NET,
+            Shape::Pyraminx => &pyraminx::NET,
+            Shape::Megaminx => &megaminx::NET,
+        }
+    }
+
+    fn palette(self, scheme: &ColorScheme) -> &[Rgb] {
+        match self {
+            Shape::Cube(_) | Shape::Skewb => &scheme.cube,
+            Shape::Pyraminx => &scheme.pyraminx,
+            Shape::Megaminx => &scheme.megaminx,
+        }
+    }
+}
+
+// Cube, Pyraminx, Skewb or Megaminx state
+#[derive(Debug, Clone)]
+pub struct StickerPuzzle {
+    shape: Shape,
+    model: Model,
+    colors: Vec<usize>, // Face each sticker came from
+}
+
+impl StickerPuzzle {
+    fn new(shape: Shape) -> Self {
+        let model = shape.model();
+        Self { shape, colors: model.solved(), model }
+    }
+
+    pub fn apply(&mut self, scramble: &str) -> Result<(), String> {
+        // Scrambles repeat few distinct turns, so each permutation is worked out once
+        let mut permutations: Vec<(Turn, Vec<usize>)> = Vec::new();
+        for token in scramble.split_whitespace() {
+            let unknown = || format!("Unknown move \"{}\"", token);
+            let (turn, times) = self.shape.parse(token).ok_or_else(unknown)?;
+            let index = match permutations.iter().position(|(known, _)| *known == turn) {
+                Some(index) => index,
+                None => {
+                    permutations.push((turn, self.model.permutation(&turn).ok_or_else(unknown)?));
+                    permutations.len() - 1
+                }
+            };
+            let permutation = &permutations[index].1;
+            for _ in 0..times {
+                let mut colors = self.colors.clone();
+                for (from, &to) in permutation.iter().enumerate() {
+                    colors[to] = self.colors[from];
+                }
+                self.colors = colors;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.colors == self.model.solved()
+    }
+
+    pub fn diagram(&self, scheme: &ColorScheme) -> Vec<Polygon> {
+        let mut polygons = self.model.net(self.shape.net(), &self.colors, self.shape.palette(scheme));
+        // Nets are unfolded with y pointing up
+        for polygon in &mut polygons {
+            polygon.points.iter_mut().for_each(|p| p[1] = -p[1]);
+        }
+        polygons
+    }
+}
+
+// State of any WCA puzzle
+#[derive(Debug, Clone)]
+pub enum Puzzle {
+    Stickers(StickerPuzzle),
+    Square1(SquareOne),
+    Clock(Clock),
+}
+
+impl Puzzle {
+    // The solved puzzle of an event, None for custom events
+    pub fn solved(event: &CubeEvent) -> Option<Self> {
+        let stickers = |shape| Some(Puzzle::Stickers(StickerPuzzle::new(shape)));
+        match event {
+            CubeEvent::Standard(event) => match event {
+                StandardEvent::Cube3x3 | StandardEvent::OneHanded | StandardEvent::Blindfolded | StandardEvent::FeetSolving => {
+                    stickers(Shape::Cube(3))
+                }
+                StandardEvent::Cube2x2 => stickers(Shape::Cube(2)),
+                StandardEvent::Cube4x4 => stickers(Shape::Cube(4)),
+                StandardEvent::Cube5x5 => stickers(Shape::Cube(5)),
+                StandardEvent::Cube6x6 => stickers(Shape::Cube(6)),
+                StandardEvent::Cube7x7 => stickers(Shape::Cube(7)),
+                StandardEvent::Pyraminx => stickers(Shape::Pyraminx),
+                StandardEvent::Skewb => stickers(Shape::Skewb),
+                StandardEvent::Megaminx => stickers(Shape::Megaminx),
+                StandardEvent::Square1 => Some(Puzzle::Square1(SquareOne::default())),
+                StandardEvent::Clock => Some(Puzzle::Clock(Clock::default())),
+            },
+            CubeEvent::Custom(_) => None,
+            CubeEvent::Training(set) => stickers(Shape::Cube(set.size())),
+        }
+    }
+
+    // Applies a scramble, failing on the first move the puzzle does not have
+    pub fn apply(&mut self, scramble: &str) -> Result<(), String> {
+        match self {
+            Puzzle::Stickers(puzzle) => puzzle.apply(scramble),
+            Puzzle::Square1(puzzle) => puzzle.apply(scramble),
+            Puzzle::Clock(puzzle) => puzzle.apply(scramble),
+        }
+    }
+
+    pub fn is_solved(&self) -> bool {
+        match self {
+            Puzzle::Stickers(puzzle) => puzzle.is_solved(),
+            Puzzle::Square1(puzzle) => puzzle.is_solved(),
+            Puzzle::Clock(puzzle) => puzzle.is_solved(),
+        }
+    }
+
+    // Draws the puzzle as a 2D net
+    pub fn diagram(&self, scheme: &ColorScheme) -> Diagram {
+        Diagram::new(match self {
+            Puzzle::Stickers(puzzle) => puzzle.diagram(scheme),
+            Puzzle::Square1(puzzle) => puzzle.diagram(&scheme.cube),
+            Puzzle::Clock(puzzle) => puzzle.diagram(&scheme.clock),
+        })
+    }
+}
+
+// Draws the state a scramble leads to, None for custom events and scrambles that do not apply
+pub fn preview(event: &CubeEvent, scramble: &str, scheme: &ColorScheme) -> Option<Diagram> {
+    let mut puzzle = Puzzle::solved(event)?;
+    puzzle.apply(scramble).ok()?;
+    Some(puzzle.diagram(scheme))
+}
+
+// Splits the amount off a move: R2 turns twice, R' once back, R3' three times back
+fn split_amount(token: &str) -> Option<(&str, i32)> {
+    let (rest, inverted) = match token.strip_suffix('\'') {
+        Some(rest) => (rest, true),
+        None => (token, false),
+    };
+    let body = rest.trim_end_matches(|c: char| c.is_ascii_digit());
+    if body.is_empty() {
+        return None;
+    }
+    let times = match &rest[body.len()..] {
+        "" => 1,
+        digits => digits.parse().ok()?,
+    };
+    Some((body, if inverted { -times } else { times }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scrambled(event: &StandardEvent, scramble: &str) -> Puzzle {
+        let mut puzzle = Puzzle::solved(&CubeEvent::Standard(event.clone())).unwrap();
+        puzzle.apply(scramble).unwrap();
+        puzzle
+    }
+
+    #[test]
+    fn sequences_that_undo_themselves_leave_the_puzzle_solved() {
+        let cases = [
+            (StandardEvent::Cube3x3, "R U R' U' R U R' U' R U R' U' R U R' U' R U R' U' R U R' U'"),
+            (StandardEvent::Cube2x2, "R U2 F' F U2 R'"),
+            (StandardEvent::Cube5x5, "3Rw Uw' M x y2 z z' y2 x' M' Uw 3Rw'"),
+            (StandardEvent::Cube7x7, "3Fw2 3Fw2 r r'"),
+            (StandardEvent::Pyraminx, "U U U l' l b b' r' r"),
+            (StandardEvent::Skewb, "R U' U R'"),
+            (StandardEvent::Megaminx, "R++ R-- D++ D++ D++ D++ D++ U U U U U"),
+            (StandardEvent::Square1, "(1,0)/ / (-1,0)"),
+            (StandardEvent::Clock, "UR5+ UR5- y2 ALL6+ ALL6+"),
+        ];
+        for (event, scramble) in cases {
+            assert!(scrambled(&event, scramble).is_solved(), "{} {}", event, scramble);
+        }
+        for (event, scramble) in [(StandardEvent::Cube3x3, "R"), (StandardEvent::Megaminx, "R++"), (StandardEvent::Square1, "/")] {
+            assert!(!scrambled(&event, scramble).is_solved(), "{} {}", event, scramble);
+        }
+    }
+
+    #[test]
+    fn scrambles_move_the_stickers_they_should() {
+        // After R the right column of U shows F's color and F's shows D's, the middle column stays
+        let scheme = ColorScheme::default();
+        let diagram = scrambled(&StandardEvent::Cube3x3, "R").diagram(&scheme);
+        let top_left = |polygon: &Polygon| {
+            polygon.points.iter().fold([f32::MAX; 2], |min, p| [min[0].min(p[0]), min[1].min(p[1])])
+        };
+        let color_at = |x: f32, y: f32| {
+            diagram.polygons.iter().find(|polygon| {
+                let corner = top_left(polygon);
+                (corner[0] - x).abs() < 0.01 && (corner[1] - y).abs() < 0.01
+            })
+            .map(|polygon| polygon.color)
+        };
+        // Stickers are 2/3 wide, U spanning x 2..4 and y 0..2, F below it
+        let step = 2.0 / 3.0;
+        assert_eq!(color_at(2.0 + 2.0 * step, 0.0), Some(scheme.cube[2]));
+        assert_eq!(color_at(2.0 + step, 0.0), Some(scheme.cube[0]));
+        assert_eq!(color_at(2.0 + 2.0 * step, 2.0), Some(scheme.cube[3]));
+        assert_eq!(diagram.polygons.len(), 54);
+        assert!((diagram.width - 8.0).abs() < 1e-3 && (diagram.height - 6.0).abs() < 1e-3);
+
+        // A Pyraminx tip turn only moves the three tip stickers
+        let Puzzle::Stickers(pyraminx) = scrambled(&StandardEvent::Pyraminx, "u") else { panic!() };
+        let moved = pyraminx.colors.iter().zip(pyraminx.model.solved()).filter(|(a, b)| *a != b).count();
+        assert_eq!(moved, 3);
+
+        // The first UR turn moves the four dials around the pin, and a corner on the back
+        let Puzzle::Clock(clock) = scrambled(&StandardEvent::Clock, "UR3+ UR DL") else { panic!() };
+        assert_eq!(clock.front(), [0, 3, 3, 0, 3, 3, 0, 0, 0]);
+        assert_eq!(clock.back(), [9, 0, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn every_generated_scramble_can_be_previewed() {
+        for event in StandardEvent::ALL {
+            let event = CubeEvent::Standard(event);
+            let scramble = crate::scramble::generate(&event, &crate::EventSettings::for_event(&event), &Default::default());
+            let diagram = preview(&event, &scramble, &ColorScheme::default());
+            assert!(diagram.is_some_and(|diagram| !diagram.polygons.is_empty()), "{} {}", event, scramble);
+        }
+        assert!(preview(&CubeEvent::Custom("Relay".into()), "R", &ColorScheme::default()).is_none());
+        assert!(preview(&CubeEvent::Standard(StandardEvent::Square1), "(0,1)/", &ColorScheme::default()).is_none());
+        assert!(preview(&CubeEvent::Standard(StandardEvent::Cube3x3), "R Q", &ColorScheme::default()).is_none());
+    }
+}
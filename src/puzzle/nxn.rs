@@ -0,0 +1,78 @@
+// NxN cubes in WCA notation: outer turns (R), wide turns (Rw, 3Rw, r), slices (M, E, S) and rotations (x, y, z)
+use super::geometry::{Placement, Point, Turn, Vector};
+use std::f64::consts::FRAC_PI_2;
+
+const FACES: &str = "URFDLB";
+pub const NORMALS: [Vector; 6] = [
+    [0.0, 1.0, 0.0],
+    [1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0],
+    [0.0, -1.0, 0.0],
+    [-1.0, 0.0, 0.0],
+    [0.0, 0.0, -1.0],
+];
+
+// Cross-shaped net: U above F, L, F, R and B in a row, D below F
+pub const NET: [Placement; 6] = [
+    Placement::Root { face: 2, up: [0.0, 1.0, 0.0], at: [0.0, 0.0] },
+    Placement::Attached { face: 0, to: 2 },
+    Placement::Attached { face: 1, to: 2 },
+    Placement::Attached { face: 3, to: 2 },
+    Placement::Attached { face: 4, to: 2 },
+    Placement::Attached { face: 5, to: 1 },
+];
+
+// Cuts a square face into size x size stickers
+pub fn grid(polygon: &[Point], size: usize) -> Vec<Vec<Point>> {
+    let at = |col: usize, row: usize| {
+        let (a, b) = (col as f64 / size as f64, row as f64 / size as f64);
+        [0, 1].map(|i| polygon[0][i] + a * (polygon[1][i] - polygon[0][i]) + b * (polygon[3][i] - polygon[0][i]))
+    };
+    (0..size)
+        .flat_map(|row| (0..size).map(move |col| (row, col)))
+        .map(|(row, col)| vec![at(col, row), at(col + 1, row), at(col + 1, row + 1), at(col, row + 1)])
+        .collect()
+}
+
+// Parses a move into a quarter turn and how many times to make it
+pub fn parse(token: &str, size: usize) -> Option<(Turn, usize)> {
+    let (body, amount) = super::split_amount(token)?;
+    let layer = 2.0 / size as f64; // Thickness of a layer, the cube spanning -1..1
+    let (digits, rest) = body.split_at(body.find(|c: char| !c.is_ascii_digit())?);
+    let (letter, wide) = match rest.char_indices().nth(1) {
+        None => (rest, false),
+        Some((i, _)) if &rest[i..] == "w" => (&rest[..i], true),
+        Some(_) => return None,
+    };
+    let plain = digits.is_empty() && !wide;
+
+    let turn = if let Some(face) = FACES.find(letter) {
+        let depth = match (digits, wide) {
+            ("", false) => 1,
+            ("", true) => 2,
+            (digits, true) => digits.parse().ok()?,
+            _ => return None,
+        };
+        if depth == 0 || depth > size {
+            return None;
+        }
+        Turn::beyond(NORMALS[face], 1.0 - layer * depth as f64, FRAC_PI_2)
+    } else if let Some(face) = FACES.to_lowercase().find(letter)
+        && plain
+    {
+        Turn::beyond(NORMALS[face], 1.0 - layer * 2.0, FRAC_PI_2)
+    } else if let Some(axis) = "xyz".find(letter)
+        && plain
+    {
+        Turn::beyond(NORMALS[[1, 0, 2][axis]], -2.0, FRAC_PI_2)
+    } else if let Some(axis) = "MES".find(letter)
+        && plain
+        && !size.is_multiple_of(2)
+    {
+        // Slices follow L, D and F
+        Turn { axis: NORMALS[[4, 3, 2][axis]], from: -layer / 2.0, to: layer / 2.0, angle: FRAC_PI_2 }
+    } else {
+        return None;
+    };
+    Some((turn, amount.rem_euclid(4) as usize))
+}
@@ -0,0 +1,57 @@
+// Pyraminx in WCA notation: U, L, R and B turn two layers around a vertex, u, l, r and b only its tip
+use super::geometry::{Placement, Point, Turn, Vector};
+use std::f64::consts::TAU;
+
+const SQRT_2: f64 = std::f64::consts::SQRT_2;
+const SQRT_6: f64 = 2.449_489_742_783_178;
+
+// U, L, R and B vertex directions, with F facing the solver and D at the bottom
+const VERTICES: [Vector; 4] = [
+    [0.0, 1.0, 0.0],
+    [-SQRT_6 / 3.0, -1.0 / 3.0, SQRT_2 / 3.0],
+    [SQRT_6 / 3.0, -1.0 / 3.0, SQRT_2 / 3.0],
+    [0.0, -1.0 / 3.0, -2.0 * SQRT_2 / 3.0],
+];
+
+// F, L, R and D faces, each facing away from the vertex it does not touch
+pub fn normals() -> Vec<Vector> {
+    [3, 2, 1, 0].map(|vertex| VERTICES[vertex].map(|x| -x)).to_vec()
+}
+
+// F in the middle pointing up, L and R beside it and D below
+pub const NET: [Placement; 4] = [
+    Placement::Root { face: 0, up: [0.0, 1.0, 0.0], at: [0.0, 0.0] },
+    Placement::Attached { face: 1, to: 0 },
+    Placement::Attached { face: 2, to: 0 },
+    Placement::Attached { face: 3, to: 0 },
+];
+
+// Cuts a triangular face into nine stickers
+pub fn triangles(polygon: &[Point]) -> Vec<Vec<Point>> {
+    let at = |i: usize, j: usize| {
+        let (a, b) = (i as f64 / 3.0, j as f64 / 3.0);
+        [0, 1].map(|k| polygon[0][k] + a * (polygon[1][k] - polygon[0][k]) + b * (polygon[2][k] - polygon[0][k]))
+    };
+    let mut stickers = Vec::with_capacity(9);
+    for i in 0..3 {
+        for j in 0..3 - i {
+            stickers.push(vec![at(i, j), at(i + 1, j), at(i, j + 1)]);
+            if i + j < 2 {
+                stickers.push(vec![at(i + 1, j), at(i + 1, j + 1), at(i, j + 1)]);
+            }
+        }
+    }
+    stickers
+}
+
+// Parses a move into a third of a turn and how many times to make it
+pub fn parse(token: &str) -> Option<(Turn, usize)> {
+    let (body, amount) = super::split_amount(token)?;
+    // Vertices lie 3 from the center and faces 1, so the layers are cut at 5/3 and 1/3
+    let (vertex, depth) = match ("ULRB".find(body), "ulrb".find(body)) {
+        (Some(vertex), _) if body.len() == 1 => (vertex, 1.0 / 3.0),
+        (_, Some(vertex)) if body.len() == 1 => (vertex, 5.0 / 3.0),
+        _ => return None,
+    };
+    Some((Turn::beyond(VERTICES[vertex], depth, TAU / 3.0), amount.rem_euclid(3) as usize))
+}
@@ -0,0 +1,23 @@
+// Skewb in WCA notation: R, U, L and B turn half the puzzle around the DRB, ULB, DLF and DLB corners
+use super::geometry::{Point, Turn};
+use std::f64::consts::TAU;
+
+const CORNERS: [[f64; 3]; 4] = [[1.0, -1.0, -1.0], [-1.0, 1.0, -1.0], [-1.0, -1.0, 1.0], [-1.0, -1.0, -1.0]];
+
+// Cuts a square face into its center and four corner stickers
+pub fn stickers(polygon: &[Point]) -> Vec<Vec<Point>> {
+    let mid = |a: Point, b: Point| [(a[0] + b[0]) / 2.0, (a[1] + b[1]) / 2.0];
+    let edges: Vec<Point> = (0..4).map(|i| mid(polygon[i], polygon[(i + 1) % 4])).collect();
+    let mut stickers = vec![edges.clone()];
+    for i in 0..4 {
+        stickers.push(vec![edges[(i + 3) % 4], polygon[i], edges[i]]);
+    }
+    stickers
+}
+
+// Parses a move into a third of a turn and how many times to make it
+pub fn parse(token: &str) -> Option<(Turn, usize)> {
+    let (body, amount) = super::split_amount(token)?;
+    let corner = "RULB".find(body).filter(|_| body.len() == 1)?;
+    Some((Turn::beyond(CORNERS[corner], 0.0, TAU / 3.0), amount.rem_euclid(3) as usize))
+}
@@ -0,0 +1,139 @@
+// Square-1 in (x,y)/ notation, drawn from above and from below with the middle layer underneath
+use super::{Polygon, Rgb};
+use crate::scramble::square1::Square1;
+
+const OUTER: f32 = 1.035_276_2; // Distance to the middle of an edge's outer side, 1 / cos 15°
+const CORNER: f32 = std::f32::consts::SQRT_2;
+const BAND: f32 = 1.15; // Outer edge of the side stickers
+const BOTTOM_OFFSET: f32 = 3.6;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SquareOne {
+    layers: Square1,
+    middle_flipped: bool, // The right half of the middle layer is upside down
+}
+
+impl SquareOne {
+    // Applies turns such as "(1,0)/ (-3,3)/", failing on illegal slices
+    pub fn apply(&mut self, scramble: &str) -> Result<(), String> {
+        let text: String = scramble.chars().filter(|c| !c.is_whitespace()).collect();
+        let mut rest = text.as_str();
+        while let Some(c) = rest.chars().next() {
+            match c {
+                '(' => {
+                    let end = rest.find(')').ok_or("Unclosed \"(\"")?;
+                    let turn = &rest[1..end];
+                    let (top, bottom) = turn
+                        .split_once(',')
+                        .and_then(|(top, bottom)| Some((top.parse().ok()?, bottom.parse().ok()?)))
+                        .ok_or_else(|| format!("Unknown move \"({})\"", turn))?;
+                    self.layers.turn(top, bottom);
+                    rest = &rest[end + 1..];
+                }
+                '/' => {
+                    if !self.layers.can_slice() {
+                        return Err("A piece blocks the slice".to_string());
+                    }
+                    self.layers.slice();
+                    self.middle_flipped = !self.middle_flipped;
+                    rest = &rest[1..];
+                }
+                _ => return Err(format!("Unknown move \"{}\"", rest)),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_solved(&self) -> bool {
+        *self == Self::default()
+    }
+
+    // Draws the top layer seen from above with F at the bottom, the bottom layer seen from below
+    // with F at the top, and the middle layer below them. Colors are in U R F D L B order
+    pub fn diagram(&self, colors: &[Rgb; 6]) -> Vec<Polygon> {
+        let sides = home_sides();
+        let mut polygons = Vec::new();
+        for (layer, offset, flip, home) in [(&self.layers.top, 0.0, 1.0, 0..8), (&self.layers.bottom, BOTTOM_OFFSET, -1.0, 8..16)] {
+            let at = |radius: f32, degrees: f32| {
+                let (sin, cos) = degrees.to_radians().sin_cos();
+                [offset + radius * cos, flip * radius * sin]
+            };
+            for (slot, width) in pieces(layer) {
+                let piece = layer[slot];
+                let start = slot_angle(slot);
+                let cap = if (0..8).contains(&piece) { colors[0] } else { colors[3] };
+                // A piece turned into the other layer shows its sides in reverse order
+                let mut piece_sides = sides[usize::from(piece)].clone();
+                if !home.contains(&piece) {
+                    piece_sides.reverse();
+                }
+
+                let outline: Vec<(f32, f32)> = if width == 1 {
+                    vec![(OUTER, start), (OUTER, start + 30.0)]
+                } else {
+                    vec![(OUTER, start), (CORNER, start + 30.0), (OUTER, start + 60.0)]
+                };
+                let mut points = vec![at(0.0, 0.0)];
+                points.extend(outline.iter().map(|&(radius, angle)| at(radius, angle)));
+                polygons.push(Polygon { points, color: cap });
+
+                for (side, pair) in piece_sides.iter().zip(outline.windows(2)) {
+                    let [(r1, a1), (r2, a2)] = [pair[0], pair[1]];
+                    polygons.push(Polygon {
+                        points: vec![at(r1, a1), at(r1 * BAND, a1), at(r2 * BAND, a2), at(r2, a2)],
+                        color: colors[*side],
+                    });
+                }
+            }
+        }
+
+        let right = if self.middle_flipped { colors[5] } else { colors[2] };
+        let (top, bottom) = (CORNER * BAND + 0.2, CORNER * BAND + 0.5);
+        for (left, width, color) in [(-CORNER, CORNER, colors[2]), (0.0, CORNER, right)] {
+            polygons.push(Polygon {
+                points: vec![[left, top], [left + width, top], [left + width, bottom], [left, bottom]],
+                color,
+            });
+        }
+        polygons
+    }
+}
+
+// Angle of a slot's first side, seen from above with x to the right and F towards +y. The
+// slice runs along slot 0's first side, so slots 0 to 5 hold the right half
+fn slot_angle(slot: usize) -> f32 {
+    -75.0 + 30.0 * slot as f32
+}
+
+// First slot and width in slots of each piece in a layer
+fn pieces(layer: &[u8; 12]) -> Vec<(usize, usize)> {
+    let first = (0..12).find(|&i| layer[i] != layer[(i + 11) % 12]).unwrap_or(0);
+    let mut pieces = Vec::new();
+    let mut offset = 0;
+    while offset < 12 {
+        let slot = (first + offset) % 12;
+        let width = if offset + 1 < 12 && layer[(slot + 1) % 12] == layer[slot] { 2 } else { 1 };
+        pieces.push((slot, width));
+        offset += width;
+    }
+    pieces
+}
+
+// Faces the sides of each piece show on the solved puzzle, in slot order
+fn home_sides() -> Vec<Vec<usize>> {
+    // Side faces around the puzzle seen from above: R, F, L and B
+    let face = |degrees: f32| [1, 2, 4, 5][((degrees / 90.0).round() as i32).rem_euclid(4) as usize];
+    let solved = Square1::default();
+    let mut sides = vec![Vec::new(); 16];
+    for layer in [&solved.top, &solved.bottom] {
+        for (slot, width) in pieces(layer) {
+            let start = slot_angle(slot);
+            sides[usize::from(layer[slot])] = if width == 1 {
+                vec![face(start + 15.0)]
+            } else {
+                vec![face(start - 15.0), face(start + 75.0)]
+            };
+        }
+    }
+    sides
+}
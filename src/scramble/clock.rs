@@ -0,0 +1,62 @@
+// Random-state Clock scrambles in WCA pin/dial notation
+use rand::Rng;
+
+// Pin configurations turned on the front, then on the back after y2
+const FRONT_TURNS: [&str; 9] = ["UR", "DR", "DL", "UL", "U", "R", "D", "L", "ALL"];
+const BACK_TURNS: [&str; 5] = ["U", "R", "D", "L", "ALL"];
+
+// Formats a dial turn of -5..=6 hours, e.g. 3+ or 2-
+fn format_amount(amount: i32) -> String {
+    if amount < 0 {
+        format!("{}-", -amount)
+    } else {
+        format!("{}+", amount)
+    }
+}
+
+// Generates a Clock scramble such as "UR3+ DR2- ... y2 U1+ ... ALL4-"
+pub fn clock_scramble(rng: &mut impl Rng) -> String {
+    let mut scramble = Vec::with_capacity(FRONT_TURNS.len() + BACK_TURNS.len() + 1);
+
+    for pins in FRONT_TURNS {
+        scramble.push(format!("{}{}", pins, format_amount(rng.gen_range(-5..=6))));
+    }
+    scramble.push("y2".to_string());
+    for pins in BACK_TURNS {
+        scramble.push(format!("{}{}", pins, format_amount(rng.gen_range(-5..=6))));
+    }
+    scramble.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn scrambles_turn_every_pin_setting_once_with_a_dial_amount() {
+        let mut rng = StdRng::seed_from_u64(54);
+        let amounts: Vec<String> = (-5..=6).map(format_amount).collect();
+        for _ in 0..20 {
+            let scramble = clock_scramble(&mut rng);
+            let turns: Vec<&str> = scramble.split(' ').collect();
+            assert_eq!(turns.len(), FRONT_TURNS.len() + 1 + BACK_TURNS.len(), "{}", scramble);
+            assert_eq!(turns[FRONT_TURNS.len()], "y2", "{}", scramble);
+
+            let pins = FRONT_TURNS.iter().chain(&BACK_TURNS);
+            let dials = turns[..FRONT_TURNS.len()].iter().chain(&turns[FRONT_TURNS.len() + 1..]);
+            for (pins, turn) in pins.zip(dials) {
+                let amount = turn.strip_prefix(pins).unwrap_or_else(|| panic!("{} in {}", turn, scramble));
+                assert!(amounts.iter().any(|a| a == amount), "{} in {}", turn, scramble);
+            }
+        }
+    }
+
+    #[test]
+    fn dial_amounts_read_as_hours_forward_or_back() {
+        assert_eq!(format_amount(6), "6+");
+        assert_eq!(format_amount(0), "0+");
+        assert_eq!(format_amount(-5), "5-");
+    }
+}
@@ -0,0 +1,231 @@
+// Cubie-level model of the 3x3x3 cube used by the random-state scrambler
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+// Face names in solver order, a move index is face * 3 + (quarter turns - 1)
+pub const FACE_NAMES: [&str; 6] = ["U", "R", "F", "D", "L", "B"];
+pub const MOVE_COUNT: usize = 18;
+
+// A cube state as permutation and orientation of corners and edges
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CubieCube {
+    pub cp: [u8; 8],  // Corner permutation (URF, UFL, ULB, UBR, DFR, DLF, DBL, DRB)
+    pub co: [u8; 8],  // Corner orientation (0-2)
+    pub ep: [u8; 12], // Edge permutation (UR, UF, UL, UB, DR, DF, DL, DB, FR, FL, BL, BR)
+    pub eo: [u8; 12], // Edge orientation (0-1)
+}
+
+// Clockwise quarter turns of the six faces, in FACE_NAMES order
+const BASIC_MOVES: [CubieCube; 6] = [
+    CubieCube {
+        cp: [3, 0, 1, 2, 4, 5, 6, 7],
+        co: [0, 0, 0, 0, 0, 0, 0, 0],
+        ep: [3, 0, 1, 2, 4, 5, 6, 7, 8, 9, 10, 11],
+        eo: [0; 12],
+    },
+    CubieCube {
+        cp: [4, 1, 2, 0, 7, 5, 6, 3],
+        co: [2, 0, 0, 1, 1, 0, 0, 2],
+        ep: [8, 1, 2, 3, 11, 5, 6, 7, 4, 9, 10, 0],
+        eo: [0; 12],
+    },
+    CubieCube {
+        cp: [1, 5, 2, 3, 0, 4, 6, 7],
+        co: [1, 2, 0, 0, 2, 1, 0, 0],
+        ep: [0, 9, 2, 3, 4, 8, 6, 7, 1, 5, 10, 11],
+        eo: [0, 1, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0],
+    },
+    CubieCube {
+        cp: [0, 1, 2, 3, 5, 6, 7, 4],
+        co: [0, 0, 0, 0, 0, 0, 0, 0],
+        ep: [0, 1, 2, 3, 5, 6, 7, 4, 8, 9, 10, 11],
+        eo: [0; 12],
+    },
+    CubieCube {
+        cp: [0, 2, 6, 3, 4, 1, 5, 7],
+        co: [0, 1, 2, 0, 0, 2, 1, 0],
+        ep: [0, 1, 10, 3, 4, 5, 9, 7, 8, 2, 6, 11],
+        eo: [0; 12],
+    },
+    CubieCube {
+        cp: [0, 1, 3, 7, 4, 5, 2, 6],
+        co: [0, 0, 1, 2, 0, 0, 2, 1],
+        ep: [0, 1, 2, 11, 4, 5, 6, 10, 8, 9, 3, 7],
+        eo: [0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 1, 1],
+    },
+];
+
+impl Default for CubieCube {
+    fn default() -> Self {
+        Self {
+            cp: [0, 1, 2, 3, 4, 5, 6, 7],
+            co: [0; 8],
+            ep: [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            eo: [0; 12],
+        }
+    }
+}
+
+impl CubieCube {
+    // Generates a uniformly random solvable cube state
+    pub fn random(rng: &mut impl Rng) -> Self {
+        let mut cube = Self::default();
+        cube.cp.shuffle(rng);
+        cube.ep.shuffle(rng);
+        if permutation_parity(&cube.cp) != permutation_parity(&cube.ep) {
+            cube.ep.swap(0, 1);
+        }
+
+        let mut twist = 0;
+        for i in 0..7 {
+            cube.co[i] = rng.gen_range(0..3);
+            twist += cube.co[i];
+        }
+        cube.co[7] = (3 - twist % 3) % 3;
+
+        let mut flip = 0;
+        for i in 0..11 {
+            cube.eo[i] = rng.gen_range(0..2);
+            flip += cube.eo[i];
+        }
+        cube.eo[11] = flip % 2;
+        cube
+    }
+
+    // Replaces this state with the state followed by `other`
+    pub fn multiply(&mut self, other: &CubieCube) {
+        let mut result = *self;
+        for i in 0..8 {
+            let from = other.cp[i] as usize;
+            result.cp[i] = self.cp[from];
+            result.co[i] = (self.co[from] + other.co[i]) % 3;
+        }
+        for i in 0..12 {
+            let from = other.ep[i] as usize;
+            result.ep[i] = self.ep[from];
+            result.eo[i] = (self.eo[from] + other.eo[i]) % 2;
+        }
+        *self = result;
+    }
+
+    // Applies a single move given by its index
+    pub fn apply_move(&mut self, mv: usize) {
+        let basic = &BASIC_MOVES[mv / 3];
+        for _ in 0..=mv % 3 {
+            self.multiply(basic);
+        }
+    }
+
+    // Applies a face-turn sequence such as a 3x3x3 scramble, None if it has other moves
+    pub fn from_moves(moves: &str) -> Option<Self> {
+        let mut cube = Self::default();
+        for mv in moves.split_whitespace() {
+            cube.apply_move(parse_move(mv)?);
+        }
+        Some(cube)
+    }
+
+    // Returns true if every piece is in place and oriented
+    pub fn is_solved(&self) -> bool {
+        *self == Self::default()
+    }
+
+    // Corner orientation coordinate (0..2187)
+    pub fn twist(&self) -> usize {
+        self.co[..7].iter().fold(0, |acc, &o| acc * 3 + o as usize)
+    }
+
+    // Edge orientation coordinate (0..2048)
+    pub fn flip(&self) -> usize {
+        self.eo[..11].iter().fold(0, |acc, &o| acc * 2 + o as usize)
+    }
+
+    // Position of the four E-slice edges, ignoring their order (0..495)
+    pub fn slice(&self) -> usize {
+        let mut index = 0;
+        let mut found = 0;
+        for j in (0..12).rev() {
+            if self.ep[j] >= 8 {
+                found += 1;
+                index += binomial(11 - j, found);
+            }
+        }
+        index
+    }
+
+    // Corner permutation coordinate (0..40320)
+    pub fn corner_perm(&self) -> usize {
+        permutation_rank(&self.cp)
+    }
+
+    // Permutation of the eight U and D layer edges, valid in phase 2 (0..40320)
+    pub fn ud_edge_perm(&self) -> usize {
+        permutation_rank(&self.ep[..8])
+    }
+
+    // Permutation of the four E-slice edges, valid in phase 2 (0..24)
+    pub fn slice_perm(&self) -> usize {
+        let slice: Vec<u8> = self.ep[8..].iter().map(|&e| e - 8).collect();
+        permutation_rank(&slice)
+    }
+}
+
+// Returns true if the permutation is odd
+pub fn permutation_parity(perm: &[u8]) -> bool {
+    let mut odd = false;
+    for i in 0..perm.len() {
+        for j in i + 1..perm.len() {
+            if perm[i] > perm[j] {
+                odd = !odd;
+            }
+        }
+    }
+    odd
+}
+
+// Ranks a permutation of 0..n in lexicographic order
+fn permutation_rank(perm: &[u8]) -> usize {
+    let mut rank = 0;
+    for i in 0..perm.len() {
+        let smaller = perm[i + 1..].iter().filter(|&&p| p < perm[i]).count();
+        rank = rank * (perm.len() - i) + smaller;
+    }
+    rank
+}
+
+// Binomial coefficient, zero when k > n
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    let mut result = 1;
+    for i in 0..k {
+        result = result * (n - i) / (i + 1);
+    }
+    result
+}
+
+// Formats a move index in standard notation
+pub fn format_move(mv: usize) -> String {
+    let suffix = ["", "2", "'"][mv % 3];
+    format!("{}{}", FACE_NAMES[mv / 3], suffix)
+}
+
+// Returns the move that undoes the given one
+pub fn inverse_move(mv: usize) -> usize {
+    mv / 3 * 3 + (2 - mv % 3)
+}
+
+// Parses a face turn in standard notation into its move index
+pub fn parse_move(text: &str) -> Option<usize> {
+    let mut chars = text.chars();
+    let first = chars.next()?;
+    let face = FACE_NAMES.iter().position(|name| name.starts_with(first))?;
+    let turns = match chars.as_str() {
+        "" => 0,
+        "2" | "2'" => 1,
+        "'" => 2,
+        _ => return None,
+    };
+    Some(face * 3 + turns)
+}
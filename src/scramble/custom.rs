@@ -0,0 +1,152 @@
+// Random-move scrambles for custom events, built from the event's own moves and suffixes
+use crate::CustomEvent;
+use rand::Rng;
+
+// Generates a scramble that never turns a move again while only moves on its axis came since
+// it was last turned, so neither "R R" nor "R L R" can appear when R and L share an axis
+pub fn custom_scramble(rng: &mut impl Rng, event: &CustomEvent) -> String {
+    let turns = random_moves(rng, event);
+    let separator = event.separator.as_ref().map_or(" ".to_string(), |separator| format!(" {} ", separator));
+
+    let mut lines: Vec<String> = match &event.lines {
+        Some(lines) => turns
+            .chunks(lines.length.max(1))
+            .map(|chunk| match lines.ends.len() {
+                0 => chunk.join(&separator),
+                ends => format!("{} {}", chunk.join(&separator), lines.ends[rng.gen_range(0..ends)]),
+            })
+            .collect(),
+        None => vec![turns.join(&separator)],
+    };
+    if let Some(first) = lines.first_mut()
+        && !event.prefix.is_empty()
+    {
+        *first = format!("{} {}", event.prefix.join(" "), first).trim_end().to_string();
+    }
+    if let Some(last) = lines.last_mut()
+        && !event.suffix.is_empty()
+    {
+        *last = format!("{} {}", last, event.suffix.join(" ")).trim_start().to_string();
+    }
+    lines.join("\n")
+}
+
+// Picks the random part of the scramble, weighting each move by the event's weights
+fn random_moves(rng: &mut impl Rng, event: &CustomEvent) -> Vec<String> {
+    if event.moves.is_empty() {
+        return Vec::new();
+    }
+    // Moves outside every axis group get an axis of their own
+    let axis_of = |face: &String| {
+        event
+            .axes
+            .iter()
+            .position(|group| group.contains(face))
+            .unwrap_or(event.axes.len() + event.moves.iter().position(|known| known == face).unwrap_or(0))
+    };
+    let axes: Vec<usize> = event.moves.iter().map(axis_of).collect();
+    let weights: Vec<u64> = event.moves.iter().map(|face| u64::from(event.weight_of(face).max(1))).collect();
+
+    let mut turns = Vec::with_capacity(event.scramble_length);
+    let mut run: Vec<usize> = Vec::new(); // Moves turned since the axis last changed
+    for _ in 0..event.scramble_length {
+        let mut allowed: Vec<usize> = (0..event.moves.len()).filter(|m| !run.contains(m)).collect();
+        // An event whose moves all share one axis can only avoid repeating the last move
+        if allowed.is_empty() {
+            allowed = (0..event.moves.len()).filter(|m| run.last() != Some(m)).collect();
+            run.clear();
+        }
+        if allowed.is_empty() {
+            allowed = vec![0];
+        }
+
+        let mut pick = rng.gen_range(0..allowed.iter().map(|&m| weights[m]).sum::<u64>());
+        let m = *allowed
+            .iter()
+            .find(|&&m| {
+                let hit = pick < weights[m];
+                pick = pick.saturating_sub(weights[m]);
+                hit
+            })
+            .unwrap_or(&allowed[0]);
+        if run.first().is_some_and(|&first| axes[first] != axes[m]) {
+            run.clear();
+        }
+        run.push(m);
+
+        let face = &event.moves[m];
+        let modifiers = event.modifiers_of(face);
+        let modifier = if modifiers.is_empty() { "" } else { modifiers[rng.gen_range(0..modifiers.len())] };
+        turns.push(format!("{}{}", face, modifier));
+    }
+    turns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scramble::parse_definition;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn scrambles_use_the_event_moves_and_avoid_redundant_sequences() {
+        let event = parse_definition("name Domino\nlength 200\nmoves R[2], L[2], U, D, F[2]\naxes R L; U D").unwrap();
+        let mut rng = StdRng::seed_from_u64(21);
+        let scramble = custom_scramble(&mut rng, &event);
+        let turns: Vec<&str> = scramble.split_whitespace().collect();
+        assert_eq!(turns.len(), 200);
+
+        let face = |turn: &str| turn.trim_end_matches(['\'', '2']).to_string();
+        for turn in &turns {
+            assert!(event.modifiers_of(&face(turn)).iter().any(|modifier| format!("{}{}", face(turn), modifier) == *turn), "{}", turn);
+        }
+        let axis = |turn: &str| event.axes.iter().position(|group| group.contains(&face(turn)));
+        for window in turns.windows(2) {
+            assert_ne!(face(window[0]), face(window[1]), "{}", scramble);
+        }
+        for window in turns.windows(3) {
+            let same_axis = axis(window[0]).is_some() && axis(window[0]) == axis(window[1]) && axis(window[1]) == axis(window[2]);
+            assert!(!same_axis, "{}", scramble);
+        }
+
+        // A single move can only repeat, and no moves give an empty scramble
+        let single = parse_definition("name Spin\nlength 3\nmoves y[/']").unwrap();
+        assert_eq!(custom_scramble(&mut rng, &single).split_whitespace().count(), 3);
+        assert_eq!(custom_scramble(&mut rng, &CustomEvent::default()), "");
+    }
+
+    #[test]
+    fn scrambles_follow_the_event_structure_and_weights() {
+        let megaminx = parse_definition("name Mega\nlength 20\nmoves R[++/--], D[++/--]\nlines 10 U U'\nprefix x2\nsuffix y").unwrap();
+        let mut rng = StdRng::seed_from_u64(22);
+        let scramble = custom_scramble(&mut rng, &megaminx);
+        let lines: Vec<Vec<&str>> = scramble.lines().map(|line| line.split_whitespace().collect()).collect();
+        assert_eq!(lines.len(), 2, "{}", scramble);
+        assert_eq!(lines[0][0], "x2");
+        assert!(matches!(lines[0][11], "U" | "U'") && lines[0].len() == 12, "{}", scramble);
+        assert!(matches!(lines[1][10], "U" | "U'") && lines[1][11] == "y" && lines[1].len() == 12, "{}", scramble);
+
+        let square1 = parse_definition("name Sq\nlength 5\nmoves (1,0)[], (0,3)[], (-2,1)[]\nseparator /").unwrap();
+        let tokens: Vec<String> = custom_scramble(&mut rng, &square1).split_whitespace().map(str::to_string).collect();
+        assert_eq!(tokens.len(), 9);
+        assert!(tokens.iter().skip(1).step_by(2).all(|token| token == "/"), "{:?}", tokens);
+
+        // Without an axis rule to get in the way, a move weighted 9 is picked about nine times as often
+        let weighted = parse_definition("name Weighted\nlength 1\nmoves A[]*9, B[]").unwrap();
+        let picked_a = (0..1000).filter(|_| custom_scramble(&mut rng, &weighted) == "A").count();
+        assert!((850..950).contains(&picked_a), "{}", picked_a);
+    }
+
+    #[test]
+    fn broken_definitions_from_files_still_scramble() {
+        // Files skip the checks of parse_definition, so the generator cannot rely on them
+        let text = r#"{"name":"Broken","scramble_length":12,"moves":["A","B"],"weights":{"A":0,"B":4294967295},"lines":{"length":0,"ends":[]}}"#;
+        let event: CustomEvent = serde_json::from_str(text).unwrap();
+        assert!(event.validate().is_err());
+        let mut rng = StdRng::seed_from_u64(23);
+        let scramble = custom_scramble(&mut rng, &event);
+        assert_eq!(scramble.lines().count(), 12, "{}", scramble);
+        assert!(scramble.lines().all(|line| matches!(line, "A" | "B" | "A'" | "B'" | "A2" | "B2")), "{}", scramble);
+    }
+}
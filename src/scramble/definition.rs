@@ -0,0 +1,241 @@
+// Definition files for custom events: one directive per line, # starting a comment
+//
+//     name Floppy
+//     length 12
+//     moves R[/'/2]*2, U[++/--], F
+//     axes R L; U D
+//     lines 10 U U'
+//     separator /
+//     prefix z2
+//     suffix x
+//
+// Moves list their suffixes between brackets, separated by /, and how often they are picked
+// after a *. Moves in an axis group are never repeated while only moves of their group came
+// between. `lines` ends every 10 moves with U or U' and a line break.
+use crate::model::{CustomEvent, DEFAULT_SCRAMBLE_LENGTH, ScrambleLines};
+
+// Highest weight a move can have, which keeps the weights of all moves adding up safely
+pub const MAX_WEIGHT: u32 = 1000;
+
+// Parses a definition, naming the line of the first error
+pub fn parse_definition(text: &str) -> Result<CustomEvent, String> {
+    let mut event = CustomEvent::default();
+    let mut length = None;
+    let mut seen: Vec<&str> = Vec::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (directive, value) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let value = value.trim();
+        let error = |message: String| format!("Line {}: {}", number + 1, message);
+
+        // Moves and axis groups can be spread over several lines
+        if !matches!(directive, "moves" | "axes") {
+            if seen.contains(&directive) {
+                return Err(error(format!("\"{}\" is set twice", directive)));
+            }
+            seen.push(directive);
+        }
+        if value.is_empty() {
+            return Err(error(format!("\"{}\" needs a value", directive)));
+        }
+
+        match directive {
+            "name" => event.name = value.to_string(),
+            "length" => length = Some(value.parse().map_err(|_| error(format!("\"{}\" is not a scramble length", value)))?),
+            "moves" => parse_moves(&mut event, value).map_err(error)?,
+            "axes" => parse_axes(&mut event, value).map_err(error)?,
+            "lines" => {
+                let mut words = value.split_whitespace();
+                let length = words
+                    .next()
+                    .and_then(|word| word.parse().ok())
+                    .filter(|&length| length > 0)
+                    .ok_or_else(|| error("\"lines\" needs the number of moves per line first".to_string()))?;
+                let ends: Vec<String> = words.map(str::to_string).collect();
+                if ends.is_empty() {
+                    return Err(error("\"lines\" needs at least one move to end each line with".to_string()));
+                }
+                event.lines = Some(ScrambleLines { length, ends });
+            }
+            "separator" => event.separator = Some(value.to_string()),
+            "prefix" => event.prefix = value.split_whitespace().map(str::to_string).collect(),
+            "suffix" => event.suffix = value.split_whitespace().map(str::to_string).collect(),
+            _ => return Err(error(format!("Unknown directive \"{}\"", directive))),
+        }
+    }
+
+    if event.name.is_empty() {
+        return Err("The definition needs a name".to_string());
+    }
+    if event.moves.is_empty() {
+        return Err("The definition needs at least one move".to_string());
+    }
+    // Axis groups may name moves listed after them
+    if let Some(face) = event.axes.iter().flatten().find(|face| !event.moves.contains(face)) {
+        return Err(format!("Axis group move {} is not one of the moves", face));
+    }
+    event.scramble_length = length.unwrap_or(DEFAULT_SCRAMBLE_LENGTH);
+    Ok(event)
+}
+
+// Writes an event as a definition `parse_definition` reads back
+pub fn format_definition(event: &CustomEvent) -> String {
+    let mut lines = vec![format!("name {}", event.name), format!("length {}", event.scramble_length)];
+    let moves: Vec<String> = event
+        .moves
+        .iter()
+        .map(|face| {
+            let mut entry = face.clone();
+            if let Some(modifiers) = event.modifiers.get(face) {
+                entry.push_str(&format!("[{}]", modifiers.join("/")));
+            }
+            if event.weight_of(face) != 1 {
+                entry.push_str(&format!("*{}", event.weight_of(face)));
+            }
+            entry
+        })
+        .collect();
+    lines.push(format!("moves {}", moves.join(", ")));
+    if !event.axes.is_empty() {
+        let groups: Vec<String> = event.axes.iter().map(|group| group.join(" ")).collect();
+        lines.push(format!("axes {}", groups.join("; ")));
+    }
+    if let Some(scramble_lines) = &event.lines {
+        lines.push(format!("lines {} {}", scramble_lines.length, scramble_lines.ends.join(" ")));
+    }
+    if let Some(separator) = &event.separator {
+        lines.push(format!("separator {}", separator));
+    }
+    if !event.prefix.is_empty() {
+        lines.push(format!("prefix {}", event.prefix.join(" ")));
+    }
+    if !event.suffix.is_empty() {
+        lines.push(format!("suffix {}", event.suffix.join(" ")));
+    }
+    lines.join("\n") + "\n"
+}
+
+// Adds moves such as "R[/'/2]*2, (1,0)", splitting on the commas outside brackets
+fn parse_moves(event: &mut CustomEvent, text: &str) -> Result<(), String> {
+    let mut entries = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                entries.push(&text[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    entries.push(&text[start..]);
+
+    for entry in entries.into_iter().map(str::trim).filter(|entry| !entry.is_empty()) {
+        let (rest, weight) = match entry.rsplit_once('*') {
+            Some((rest, weight)) => {
+                let weight: u32 = weight
+                    .trim()
+                    .parse()
+                    .ok()
+                    .filter(|weight| (1..=MAX_WEIGHT).contains(weight))
+                    .ok_or_else(|| format!("\"{}\" is not a weight from 1 to {}", weight.trim(), MAX_WEIGHT))?;
+                (rest.trim(), weight)
+            }
+            None => (entry, 1),
+        };
+        let (face, modifiers) = match rest.split_once('[') {
+            Some((face, list)) => {
+                let list = list.strip_suffix(']').ok_or_else(|| format!("Unclosed \"[\" in \"{}\"", entry))?;
+                (face.trim(), Some(list.split('/').map(|modifier| modifier.trim().to_string()).collect::<Vec<_>>()))
+            }
+            None => (rest, None),
+        };
+        if face.is_empty() || face.contains(char::is_whitespace) || face.contains(']') {
+            return Err(format!("\"{}\" is not a move", entry));
+        }
+        if event.moves.iter().any(|known| known == face) {
+            return Err(format!("{} is listed twice", face));
+        }
+
+        event.moves.push(face.to_string());
+        if let Some(modifiers) = modifiers {
+            event.modifiers.insert(face.to_string(), modifiers);
+        }
+        if weight != 1 {
+            event.weights.insert(face.to_string(), weight);
+        }
+    }
+    Ok(())
+}
+
+// Adds axis groups such as "R L; U D"
+fn parse_axes(event: &mut CustomEvent, text: &str) -> Result<(), String> {
+    for group in text.split(';').map(str::trim).filter(|group| !group.is_empty()) {
+        let group: Vec<String> = group.split_whitespace().map(str::to_string).collect();
+        for face in &group {
+            if event.axes.iter().flatten().chain(&group).filter(|other| *other == face).count() > 1 {
+                return Err(format!("{} is in more than one axis group", face));
+            }
+        }
+        event.axes.push(group);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn definitions_round_trip_through_the_text_format() {
+        let floppy = parse_definition(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/custom_events/floppy.txt"))).unwrap();
+        assert_eq!(floppy.name, "Floppy");
+        assert_eq!(floppy.scramble_length, 12);
+        assert_eq!(floppy.moves, ["R", "L", "U", "D"]);
+        assert_eq!(floppy.modifiers_of("U"), ["2"]);
+        assert_eq!(floppy.weight_of("R"), 2);
+        assert_eq!(floppy.weight_of("U"), 1);
+        assert_eq!(floppy.axes, [["R", "L"], ["U", "D"]]);
+        assert_eq!(floppy.prefix, ["z2"]);
+
+        let text = "name Mega\nlength 70\nmoves R[++/--], D[++/--], U[/']*3\nlines 10 U U'\nseparator /\nsuffix x y\n";
+        let mega = parse_definition(text).unwrap();
+        assert_eq!(format_definition(&mega), text);
+        assert_eq!(parse_definition(&format_definition(&floppy)).unwrap(), floppy);
+
+        // Moves default to the usual suffixes and scramble length
+        let plain = parse_definition("name Plain\nmoves (1,0), R").unwrap();
+        assert_eq!(plain.moves, ["(1,0)", "R"]);
+        assert_eq!(plain.modifiers_of("R"), ["", "'", "2"]);
+        assert_eq!(plain.scramble_length, DEFAULT_SCRAMBLE_LENGTH);
+    }
+
+    #[test]
+    fn invalid_definitions_say_what_is_wrong() {
+        let cases = [
+            ("name A\nlenght 5\nmoves R", "Line 2: Unknown directive \"lenght\""),
+            ("name A\nlength five\nmoves R", "Line 2: \"five\" is not a scramble length"),
+            ("name A\nname B\nmoves R", "Line 2: \"name\" is set twice"),
+            ("name A\n\nmoves R, R", "Line 3: R is listed twice"),
+            ("name A\nmoves R[', U", "Line 2: Unclosed \"[\" in \"R[', U\""),
+            ("name A\nmoves R*0", "Line 2: \"0\" is not a weight from 1 to 1000"),
+            ("name A\nmoves A*4294967295, B*2", "Line 2: \"4294967295\" is not a weight from 1 to 1000"),
+            ("name A\nmoves A*2147483648, B*2147483648", "Line 2: \"2147483648\" is not a weight from 1 to 1000"),
+            ("name A\nmoves R, L\naxes R L; L", "Line 3: L is in more than one axis group"),
+            ("name A\nmoves R\naxes R L", "Axis group move L is not one of the moves"),
+            ("name A\nmoves R\nlines 10", "Line 3: \"lines\" needs at least one move to end each line with"),
+            ("name A\nmoves R\nprefix", "Line 3: \"prefix\" needs a value"),
+            ("# No name\nmoves R", "The definition needs a name"),
+            ("name A", "The definition needs at least one move"),
+        ];
+        for (text, error) in cases {
+            assert_eq!(parse_definition(text), Err(error.to_string()), "{}", text);
+        }
+    }
+}
@@ -0,0 +1,50 @@
+// Pochmann-style Megaminx scrambles as used by the WCA
+use rand::Rng;
+
+const LINES: usize = 7;
+const MOVES_PER_LINE: usize = 10;
+
+// Generates seven lines of R++/D-- moves, each ending with U or U'
+pub fn megaminx_scramble(rng: &mut impl Rng) -> String {
+    let mut lines = Vec::with_capacity(LINES);
+
+    for _ in 0..LINES {
+        let mut line = Vec::with_capacity(MOVES_PER_LINE + 1);
+        let mut last_clockwise = false;
+        for i in 0..MOVES_PER_LINE {
+            let face = if i % 2 == 0 { "R" } else { "D" };
+            last_clockwise = rng.gen_bool(0.5);
+            line.push(format!("{}{}", face, if last_clockwise { "++" } else { "--" }));
+        }
+        // The closing U turn follows the direction of the last D move
+        line.push(if last_clockwise { "U" } else { "U'" }.to_string());
+        lines.push(line.join(" "));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn scrambles_are_seven_lines_of_alternating_r_and_d_moves() {
+        let mut rng = StdRng::seed_from_u64(53);
+        for _ in 0..20 {
+            let scramble = megaminx_scramble(&mut rng);
+            let lines: Vec<Vec<&str>> = scramble.lines().map(|line| line.split(' ').collect()).collect();
+            assert_eq!(lines.len(), LINES, "{}", scramble);
+            for line in lines {
+                assert_eq!(line.len(), MOVES_PER_LINE + 1, "{:?}", line);
+                for (i, mv) in line[..MOVES_PER_LINE].iter().enumerate() {
+                    let face = if i % 2 == 0 { "R" } else { "D" };
+                    assert!(*mv == format!("{}++", face) || *mv == format!("{}--", face), "{:?}", line);
+                }
+                let expected_u = if line[MOVES_PER_LINE - 1] == "D++" { "U" } else { "U'" };
+                assert_eq!(line[MOVES_PER_LINE], expected_u, "{:?}", line);
+            }
+        }
+    }
+}
@@ -0,0 +1,168 @@
+// Scramble generators for the standard and custom events
+mod clock;
+pub(crate) mod cube;
+mod custom;
+mod definition;
+mod megaminx;
+mod nxn;
+mod partial;
+mod set;
+mod skewb;
+pub(crate) mod square1;
+mod two_phase;
+
+pub use clock::clock_scramble;
+pub use custom::custom_scramble;
+pub use definition::{format_definition, parse_definition};
+pub use megaminx::megaminx_scramble;
+pub use nxn::big_cube_scramble;
+pub use partial::{ScrambleType, partial_scramble};
+pub use set::{ScrambleSet, SetScramble};
+pub use skewb::skewb_scramble;
+pub use square1::square1_scramble;
+pub use two_phase::use_cache_dir;
+
+use crate::{CubeEvent, CustomEvent, EventSettings, StandardEvent, trainer};
+use cube::CubieCube;
+use rand::Rng;
+use std::collections::HashMap;
+
+// Generates a scramble for the given event with its settings' scramble type and cases,
+// looking custom events up by name
+pub fn generate(event: &CubeEvent, settings: &EventSettings, custom_events: &HashMap<String, CustomEvent>) -> String {
+    generate_with(&mut rand::thread_rng(), event, settings, custom_events)
+}
+
+// Generates a scramble like `generate`, along with the trainer case it sets up
+pub fn generate_case(event: &CubeEvent, settings: &EventSettings, custom_events: &HashMap<String, CustomEvent>) -> SetScramble {
+    generate_case_with(&mut rand::thread_rng(), event, settings, custom_events)
+}
+
+// Part of the cube an event's scrambles randomize; only the 3x3x3 events can leave some of it solved
+pub fn scramble_type(event: &CubeEvent, settings: &EventSettings) -> ScrambleType {
+    match event {
+        CubeEvent::Standard(StandardEvent::Cube3x3 | StandardEvent::OneHanded | StandardEvent::FeetSolving) => {
+            settings.scramble_type
+        }
+        _ => ScrambleType::RandomState,
+    }
+}
+
+// Generates a scramble from the given random numbers, so a seeded generator always gives the
+// same scramble
+pub fn generate_with(
+    rng: &mut impl Rng,
+    event: &CubeEvent,
+    settings: &EventSettings,
+    custom_events: &HashMap<String, CustomEvent>,
+) -> String {
+    match event {
+        CubeEvent::Standard(StandardEvent::Cube3x3)
+        | CubeEvent::Standard(StandardEvent::OneHanded)
+        | CubeEvent::Standard(StandardEvent::FeetSolving) => {
+            partial_scramble(rng, settings.scramble_type)
+        },
+        CubeEvent::Standard(StandardEvent::Blindfolded) => {
+            blindfolded_3x3(rng)
+        },
+        CubeEvent::Standard(StandardEvent::Cube2x2) => {
+            face_turn_scramble(rng, &["R", "U", "F"], 9)
+        },
+        CubeEvent::Standard(StandardEvent::Cube4x4) => big_cube_scramble(rng, 4, 40),
+        CubeEvent::Standard(StandardEvent::Cube5x5) => big_cube_scramble(rng, 5, 60),
+        CubeEvent::Standard(StandardEvent::Cube6x6) => big_cube_scramble(rng, 6, 80),
+        CubeEvent::Standard(StandardEvent::Cube7x7) => big_cube_scramble(rng, 7, 100),
+        CubeEvent::Standard(StandardEvent::Pyraminx) => {
+            pyraminx_scramble(rng)
+        },
+        CubeEvent::Standard(StandardEvent::Megaminx) => megaminx_scramble(rng),
+        CubeEvent::Standard(StandardEvent::Skewb) => skewb_scramble(rng),
+        CubeEvent::Standard(StandardEvent::Square1) => square1_scramble(rng),
+        CubeEvent::Standard(StandardEvent::Clock) => clock_scramble(rng),
+        CubeEvent::Custom(name) => custom_events
+            .get(name)
+            .map(|custom| custom_scramble(rng, custom))
+            .unwrap_or_default(),
+        CubeEvent::Training(_) => generate_case_with(rng, event, settings, custom_events).scramble,
+    }
+}
+
+// Generates a scramble like `generate_with`, picking a trainer event's case from its settings
+pub fn generate_case_with(
+    rng: &mut impl Rng,
+    event: &CubeEvent,
+    settings: &EventSettings,
+    custom_events: &HashMap<String, CustomEvent>,
+) -> SetScramble {
+    match event {
+        CubeEvent::Training(set) => {
+            let case = trainer::pick_case(rng, *set, &settings.cases);
+            SetScramble { scramble: trainer::scramble(rng, case), case: Some(case.id.clone()) }
+        }
+        _ => SetScramble { scramble: generate_with(rng, event, settings, custom_events), case: None },
+    }
+}
+
+// Generates a cube scramble with specified moves and length, never turning the same face twice in a row
+fn face_turn_scramble(rng: &mut impl Rng, moves: &[&str], length: usize) -> String {
+    let modifiers = ["", "'", "2"];
+    let mut scramble = Vec::new();
+    let mut last_move = None;
+
+    while scramble.len() < length {
+        let move_idx = rng.gen_range(0..moves.len());
+        if last_move == Some(move_idx) {
+            continue;
+        }
+        let mod_idx = rng.gen_range(0..modifiers.len());
+        scramble.push(format!("{}{}", moves[move_idx], modifiers[mod_idx]));
+        last_move = Some(move_idx);
+    }
+    scramble.join(" ")
+}
+
+// Generates a Pyraminx scramble
+fn pyraminx_scramble(rng: &mut impl Rng) -> String {
+    let moves = ["R", "U", "L", "B"];
+    let modifiers = ["", "'"];
+    let mut scramble = Vec::new();
+
+    for _ in 0..10 {
+        let move_idx = rng.gen_range(0..moves.len());
+        let mod_idx = rng.gen_range(0..modifiers.len());
+        scramble.push(format!("{}{}", moves[move_idx], modifiers[mod_idx]));
+    }
+    scramble.join(" ")
+}
+
+// Generates a WCA-style random-state 3x3x3 scramble
+pub fn random_state_3x3(rng: &mut impl Rng) -> String {
+    state_scramble(&CubieCube::random(rng))
+}
+
+// Generates a scramble that leads to the given state
+pub(crate) fn state_scramble(state: &CubieCube) -> String {
+    let solution = two_phase::solve(state);
+
+    // Undoing the solution from a solved cube reaches the state
+    solution
+        .iter()
+        .rev()
+        .map(|&mv| cube::format_move(cube::inverse_move(mv)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Generates a random-state 3x3x3 scramble followed by wide moves that randomize orientation
+pub fn blindfolded_3x3(rng: &mut impl Rng) -> String {
+    let mut scramble = random_state_3x3(rng);
+    let first = ["", "Rw", "Rw2", "Rw'", "Fw", "Fw'"][rng.gen_range(0..6)];
+    let second = ["", "Uw", "Uw2", "Uw'"][rng.gen_range(0..4)];
+    for rotation in [first, second] {
+        if !rotation.is_empty() {
+            scramble.push(' ');
+            scramble.push_str(rotation);
+        }
+    }
+    scramble
+}
@@ -0,0 +1,98 @@
+// Random-move scrambles for 4x4x4 through 7x7x7 using WCA wide-move notation
+use rand::Rng;
+
+const FACES: [&str; 6] = ["U", "R", "F", "D", "L", "B"];
+const SUFFIXES: [&str; 3] = ["", "'", "2"];
+
+// Formats a turn of `depth` layers from `face`, e.g. R, Rw, 3Rw
+fn format_turn(face: usize, depth: usize) -> String {
+    match depth {
+        1 => FACES[face].to_string(),
+        2 => format!("{}w", FACES[face]),
+        _ => format!("{}{}w", depth, FACES[face]),
+    }
+}
+
+// Generates a scramble for an NxN cube with the given number of moves
+pub fn big_cube_scramble(rng: &mut impl Rng, size: usize, length: usize) -> String {
+    let max_depth = size / 2;
+    let mut scramble = Vec::with_capacity(length);
+    let mut axis_moves: Vec<(usize, usize)> = Vec::new(); // Turns made since the axis last changed
+
+    while scramble.len() < length {
+        let face = rng.gen_range(0..FACES.len());
+        let depth = rng.gen_range(1..=max_depth);
+
+        // On even cubes a half-cube turn from D, L or B equals one from U, R or F plus a rotation
+        if size.is_multiple_of(2) && depth == max_depth && face >= 3 {
+            continue;
+        }
+
+        // Turns on one axis commute, so never repeat the same layer block within a run
+        let same_axis = axis_moves.first().is_some_and(|&(f, _)| f % 3 == face % 3);
+        if same_axis && axis_moves.contains(&(face, depth)) {
+            continue;
+        }
+        if !same_axis {
+            axis_moves.clear();
+        }
+        axis_moves.push((face, depth));
+
+        let suffix = SUFFIXES[rng.gen_range(0..SUFFIXES.len())];
+        scramble.push(format!("{}{}", format_turn(face, depth), suffix));
+    }
+    scramble.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    // Splits a turn such as 3Rw' into its face, depth and suffix
+    fn parse_turn(turn: &str) -> (usize, usize, &str) {
+        let (depth, rest) = match turn.chars().next() {
+            Some(digit) if digit.is_ascii_digit() => (digit.to_digit(10).unwrap() as usize, &turn[1..]),
+            _ => (0, turn),
+        };
+        let face = FACES.iter().position(|face| rest.starts_with(face)).unwrap_or_else(|| panic!("{}", turn));
+        let rest = &rest[1..];
+        let (depth, suffix) = match rest.strip_prefix('w') {
+            Some(suffix) => (if depth == 0 { 2 } else { depth }, suffix),
+            None => (1, rest),
+        };
+        assert!(SUFFIXES.contains(&suffix), "{}", turn);
+        (face, depth, suffix)
+    }
+
+    #[test]
+    fn scrambles_use_wide_moves_the_cube_has_without_redundant_turns() {
+        let mut rng = StdRng::seed_from_u64(55);
+        for (size, length) in [(4, 40), (5, 60), (6, 80), (7, 100)] {
+            let scramble = big_cube_scramble(&mut rng, size, length);
+            let turns: Vec<(usize, usize, &str)> = scramble.split(' ').map(parse_turn).collect();
+            assert_eq!(turns.len(), length, "{}", scramble);
+
+            let mut run: Vec<(usize, usize)> = Vec::new();
+            for &(face, depth, _) in &turns {
+                assert!((1..=size / 2).contains(&depth), "{}x{}: {}", size, size, scramble);
+                if size % 2 == 0 && depth == size / 2 {
+                    assert!(face < 3, "{}x{}: {}", size, size, scramble);
+                }
+                if run.first().is_some_and(|&(f, _)| f % 3 != face % 3) {
+                    run.clear();
+                }
+                assert!(!run.contains(&(face, depth)), "{}x{}: {}", size, size, scramble);
+                run.push((face, depth));
+            }
+        }
+    }
+
+    #[test]
+    fn turns_are_written_in_wca_notation() {
+        assert_eq!(format_turn(1, 1), "R");
+        assert_eq!(format_turn(0, 2), "Uw");
+        assert_eq!(format_turn(5, 3), "3Bw");
+    }
+}
@@ -0,0 +1,197 @@
+// Random-state 3x3x3 scrambles that leave part of the cube solved, for drilling one step
+use super::cube::{CubieCube, permutation_parity};
+use super::state_scramble;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+// Positions of the pieces each type keeps solved (see the cubie order in cube.rs)
+const CROSS_EDGES: [usize; 4] = [4, 5, 6, 7];
+const F2L_CORNERS: [usize; 4] = [4, 5, 6, 7];
+const F2L_EDGES: [usize; 8] = [4, 5, 6, 7, 8, 9, 10, 11];
+const FIRST_BLOCK_CORNERS: [usize; 2] = [5, 6];
+const FIRST_BLOCK_EDGES: [usize; 3] = [6, 9, 10];
+const LSE_EDGES: [usize; 6] = [0, 1, 2, 3, 5, 7];
+
+// Turns of R and U an <R,U> scramble state is mixed with
+const RU_TURNS: usize = 200;
+
+// Which part of the cube a 3x3x3 scramble randomizes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ScrambleType {
+    #[default]
+    RandomState,
+    CrossSolved,     // Everything but the cross, for F2L
+    LastLayer,       // Only the last layer, with F2L solved
+    TwoGen,          // Any state <R,U> reaches, for one-handed practice
+    TwoGll,          // Last layers with edges oriented and corners permuted
+    RouxSecondBlock, // Everything but the Roux first block
+    Lse,             // The six edges left after CMLL
+}
+
+impl ScrambleType {
+    pub const ALL: [ScrambleType; 7] = [
+        ScrambleType::RandomState,
+        ScrambleType::CrossSolved,
+        ScrambleType::LastLayer,
+        ScrambleType::TwoGen,
+        ScrambleType::TwoGll,
+        ScrambleType::RouxSecondBlock,
+        ScrambleType::Lse,
+    ];
+
+    // Whether the whole cube is scrambled, as in competition
+    pub fn is_random_state(&self) -> bool {
+        *self == ScrambleType::RandomState
+    }
+}
+
+impl std::fmt::Display for ScrambleType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ScrambleType::RandomState => write!(f, "Random state"),
+            ScrambleType::CrossSolved => write!(f, "Cross solved (F2L)"),
+            ScrambleType::LastLayer => write!(f, "Last layer"),
+            ScrambleType::TwoGen => write!(f, "<R,U> only"),
+            ScrambleType::TwoGll => write!(f, "2GLL"),
+            ScrambleType::RouxSecondBlock => write!(f, "Roux second block"),
+            ScrambleType::Lse => write!(f, "Roux LSE"),
+        }
+    }
+}
+
+// Generates a scramble of the given type, each state of the type being equally likely except
+// for <R,U>, which mixes the cube with random turns
+pub fn partial_scramble(rng: &mut impl Rng, scramble_type: ScrambleType) -> String {
+    state_scramble(&partial_state(rng, scramble_type))
+}
+
+// A random state of the given type
+fn partial_state(rng: &mut impl Rng, scramble_type: ScrambleType) -> CubieCube {
+    match scramble_type {
+        ScrambleType::RandomState => CubieCube::random(rng),
+        ScrambleType::CrossSolved => random_keeping(rng, &[], &CROSS_EDGES),
+        ScrambleType::LastLayer => random_keeping(rng, &F2L_CORNERS, &F2L_EDGES),
+        ScrambleType::RouxSecondBlock => random_keeping(rng, &FIRST_BLOCK_CORNERS, &FIRST_BLOCK_EDGES),
+        ScrambleType::TwoGen => {
+            let mut cube = CubieCube::default();
+            for turn in 0..RU_TURNS {
+                // R is face 1 and U face 0, each with three ways to turn
+                cube.apply_move((turn % 2) * 3 + rng.gen_range(0..3));
+            }
+            cube
+        }
+        ScrambleType::TwoGll => {
+            let mut cube = random_auf(rng);
+            shuffle_at(rng, &mut cube.ep, &[0, 1, 2, 3]);
+            twist_at(rng, &mut cube.co, &[0, 1, 2, 3], 3);
+            fix_parity(&mut cube, &[0, 1]);
+            cube
+        }
+        ScrambleType::Lse => {
+            // <M,U> reaches every even permutation and even flip of these edges
+            let mut cube = random_auf(rng);
+            shuffle_at(rng, &mut cube.ep, &LSE_EDGES);
+            twist_at(rng, &mut cube.eo, &LSE_EDGES, 2);
+            fix_parity(&mut cube, &[5, 7]);
+            cube
+        }
+    }
+}
+
+// A random state with the corners and edges at the given positions solved
+fn random_keeping(rng: &mut impl Rng, corners: &[usize], edges: &[usize]) -> CubieCube {
+    let free_corners: Vec<usize> = (0..8).filter(|i| !corners.contains(i)).collect();
+    let free_edges: Vec<usize> = (0..12).filter(|i| !edges.contains(i)).collect();
+    let mut cube = CubieCube::default();
+    shuffle_at(rng, &mut cube.cp, &free_corners);
+    shuffle_at(rng, &mut cube.ep, &free_edges);
+    twist_at(rng, &mut cube.co, &free_corners, 3);
+    twist_at(rng, &mut cube.eo, &free_edges, 2);
+    fix_parity(&mut cube, &free_edges[..2]);
+    cube
+}
+
+// A solved cube with a random U layer turn
+fn random_auf(rng: &mut impl Rng) -> CubieCube {
+    let mut cube = CubieCube::default();
+    (0..rng.gen_range(0..4)).for_each(|_| cube.apply_move(0));
+    cube
+}
+
+// Shuffles the pieces at the given positions among themselves
+fn shuffle_at(rng: &mut impl Rng, pieces: &mut [u8], positions: &[usize]) {
+    let mut shuffled: Vec<u8> = positions.iter().map(|&position| pieces[position]).collect();
+    shuffled.shuffle(rng);
+    for (&position, piece) in positions.iter().zip(shuffled) {
+        pieces[position] = piece;
+    }
+}
+
+// Randomly orients the pieces at the given positions, the last one keeping the total a
+// multiple of `states` so the cube stays solvable
+fn twist_at(rng: &mut impl Rng, orientations: &mut [u8], positions: &[usize], states: u8) {
+    let Some((&last, rest)) = positions.split_last() else {
+        return;
+    };
+    let mut total = 0;
+    for &position in rest {
+        orientations[position] = rng.gen_range(0..states);
+        total += orientations[position];
+    }
+    orientations[last] = (states - total % states) % states;
+}
+
+// Swaps two edges when the corner and edge permutations have different parities
+fn fix_parity(cube: &mut CubieCube, edges: &[usize]) {
+    if permutation_parity(&cube.cp) != permutation_parity(&cube.ep) {
+        cube.ep.swap(edges[0], edges[1]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    // The corners and edges at the given positions are solved
+    fn solved_at(cube: &CubieCube, corners: &[usize], edges: &[usize]) -> bool {
+        corners.iter().all(|&i| cube.cp[i] == i as u8 && cube.co[i] == 0)
+            && edges.iter().all(|&i| cube.ep[i] == i as u8 && cube.eo[i] == 0)
+    }
+
+    #[test]
+    fn partial_scrambles_keep_their_part_of_the_cube_solved() {
+        let mut rng = StdRng::seed_from_u64(24);
+        let not_lse: Vec<usize> = (0..12).filter(|i| !LSE_EDGES.contains(i)).collect();
+        for scramble_type in ScrambleType::ALL {
+            for _ in 0..3 {
+                let scramble = partial_scramble(&mut rng, scramble_type);
+                let cube = CubieCube::from_moves(&scramble).unwrap();
+                // The cube after the U turn that puts the corners back, for the types with an AUF
+                let aligned = (0..4)
+                    .map(|turns| {
+                        let mut aligned = cube;
+                        (0..turns).for_each(|_| aligned.apply_move(0));
+                        aligned
+                    })
+                    .find(|aligned| aligned.cp[0] == 0)
+                    .unwrap_or(cube);
+                let kept = match scramble_type {
+                    ScrambleType::RandomState => !cube.is_solved(),
+                    ScrambleType::CrossSolved => solved_at(&cube, &[], &CROSS_EDGES),
+                    ScrambleType::LastLayer => solved_at(&cube, &F2L_CORNERS, &F2L_EDGES),
+                    ScrambleType::RouxSecondBlock => solved_at(&cube, &FIRST_BLOCK_CORNERS, &FIRST_BLOCK_EDGES),
+                    // Neither R nor U turns the left block, and neither flips edges
+                    ScrambleType::TwoGen => solved_at(&cube, &[5, 6], &[5, 6, 7, 9, 10]) && cube.eo == [0; 12],
+                    ScrambleType::TwoGll => {
+                        solved_at(&cube, &F2L_CORNERS, &F2L_EDGES) && aligned.cp == [0, 1, 2, 3, 4, 5, 6, 7] && cube.eo == [0; 12]
+                    }
+                    ScrambleType::Lse => solved_at(&aligned, &[0, 1, 2, 3, 4, 5, 6, 7], &not_lse),
+                };
+                assert!(kept, "{}: {}", scramble_type, scramble);
+            }
+        }
+    }
+}
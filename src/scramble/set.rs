@@ -0,0 +1,234 @@
+// Scramble sets: everyone using the same code gets the same scrambles in the same order, so a
+// team can race on them. A set can also be shared as a file with the scrambles written out
+use super::generate_case_with;
+use crate::{CubeEvent, CustomEvent, EventSettings};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// Characters of generated codes, leaving out I, L, O and U so codes read back unambiguously
+const CODE_ALPHABET: &[u8] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+// A sequence of scrambles for every event, reproduced from its code
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScrambleSet {
+    pub code: String, // Generated code such as "K7F3-9QX2", or any text used as a seed
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub scrambles: HashMap<String, Vec<SetScramble>>, // Scrambles written out for a file, keyed by event name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub custom_events: HashMap<String, CustomEvent>, // Definitions of the custom events written out, keyed by name
+}
+
+// One scramble of a set, with the trainer case it sets up
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SetScramble {
+    pub scramble: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case: Option<String>, // Id of the case, for trainer scrambles
+}
+
+impl ScrambleSet {
+    // A set with a fresh random code
+    pub fn random(rng: &mut impl Rng) -> Self {
+        let code: String = (0..8).map(|_| CODE_ALPHABET[rng.gen_range(0..CODE_ALPHABET.len())] as char).collect();
+        Self { code: format!("{}-{}", &code[..4], &code[4..]), ..Self::default() }
+    }
+
+    // The set of a shared code or seed text
+    pub fn from_code(code: &str) -> Result<Self, String> {
+        let set = Self { code: code.trim().to_string(), ..Self::default() };
+        if set.seed_text().is_empty() {
+            return Err("Enter a set code or seed".to_string());
+        }
+        Ok(set)
+    }
+
+    // Reads a set file written by `to_json`
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let set: Self = serde_json::from_str(text).map_err(|e| format!("Not a scramble set: {}", e))?;
+        Self::from_code(&set.code)?;
+        for custom_event in set.custom_events.values() {
+            custom_event.validate()?;
+        }
+        Ok(set)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    // Random number generator of one scramble, seeded the same for everyone with the code.
+    // ChaCha8 is a fixed algorithm, unlike StdRng, so codes keep their scrambles across builds.
+    pub fn rng(&self, event: &CubeEvent, index: usize) -> ChaCha8Rng {
+        ChaCha8Rng::seed_from_u64(fnv1a(&format!("{}/{}/{}", self.seed_text(), event, index)))
+    }
+
+    // The scramble at a position of an event's sequence, as written out in the set file if it
+    // was, so a file gives the same scrambles whatever version of the scrambler reads it.
+    // Generated scrambles use the event's default settings, covering every scramble type and
+    // trainer case, and the set's own custom event definitions when it has them, so they do not
+    // depend on the reader's settings.
+    pub fn scramble(&self, event: &CubeEvent, index: usize, custom_events: &HashMap<String, CustomEvent>) -> SetScramble {
+        if let Some(written) = self.written(event, index) {
+            return written.clone();
+        }
+
+        let custom_events = match event {
+            CubeEvent::Custom(name) if self.custom_events.contains_key(name) => &self.custom_events,
+            _ => custom_events,
+        };
+        generate_case_with(&mut self.rng(event, index), event, &EventSettings::for_event(event), custom_events)
+    }
+
+    // The scramble the set file has for a position, if any
+    pub fn written(&self, event: &CubeEvent, index: usize) -> Option<&SetScramble> {
+        self.scrambles.get(&event.to_string())?.get(index)
+    }
+
+    // Writes the first `count` scrambles of an event into the set for sharing as a file, along
+    // with the definition of a custom event
+    pub fn write_out(&mut self, event: &CubeEvent, count: usize, custom_events: &HashMap<String, CustomEvent>) {
+        if let CubeEvent::Custom(name) = event
+            && let Some(custom_event) = custom_events.get(name)
+        {
+            self.custom_events.entry(name.clone()).or_insert_with(|| custom_event.clone());
+        }
+        let scrambles = (0..count).map(|index| self.scramble(event, index, custom_events)).collect();
+        self.scrambles.insert(event.to_string(), scrambles);
+    }
+
+    // The code with case, spaces and dashes ignored, so "k7f3 9qx2" is the same set
+    fn seed_text(&self) -> String {
+        self.code
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '-')
+            .flat_map(char::to_uppercase)
+            .collect()
+    }
+}
+
+// 64-bit FNV-1a, which unlike the standard library's hasher gives the same value on every
+// platform and Rust version
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StandardEvent;
+    use crate::scramble::{ScrambleType, generate_with};
+    use crate::trainer::{self, AlgSet};
+
+    // The scrambles at the first `count` positions of an event
+    fn scrambles(set: &ScrambleSet, event: &CubeEvent, count: usize, custom_events: &HashMap<String, CustomEvent>) -> Vec<String> {
+        (0..count).map(|index| set.scramble(event, index, custom_events).scramble).collect()
+    }
+
+    #[test]
+    fn sets_reproduce_the_same_scrambles_from_their_code() {
+        let set = ScrambleSet::random(&mut ChaCha8Rng::seed_from_u64(25));
+        assert!(set.code.len() == 9 && set.code.as_bytes()[4] == b'-', "{}", set.code);
+        let same = ScrambleSet::from_code(&format!("  {} ", set.code.to_lowercase().replace('-', " "))).unwrap();
+        let other = ScrambleSet::from_code("week 42").unwrap();
+        let custom_events = HashMap::new();
+
+        for event in [
+            CubeEvent::Standard(StandardEvent::Cube3x3),
+            CubeEvent::Standard(StandardEvent::Pyraminx),
+            CubeEvent::Training(AlgSet::Pll),
+        ] {
+            let first = scrambles(&set, &event, 3, &custom_events);
+            assert_eq!(first, scrambles(&same, &event, 3, &custom_events), "{}", event);
+            assert_ne!(first, scrambles(&other, &event, 3, &custom_events), "{}", event);
+            assert!(first[0] != first[1] && first[1] != first[2], "{} {:?}", event, first);
+        }
+        assert_eq!(ScrambleSet::from_code(" - "), Err("Enter a set code or seed".to_string()));
+    }
+
+    #[test]
+    fn trainer_scrambles_come_with_their_case() {
+        let set = ScrambleSet::from_code("PLL night").unwrap();
+        let event = CubeEvent::Training(AlgSet::Pll);
+        let mut rng = set.rng(&event, 0);
+        let case = trainer::pick_case(&mut rng, AlgSet::Pll, &[]);
+        let expected = SetScramble { scramble: trainer::scramble(&mut rng, case), case: Some(case.id.clone()) };
+        assert_eq!(set.scramble(&event, 0, &HashMap::new()), expected);
+    }
+
+    #[test]
+    fn set_files_keep_their_written_out_scrambles() {
+        let event = CubeEvent::Standard(StandardEvent::Skewb);
+        let custom_events = HashMap::new();
+        let mut set = ScrambleSet::from_code("K7F3-9QX2").unwrap();
+        set.write_out(&event, 5, &custom_events);
+
+        let mut shared = ScrambleSet::from_json(&set.to_json()).unwrap();
+        assert_eq!(shared, set);
+        assert_eq!(shared.scramble(&event, 4, &custom_events), set.scrambles["Skewb"][4]);
+        // Past the written scrambles the code takes over
+        assert_eq!(shared.written(&event, 5), None);
+        assert_eq!(shared.scramble(&event, 5, &custom_events), set.scramble(&event, 5, &custom_events));
+
+        shared.scrambles.get_mut("Skewb").unwrap()[0].scramble = "R U R' U'".to_string();
+        assert_eq!(shared.scramble(&event, 0, &custom_events).scramble, "R U R' U'");
+        assert!(ScrambleSet::from_json("{\"code\": \"\"}").is_err());
+        assert!(ScrambleSet::from_json("[1, 2]").unwrap_err().starts_with("Not a scramble set"));
+    }
+
+    #[test]
+    fn set_files_keep_the_case_of_each_scramble() {
+        let event = CubeEvent::Training(AlgSet::Oll);
+        let mut set = ScrambleSet::from_code("oll race").unwrap();
+        set.write_out(&event, 3, &HashMap::new());
+        let shared = ScrambleSet::from_json(&set.to_json()).unwrap();
+        for index in 0..3 {
+            let scramble = shared.scramble(&event, index, &HashMap::new());
+            assert!(scramble.case.is_some());
+            assert_eq!(scramble, set.scramble(&event, index, &HashMap::new()));
+        }
+    }
+
+    #[test]
+    fn set_files_carry_their_custom_event_definitions() {
+        let event = CubeEvent::Custom("Mirror".to_string());
+        let definition = |moves: &[&str]| CustomEvent {
+            name: "Mirror".to_string(),
+            scramble_length: 12,
+            moves: moves.iter().map(|m| m.to_string()).collect(),
+            ..CustomEvent::default()
+        };
+        let mine = HashMap::from([("Mirror".to_string(), definition(&["R", "U", "F"]))]);
+        let theirs = HashMap::from([("Mirror".to_string(), definition(&["L", "D", "B"]))]);
+
+        let mut set = ScrambleSet::from_code("mirror").unwrap();
+        set.write_out(&event, 1, &mine);
+        let shared = ScrambleSet::from_json(&set.to_json()).unwrap();
+        assert_eq!(shared.custom_events["Mirror"], mine["Mirror"]);
+        // Scrambles past the written ones still follow the definition in the file
+        assert_eq!(scrambles(&shared, &event, 3, &theirs), scrambles(&set, &event, 3, &mine));
+        assert!(shared.scramble(&event, 2, &theirs).scramble.split(' ').all(|m| !m.starts_with(['L', 'D', 'B'])));
+    }
+
+    #[test]
+    fn scrambles_do_not_depend_on_local_settings() {
+        // Only the code and the event go into a set scramble, so a teammate's scramble type
+        // or trainer cases cannot change it
+        let set = ScrambleSet::from_code("week 42").unwrap();
+        let cube = CubeEvent::Standard(StandardEvent::Cube3x3);
+        let random_state = EventSettings::for_event(&cube);
+        assert_eq!(random_state.scramble_type, ScrambleType::RandomState);
+        let expected = generate_with(&mut set.rng(&cube, 0), &cube, &random_state, &HashMap::new());
+        assert_eq!(set.scramble(&cube, 0, &HashMap::new()).scramble, expected);
+    }
+
+    #[test]
+    fn codes_keep_their_scrambles_across_builds() {
+        // Changing these breaks every code that was ever shared
+        let set = ScrambleSet::from_code("K7F3-9QX2").unwrap();
+        let event = CubeEvent::Standard(StandardEvent::Pyraminx);
+        assert_eq!(set.scramble(&event, 0, &HashMap::new()).scramble, "B L' R' B' U R' R R U B'");
+        assert_eq!(set.scramble(&event, 1, &HashMap::new()).scramble, "R B U R' U L R B U' B'");
+    }
+}
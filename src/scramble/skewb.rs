@@ -0,0 +1,236 @@
+// Random-state Skewb scrambles from a complete distance table
+use rand::Rng;
+use std::collections::{HashMap, VecDeque};
+use std::sync::OnceLock;
+
+// WCA moves turn the corners DRB (R), ULB (U), DLF (L) and DLB (B), leaving UFR fixed
+const AXES: [[i32; 3]; 4] = [[1, -1, -1], [-1, 1, -1], [-1, -1, 1], [-1, -1, -1]];
+const AXIS_NAMES: [&str; 4] = ["R", "U", "L", "B"];
+const MOVE_COUNT: usize = 8; // Each axis clockwise, then counter-clockwise
+
+// WCA rejects states that can be solved in fewer moves than this
+const MIN_DISTANCE: u8 = 7;
+
+type Vector = [i32; 3];
+
+// A sticker slot, given by the position of its piece and the direction it faces
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Slot {
+    piece: Vector,
+    normal: Vector,
+}
+
+fn dot(a: Vector, b: Vector) -> i32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: Vector, b: Vector) -> Vector {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+// Rotates `v` by 120 degrees clockwise, seen from outside, around the corner axis `a`
+fn rotate(v: Vector, a: Vector) -> Vector {
+    let (c, d) = (cross(a, v), dot(a, v));
+    [0, 1, 2].map(|i| (a[i] * d - v[i] - c[i]) / 2)
+}
+
+// Lists the six center slots followed by the 24 corner sticker slots
+fn slots() -> Vec<Slot> {
+    let normals: [Vector; 6] = [[0, 1, 0], [1, 0, 0], [0, 0, 1], [0, -1, 0], [-1, 0, 0], [0, 0, -1]];
+    let mut slots: Vec<Slot> = normals.iter().map(|&n| Slot { piece: n, normal: n }).collect();
+    for x in [-1, 1] {
+        for y in [-1, 1] {
+            for z in [-1, 1] {
+                let piece = [x, y, z];
+                for axis in 0..3 {
+                    let mut normal = [0; 3];
+                    normal[axis] = piece[axis];
+                    slots.push(Slot { piece, normal });
+                }
+            }
+        }
+    }
+    slots
+}
+
+// Permutation of sticker slots for each move: sticker in slot i moves to slot perm[i]
+fn move_permutations() -> Vec<Vec<usize>> {
+    let slots = slots();
+    let mut moves = Vec::with_capacity(MOVE_COUNT);
+    for turns in [1, 2] {
+        for axis in AXES {
+            let perm = slots
+                .iter()
+                .enumerate()
+                .map(|(i, slot)| {
+                    if dot(slot.piece, axis) <= 0 {
+                        return i;
+                    }
+                    let mut target = *slot;
+                    for _ in 0..turns {
+                        target = Slot { piece: rotate(target.piece, axis), normal: rotate(target.normal, axis) };
+                    }
+                    slots.iter().position(|s| *s == target).unwrap_or(i)
+                })
+                .collect();
+            moves.push(perm);
+        }
+    }
+    moves
+}
+
+// Applies a slot permutation to a sticker state
+fn permute<const N: usize>(state: &[u8; N], perm: &[usize]) -> [u8; N] {
+    let mut next = *state;
+    for (i, &target) in perm.iter().enumerate() {
+        next[target] = state[i];
+    }
+    next
+}
+
+// Enumerates every reachable value of a sub-state and its transitions under each move
+fn explore<const N: usize>(solved: [u8; N], perms: &[Vec<usize>]) -> Vec<[u32; MOVE_COUNT]> {
+    let mut index: HashMap<[u8; N], u32> = HashMap::from([(solved, 0)]);
+    let mut states = vec![solved];
+    let mut table = Vec::new();
+    let mut queue = VecDeque::from([0usize]);
+
+    while let Some(current) = queue.pop_front() {
+        let mut row = [0; MOVE_COUNT];
+        for (m, perm) in perms.iter().enumerate() {
+            let next = permute(&states[current], perm);
+            let next_index = *index.entry(next).or_insert_with(|| {
+                states.push(next);
+                queue.push_back(states.len() - 1);
+                (states.len() - 1) as u32
+            });
+            row[m] = next_index;
+        }
+        // States are numbered in discovery order, so rows are filled in sequence
+        table.push(row);
+    }
+    table
+}
+
+// Transition tables for centers and corners plus the distance of every state
+struct Tables {
+    center_move: Vec<[u32; MOVE_COUNT]>,
+    corner_move: Vec<[u32; MOVE_COUNT]>,
+    distance: Vec<u8>,
+}
+
+static TABLES: OnceLock<Tables> = OnceLock::new();
+
+fn tables() -> &'static Tables {
+    TABLES.get_or_init(|| {
+        let perms = move_permutations();
+        let center_perms: Vec<Vec<usize>> = perms.iter().map(|p| p[..6].to_vec()).collect();
+        let corner_perms: Vec<Vec<usize>> = perms.iter().map(|p| p[6..].iter().map(|&t| t - 6).collect()).collect();
+
+        let center_move = explore([0, 1, 2, 3, 4, 5], &center_perms);
+        let corner_move = explore(std::array::from_fn::<u8, 24, _>(|i| i as u8), &corner_perms);
+
+        let corners = corner_move.len();
+        let mut distance = vec![u8::MAX; center_move.len() * corners];
+        let mut queue = VecDeque::from([0usize]);
+        distance[0] = 0;
+        while let Some(state) = queue.pop_front() {
+            let (center, corner) = (state / corners, state % corners);
+            for m in 0..MOVE_COUNT {
+                let next = center_move[center][m] as usize * corners + corner_move[corner][m] as usize;
+                if distance[next] == u8::MAX {
+                    distance[next] = distance[state] + 1;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        Tables { center_move, corner_move, distance }
+    })
+}
+
+// Formats a move index, e.g. R or R'
+fn format_move(mv: usize) -> String {
+    format!("{}{}", AXIS_NAMES[mv % 4], if mv < 4 { "" } else { "'" })
+}
+
+// Generates a random-state Skewb scramble
+pub fn skewb_scramble(rng: &mut impl Rng) -> String {
+    let tables = tables();
+    let corners = tables.corner_move.len();
+
+    let mut state = loop {
+        let candidate = rng.gen_range(0..tables.distance.len());
+        let distance = tables.distance[candidate];
+        if distance != u8::MAX && distance >= MIN_DISTANCE {
+            break candidate;
+        }
+    };
+
+    // Walk back to solved along decreasing distances, then undo that path
+    let mut solution = Vec::new();
+    while tables.distance[state] > 0 {
+        let (center, corner) = (state / corners, state % corners);
+        for m in 0..MOVE_COUNT {
+            let next = tables.center_move[center][m] as usize * corners + tables.corner_move[corner][m] as usize;
+            if tables.distance[next] < tables.distance[state] {
+                solution.push(m);
+                state = next;
+                break;
+            }
+        }
+    }
+
+    solution
+        .iter()
+        .rev()
+        .map(|&mv| format_move((mv + 4) % MOVE_COUNT))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    // Parses a move written by `format_move`
+    fn parse_move(text: &str) -> usize {
+        let axis = AXIS_NAMES.iter().position(|name| text.trim_end_matches('\'') == *name).unwrap();
+        if text.ends_with('\'') { axis + 4 } else { axis }
+    }
+
+    #[test]
+    fn every_skewb_state_is_in_the_distance_table() {
+        let tables = tables();
+        let reachable: Vec<u8> = tables.distance.iter().copied().filter(|&d| d != u8::MAX).collect();
+        // The Skewb has 3,149,280 states and God's number 11
+        assert_eq!(reachable.len(), 3_149_280);
+        assert_eq!(reachable.iter().max(), Some(&11));
+    }
+
+    #[test]
+    fn scrambles_reach_a_state_at_their_own_length() {
+        let tables = tables();
+        let corners = tables.corner_move.len();
+        let mut rng = StdRng::seed_from_u64(51);
+        let mut seen = Vec::new();
+        for _ in 0..20 {
+            let scramble = skewb_scramble(&mut rng);
+            let mut state = 0;
+            for mv in scramble.split_whitespace().map(parse_move) {
+                let (center, corner) = (state / corners, state % corners);
+                state = tables.center_move[center][mv] as usize * corners + tables.corner_move[corner][mv] as usize;
+            }
+            // Scrambles are optimal solutions undone, so their length is the state's distance
+            let length = scramble.split_whitespace().count();
+            assert_eq!(tables.distance[state] as usize, length, "{}", scramble);
+            assert!((MIN_DISTANCE as usize..=11).contains(&length), "{}", scramble);
+            seen.push(state);
+        }
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), 20);
+    }
+}
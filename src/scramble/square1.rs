@@ -0,0 +1,133 @@
+// Square-1 scrambles in (x,y)/ notation that respect the puzzle's shape
+use rand::Rng;
+
+const SLICES: usize = 12;
+
+// Layers as twelve 30-degree slots viewed from above, each holding a piece id.
+// Corners fill two neighbouring slots with the same id, edges fill one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Square1 {
+    pub top: [u8; 12],
+    pub bottom: [u8; 12],
+}
+
+impl Default for Square1 {
+    fn default() -> Self {
+        // Cube shape with the slice running between an edge and a corner on both sides
+        Self {
+            top: [0, 0, 1, 2, 2, 3, 4, 4, 5, 6, 6, 7],
+            bottom: [8, 8, 9, 10, 10, 11, 12, 12, 13, 14, 14, 15],
+        }
+    }
+}
+
+impl Square1 {
+    // Returns true if no piece straddles the slice in either layer
+    pub fn can_slice(&self) -> bool {
+        [&self.top, &self.bottom]
+            .iter()
+            .all(|layer| layer[11] != layer[0] && layer[5] != layer[6])
+    }
+
+    // Turns the top layer clockwise seen from above and the bottom clockwise seen from below
+    pub fn turn(&mut self, top: i32, bottom: i32) {
+        let (top_shift, bottom_shift) = (top.rem_euclid(12) as usize, bottom.rem_euclid(12) as usize);
+        let old = self.clone();
+        for i in 0..12 {
+            self.top[(i + top_shift) % 12] = old.top[i];
+            self.bottom[(i + 12 - bottom_shift) % 12] = old.bottom[i];
+        }
+    }
+
+    // Flips the right half of the puzzle over
+    pub fn slice(&mut self) {
+        let old = self.clone();
+        for i in 0..6 {
+            self.top[5 - i] = old.bottom[i];
+            self.bottom[5 - i] = old.top[i];
+        }
+    }
+}
+
+// Generates a Square-1 scramble such as "(1,0)/ (-3,3)/ (0,-3)/ ..."
+pub fn square1_scramble(rng: &mut impl Rng) -> String {
+    let mut puzzle = Square1::default();
+    let mut scramble = Vec::with_capacity(SLICES);
+
+    while scramble.len() < SLICES {
+        let (top, bottom) = (rng.gen_range(-5..=6), rng.gen_range(-5..=6));
+        // (0,0) would undo the previous slice
+        if top == 0 && bottom == 0 {
+            continue;
+        }
+
+        let mut next = puzzle.clone();
+        next.turn(top, bottom);
+        if !next.can_slice() {
+            continue;
+        }
+        next.slice();
+        puzzle = next;
+        scramble.push(format!("({},{})/", top, bottom));
+    }
+    scramble.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    // Returns true if every corner fills two neighbouring slots of one layer
+    fn has_whole_corners(layer: &[u8; 12]) -> bool {
+        (0..12).all(|i| {
+            let count = layer.iter().filter(|&&piece| piece == layer[i]).count();
+            let corner = layer[i].is_multiple_of(2);
+            match (corner, count) {
+                (true, 2) => layer[(i + 1) % 12] == layer[i] || layer[(i + 11) % 12] == layer[i],
+                (false, 1) => true,
+                _ => false,
+            }
+        })
+    }
+
+    #[test]
+    fn scrambles_only_slice_when_the_shape_allows_it() {
+        let mut rng = StdRng::seed_from_u64(52);
+        for _ in 0..50 {
+            let scramble = square1_scramble(&mut rng);
+            let mut puzzle = Square1::default();
+            let turns: Vec<&str> = scramble.split(' ').collect();
+            assert_eq!(turns.len(), SLICES, "{}", scramble);
+            for turn in turns {
+                let (top, bottom) = turn
+                    .strip_prefix('(')
+                    .and_then(|turn| turn.strip_suffix(")/"))
+                    .and_then(|turn| turn.split_once(','))
+                    .unwrap_or_else(|| panic!("{} in {}", turn, scramble));
+                let (top, bottom): (i32, i32) = (top.parse().unwrap(), bottom.parse().unwrap());
+                assert!((-5..=6).contains(&top) && (-5..=6).contains(&bottom), "{}", scramble);
+                assert_ne!((top, bottom), (0, 0), "{}", scramble);
+
+                puzzle.turn(top, bottom);
+                assert!(puzzle.can_slice(), "{} in {}", turn, scramble);
+                puzzle.slice();
+                assert!(has_whole_corners(&puzzle.top) && has_whole_corners(&puzzle.bottom), "{}", scramble);
+            }
+        }
+    }
+
+    #[test]
+    fn slicing_twice_and_turning_back_restore_the_puzzle() {
+        let mut puzzle = Square1::default();
+        puzzle.turn(2, 0);
+        assert!(!puzzle.can_slice());
+        puzzle.turn(-2, 3);
+        assert!(puzzle.can_slice());
+        puzzle.slice();
+        puzzle.slice();
+        puzzle.turn(0, 9);
+        assert_eq!(puzzle, Square1::default());
+    }
+}
@@ -0,0 +1,376 @@
+// Kociemba two-phase solver used to turn random cube states into scrambles
+use super::cube::{CubieCube, MOVE_COUNT};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+const TWIST_COUNT: usize = 2187;
+const FLIP_COUNT: usize = 2048;
+const SLICE_COUNT: usize = 495;
+const PERM_COUNT: usize = 40320;
+const SLICE_PERM_COUNT: usize = 24;
+
+// Moves that keep the cube in the phase 2 subgroup <U, D, R2, L2, F2, B2>
+const PHASE2_MOVES: [usize; 10] = [0, 1, 2, 4, 7, 9, 10, 11, 13, 16];
+
+const CACHE_FILE: &str = "two_phase_tables.bin";
+const CACHE_MAGIC: &[u8; 4] = b"LST2";
+const CACHE_VERSION: u32 = 1;
+const UNVISITED: u8 = u8::MAX;
+
+// Move and pruning tables for both phases
+struct Tables {
+    twist_move: Vec<[u16; MOVE_COUNT]>,       // Phase 1 corner orientation transitions
+    flip_move: Vec<[u16; MOVE_COUNT]>,        // Phase 1 edge orientation transitions
+    slice_move: Vec<[u16; MOVE_COUNT]>,       // Phase 1 E-slice position transitions
+    corner_perm_move: Vec<[u16; 10]>,         // Phase 2 corner permutation transitions
+    edge_perm_move: Vec<[u16; 10]>,           // Phase 2 U/D edge permutation transitions
+    slice_perm_move: Vec<[u16; 10]>,          // Phase 2 E-slice permutation transitions
+    slice_twist_prune: Vec<u8>,               // Phase 1 distance bound from slice and twist
+    slice_flip_prune: Vec<u8>,                // Phase 1 distance bound from slice and flip
+    slice_corner_prune: Vec<u8>,              // Phase 2 distance bound from slice and corners
+    slice_edge_prune: Vec<u8>,                // Phase 2 distance bound from slice and edges
+}
+
+static TABLES: OnceLock<Tables> = OnceLock::new();
+static CACHE_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+// Keeps the pruning tables in `dir` between runs. Only the app sets this, so tests and library
+// users never write into the user's data directory.
+pub fn use_cache_dir(dir: PathBuf) {
+    let _ = CACHE_DIR.set(dir);
+}
+
+// Returns the solver tables, loading them from the cache or generating them once
+fn tables() -> &'static Tables {
+    TABLES.get_or_init(|| Tables::load_or_generate(CACHE_DIR.get().map(PathBuf::as_path)))
+}
+
+impl Tables {
+    // Builds the move tables and reads the pruning tables from the cache if possible
+    fn load_or_generate(cache_dir: Option<&Path>) -> Self {
+        let twist_move = build_move_table(TWIST_COUNT, &ALL_MOVES, CubieCube::twist);
+        let flip_move = build_move_table(FLIP_COUNT, &ALL_MOVES, CubieCube::flip);
+        let slice_move = build_move_table(SLICE_COUNT, &ALL_MOVES, CubieCube::slice);
+        let corner_perm_move = build_move_table(PERM_COUNT, &PHASE2_MOVES, CubieCube::corner_perm);
+        let edge_perm_move = build_move_table(PERM_COUNT, &PHASE2_MOVES, CubieCube::ud_edge_perm);
+        let slice_perm_move = build_move_table(SLICE_PERM_COUNT, &PHASE2_MOVES, CubieCube::slice_perm);
+
+        let mut tables = Self {
+            twist_move,
+            flip_move,
+            slice_move,
+            corner_perm_move,
+            edge_perm_move,
+            slice_perm_move,
+            slice_twist_prune: Vec::new(),
+            slice_flip_prune: Vec::new(),
+            slice_corner_prune: Vec::new(),
+            slice_edge_prune: Vec::new(),
+        };
+
+        if let Some(dir) = cache_dir
+            && tables.load_pruning_tables(&dir.join(CACHE_FILE))
+        {
+            return tables;
+        }
+
+        tables.slice_twist_prune = build_pruning_table(SLICE_COUNT, TWIST_COUNT, &tables.slice_move, &tables.twist_move);
+        tables.slice_flip_prune = build_pruning_table(SLICE_COUNT, FLIP_COUNT, &tables.slice_move, &tables.flip_move);
+        tables.slice_corner_prune = build_pruning_table(SLICE_PERM_COUNT, PERM_COUNT, &tables.slice_perm_move, &tables.corner_perm_move);
+        tables.slice_edge_prune = build_pruning_table(SLICE_PERM_COUNT, PERM_COUNT, &tables.slice_perm_move, &tables.edge_perm_move);
+
+        if let Some(dir) = cache_dir
+            && std::fs::create_dir_all(dir).is_ok()
+        {
+            let _ = std::fs::write(dir.join(CACHE_FILE), tables.encode_pruning_tables());
+        }
+        tables
+    }
+
+    // Serializes the pruning tables for the cache file
+    fn encode_pruning_tables(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(CACHE_MAGIC);
+        bytes.extend_from_slice(&CACHE_VERSION.to_le_bytes());
+        for table in self.pruning_tables() {
+            bytes.extend_from_slice(&(table.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(table);
+        }
+        bytes
+    }
+
+    // Reads the pruning tables from the cache file, returning false if it is missing or invalid
+    fn load_pruning_tables(&mut self, path: &Path) -> bool {
+        let Ok(bytes) = std::fs::read(path) else {
+            return false;
+        };
+        if bytes.len() < 8 || &bytes[..4] != CACHE_MAGIC || bytes[4..8] != CACHE_VERSION.to_le_bytes() {
+            return false;
+        }
+
+        let expected = [
+            SLICE_COUNT * TWIST_COUNT,
+            SLICE_COUNT * FLIP_COUNT,
+            SLICE_PERM_COUNT * PERM_COUNT,
+            SLICE_PERM_COUNT * PERM_COUNT,
+        ];
+        let mut loaded = Vec::new();
+        let mut offset = 8;
+        for len in expected {
+            let Some(header) = bytes.get(offset..offset + 4) else {
+                return false;
+            };
+            if u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as usize != len {
+                return false;
+            }
+            offset += 4;
+            let Some(table) = bytes.get(offset..offset + len) else {
+                return false;
+            };
+            loaded.push(table.to_vec());
+            offset += len;
+        }
+
+        self.slice_edge_prune = loaded.pop().unwrap_or_default();
+        self.slice_corner_prune = loaded.pop().unwrap_or_default();
+        self.slice_flip_prune = loaded.pop().unwrap_or_default();
+        self.slice_twist_prune = loaded.pop().unwrap_or_default();
+        true
+    }
+
+    fn pruning_tables(&self) -> [&Vec<u8>; 4] {
+        [&self.slice_twist_prune, &self.slice_flip_prune, &self.slice_corner_prune, &self.slice_edge_prune]
+    }
+}
+
+const ALL_MOVES: [usize; MOVE_COUNT] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17];
+
+// Builds a coordinate transition table by exploring states from the solved cube
+fn build_move_table<const N: usize>(size: usize, moves: &[usize; N], coord: fn(&CubieCube) -> usize) -> Vec<[u16; N]> {
+    let mut table = vec![[u16::MAX; N]; size];
+    let mut representatives: Vec<Option<CubieCube>> = vec![None; size];
+    let mut queue = VecDeque::new();
+
+    let solved = CubieCube::default();
+    representatives[coord(&solved)] = Some(solved);
+    queue.push_back(coord(&solved));
+
+    while let Some(index) = queue.pop_front() {
+        let Some(cube) = representatives[index] else { continue };
+        for (i, &mv) in moves.iter().enumerate() {
+            let mut next = cube;
+            next.apply_move(mv);
+            let next_index = coord(&next);
+            table[index][i] = next_index as u16;
+            if representatives[next_index].is_none() {
+                representatives[next_index] = Some(next);
+                queue.push_back(next_index);
+            }
+        }
+    }
+    table
+}
+
+// Builds a distance table over the product of two coordinates with breadth-first search
+fn build_pruning_table<const N: usize>(outer: usize, inner: usize, outer_move: &[[u16; N]], inner_move: &[[u16; N]]) -> Vec<u8> {
+    let mut table = vec![UNVISITED; outer * inner];
+    let mut queue = VecDeque::new();
+    table[0] = 0;
+    queue.push_back(0);
+
+    while let Some(index) = queue.pop_front() {
+        let (o, i) = (index / inner, index % inner);
+        let depth = table[index];
+        for m in 0..N {
+            let next = outer_move[o][m] as usize * inner + inner_move[i][m] as usize;
+            if table[next] == UNVISITED {
+                table[next] = depth + 1;
+                queue.push_back(next);
+            }
+        }
+    }
+    table
+}
+
+// Returns true if `mv` may follow `last` without being redundant
+fn allowed_after(last: Option<usize>, mv: usize) -> bool {
+    match last {
+        None => true,
+        Some(last) => {
+            let (face, last_face) = (mv / 3, last / 3);
+            face != last_face && face + 3 != last_face
+        }
+    }
+}
+
+// Search state for a single solve
+struct Search<'a> {
+    tables: &'a Tables,
+    cube: CubieCube,         // State being solved
+    path: Vec<usize>,        // Current move sequence
+    max_length: usize,       // Maximum total solution length
+    nodes: usize,            // Nodes visited so far
+    node_limit: usize,       // Give up once this many nodes were visited
+}
+
+impl Search<'_> {
+    fn phase1(&mut self, twist: usize, flip: usize, slice: usize, togo: usize) -> bool {
+        self.nodes += 1;
+        if self.nodes > self.node_limit {
+            return false;
+        }
+
+        if togo == 0 {
+            // A phase 1 solution ending in a phase 2 move was already tried at a shorter depth
+            let ends_in_phase2 = self.path.last().is_some_and(|mv| PHASE2_MOVES.contains(mv));
+            return twist == 0 && flip == 0 && slice == 0 && !ends_in_phase2 && self.start_phase2();
+        }
+
+        for mv in 0..MOVE_COUNT {
+            if !allowed_after(self.path.last().copied(), mv) {
+                continue;
+            }
+            let t = self.tables;
+            let next_twist = t.twist_move[twist][mv] as usize;
+            let next_flip = t.flip_move[flip][mv] as usize;
+            let next_slice = t.slice_move[slice][mv] as usize;
+            let bound = t.slice_twist_prune[next_slice * TWIST_COUNT + next_twist]
+                .max(t.slice_flip_prune[next_slice * FLIP_COUNT + next_flip]) as usize;
+            if bound >= togo {
+                continue;
+            }
+
+            self.path.push(mv);
+            if self.phase1(next_twist, next_flip, next_slice, togo - 1) {
+                return true;
+            }
+            self.path.pop();
+        }
+        false
+    }
+
+    fn start_phase2(&mut self) -> bool {
+        let mut cube = self.cube;
+        for &mv in &self.path {
+            cube.apply_move(mv);
+        }
+
+        let (corners, edges, slice) = (cube.corner_perm(), cube.ud_edge_perm(), cube.slice_perm());
+        let max_depth = self.max_length.saturating_sub(self.path.len()).min(18);
+        for depth in 0..=max_depth {
+            if self.phase2(corners, edges, slice, depth) {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn phase2(&mut self, corners: usize, edges: usize, slice: usize, togo: usize) -> bool {
+        self.nodes += 1;
+        if togo == 0 {
+            return corners == 0 && edges == 0 && slice == 0;
+        }
+
+        for (i, &mv) in PHASE2_MOVES.iter().enumerate() {
+            if !allowed_after(self.path.last().copied(), mv) {
+                continue;
+            }
+            let t = self.tables;
+            let next_corners = t.corner_perm_move[corners][i] as usize;
+            let next_edges = t.edge_perm_move[edges][i] as usize;
+            let next_slice = t.slice_perm_move[slice][i] as usize;
+            let bound = t.slice_corner_prune[next_slice * PERM_COUNT + next_corners]
+                .max(t.slice_edge_prune[next_slice * PERM_COUNT + next_edges]) as usize;
+            if bound >= togo {
+                continue;
+            }
+
+            self.path.push(mv);
+            if self.phase2(next_corners, next_edges, next_slice, togo - 1) {
+                return true;
+            }
+            self.path.pop();
+        }
+        false
+    }
+}
+
+// Finds a solution of at most `max_length` moves, giving up after `node_limit` search nodes
+fn solve_within(cube: &CubieCube, max_length: usize, node_limit: usize) -> Option<Vec<usize>> {
+    let tables = tables();
+    let mut search = Search {
+        tables,
+        cube: *cube,
+        path: Vec::new(),
+        max_length,
+        nodes: 0,
+        node_limit,
+    };
+
+    let (twist, flip, slice) = (cube.twist(), cube.flip(), cube.slice());
+    for depth in 0..=max_length.min(12) {
+        if search.phase1(twist, flip, slice, depth) {
+            return Some(search.path);
+        }
+        if search.nodes > node_limit {
+            break;
+        }
+    }
+    None
+}
+
+// Solves a cube state, preferring short solutions but always returning one.
+// The search is bounded by node counts rather than wall time so results are reproducible.
+pub fn solve(cube: &CubieCube) -> Vec<usize> {
+    for (max_length, node_limit) in [(21, 500_000), (23, 500_000)] {
+        if let Some(solution) = solve_within(cube, max_length, node_limit) {
+            return solution;
+        }
+    }
+    // Phase 1 needs at most 12 moves and phase 2 at most 18, so this always succeeds
+    solve_within(cube, 30, usize::MAX).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scramble::state_scramble;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn scrambles_reach_their_random_state() {
+        let mut rng = StdRng::seed_from_u64(41);
+        for _ in 0..10 {
+            let state = CubieCube::random(&mut rng);
+            let scramble = state_scramble(&state);
+            assert_eq!(CubieCube::from_moves(&scramble), Some(state), "{}", scramble);
+            // Phase 1 takes at most 12 moves and phase 2 at most 18
+            assert!(scramble.split_whitespace().count() <= 30, "{}", scramble);
+        }
+        assert_eq!(state_scramble(&CubieCube::default()), "");
+    }
+
+    #[test]
+    fn pruning_tables_are_read_back_from_the_cache() {
+        let dir = std::env::temp_dir().join(format!("lstimer-two-phase-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        Tables::load_or_generate(Some(&dir));
+        assert!(dir.join(CACHE_FILE).exists());
+
+        // Tests never set a cache directory, so the shared tables were generated
+        let cached = Tables::load_or_generate(Some(&dir));
+        assert_eq!(cached.pruning_tables(), tables().pruning_tables());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn solutions_never_turn_a_face_twice_in_a_row() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..10 {
+            let solution = solve(&CubieCube::random(&mut rng));
+            assert!(solution.windows(2).all(|pair| pair[0] / 3 != pair[1] / 3), "{:?}", solution);
+        }
+    }
+}
@@ -0,0 +1,186 @@
+// Smart cube move streams: following the cube through the scramble and timing the solve from its turns
+pub mod reconstruction;
+pub mod replay;
+
+pub use reconstruction::{Reconstruction, Step};
+pub use replay::ReplaySource;
+
+use crate::TimedMove;
+use crate::scramble::cube::{self, CubieCube};
+use crate::timing::{FinishedSolve, Timer};
+
+// Delivers the turns of a smart cube (GAN, GiiKER, MoYu, ...) or a recording of one
+pub trait MoveSource {
+    // Returns the next turn, timed from when the source started, None if none is waiting
+    fn next_move(&mut self) -> Option<TimedMove>;
+}
+
+// Follows a smart cube's state, starting the solve on the first turn after the scramble
+// is reached and stopping it once the cube is solved
+#[derive(Debug, Clone, Default)]
+pub struct SmartCube {
+    state: CubieCube,               // Tracked state of the cube, solved when the source starts
+    scrambled: Option<CubieCube>,   // State the scramble leads to, None if it cannot be followed
+    solve: Vec<TimedMove>,          // Turns of the solve in progress, timed from the source
+}
+
+impl SmartCube {
+    // Starts following a cube that is currently solved
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Sets the scramble to wait for, returning false if it has turns other than face turns
+    pub fn set_scramble(&mut self, scramble: &str) -> bool {
+        self.scrambled = CubieCube::from_moves(scramble);
+        self.scrambled.is_some()
+    }
+
+    // Returns true once the cube matches the scramble and the next turn starts the solve
+    pub fn is_scrambled(&self) -> bool {
+        self.solve.is_empty() && self.scrambled == Some(self.state)
+    }
+
+    // Returns true while a solve is being recorded
+    pub fn is_solving(&self) -> bool {
+        !self.solve.is_empty()
+    }
+
+    // Applies a turn, driving the timer, and returns the solve the turn finished
+    pub fn apply_move(&mut self, timer: &mut Timer, mv: TimedMove) -> Option<FinishedSolve> {
+        let index = cube::parse_move(&mv.turn)?;
+        let now = timer.now();
+
+        if self.is_scrambled() {
+            timer.sync_running(now, None);
+        } else if self.solve.is_empty() {
+            // Scrambling, or turning the cube between solves
+            self.state.apply_move(index);
+            return None;
+        }
+        self.state.apply_move(index);
+        self.solve.push(mv);
+
+        if !self.state.is_solved() {
+            return None;
+        }
+        let first = self.solve[0].at;
+        let moves: Vec<TimedMove> = std::mem::take(&mut self.solve)
+            .into_iter()
+            .map(|mv| TimedMove { at: mv.at.saturating_sub(first), ..mv })
+            .collect();
+        let time = moves.last().map(|mv| mv.at).unwrap_or_default();
+
+        // The cube's own timestamps are more precise than when the turns reached the app
+        let mut solve = timer.stop_external(now, time)?;
+        solve.moves = moves;
+        Some(solve)
+    }
+
+    // Applies every turn waiting in a source, returning the solves they finished
+    pub fn poll(&mut self, source: &mut dyn MoveSource, timer: &mut Timer) -> Vec<FinishedSolve> {
+        std::iter::from_fn(|| source.next_move())
+            .filter_map(|mv| self.apply_move(timer, mv))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timing::{ManualClock, TimerState};
+    use std::time::Duration;
+
+    const SCRAMBLE: &str = "F R' F' R U R U' R' F R U' R' U R U R' F' R U2 R' U' R U' R' L' U L U R U' R' F' R' D'";
+    const LOG: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/smart_cube/cfop_solve.txt"));
+
+    fn turn(turn: &str, millis: u64) -> TimedMove {
+        TimedMove { turn: turn.to_string(), at: Duration::from_millis(millis) }
+    }
+
+    #[test]
+    fn a_replayed_solve_is_timed_from_the_first_turn_after_the_scramble() {
+        let mut source = ReplaySource::from_log(LOG).unwrap();
+        let mut cube = SmartCube::new();
+        cube.set_scramble(SCRAMBLE);
+        let mut timer = Timer::new(ManualClock::new());
+
+        let solves = cube.poll(&mut source, &mut timer);
+        assert_eq!(solves.len(), 1);
+        assert_eq!(solves[0].moves.len(), 35);
+        assert_eq!(solves[0].moves[0], turn("D", 0));
+        assert_eq!(solves[0].time, solves[0].moves[34].at);
+        assert_eq!(timer.state, TimerState::Stopped);
+    }
+
+    #[test]
+    fn turns_that_miss_the_scramble_do_not_start_the_timer() {
+        let mut cube = SmartCube::new();
+        cube.set_scramble("R U");
+        let mut timer = Timer::new(ManualClock::new());
+
+        for (i, mv) in ["R", "U'", "U", "U", "U'"].into_iter().enumerate() {
+            cube.apply_move(&mut timer, turn(mv, i as u64 * 100));
+        }
+        // R U' U U reaches the scramble, only the turn after it starts the solve
+        assert!(cube.is_solving());
+        assert_eq!(timer.state, TimerState::Running);
+        let solve = cube.apply_move(&mut timer, turn("R'", 600)).unwrap();
+        assert_eq!(solve.time, Duration::from_millis(200));
+
+        let mut cube = SmartCube::new();
+        assert!(!cube.set_scramble("Rw U"));
+        cube.apply_move(&mut timer, turn("R", 0));
+        assert!(!cube.is_scrambled() && !cube.is_solving());
+    }
+
+    #[test]
+    fn a_replayed_solve_is_broken_into_cfop_steps() {
+        let mut cube = SmartCube::new();
+        cube.set_scramble(SCRAMBLE);
+        let mut timer = Timer::new(ManualClock::new());
+        let solve = cube.poll(&mut ReplaySource::from_log(LOG).unwrap(), &mut timer).remove(0);
+
+        let reconstruction = Reconstruction::analyze(SCRAMBLE, &solve.moves).unwrap();
+        let steps: Vec<(&str, String)> = reconstruction.steps.iter().map(|step| (step.name, step.moves.join(" "))).collect();
+        assert_eq!(steps, [
+            ("Cross", "D R F".to_string()),
+            ("F2L", "R U R' U' L' U' L".to_string()),
+            ("OLL", "R U R' U R U2 R'".to_string()),
+            ("PLL", "F R U' R' U' R U R' F' R U R' U' R' F R F'".to_string()),
+        ]);
+        assert_eq!(reconstruction.move_count(), 34);
+        assert_eq!(reconstruction.time, solve.time);
+        let durations: Duration = reconstruction.steps.iter().map(|step| step.duration).sum();
+        assert_eq!(durations, solve.time);
+        assert!((reconstruction.tps() - 34.0 / solve.time.as_secs_f64()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn crosses_are_found_on_every_face() {
+        let mut cube = SmartCube::new();
+        cube.set_scramble(SCRAMBLE);
+        let mut timer = Timer::new(ManualClock::new());
+        let solve = cube.poll(&mut ReplaySource::from_log(LOG).unwrap(), &mut timer).remove(0);
+        let expected = Reconstruction::analyze(SCRAMBLE, &solve.moves).unwrap();
+
+        // The same solve held with the cross on each face: faces renamed by a whole-cube rotation
+        for faces in ["UFLDBR", "DRBULF", "BRUFLD", "FRDBLU", "LUFRDB", "RFULBD"] {
+            let rename = |turns: &str| -> String {
+                turns.chars().map(|c| "URFDLB".find(c).map_or(c, |face| faces.as_bytes()[face] as char)).collect()
+            };
+            let moves: Vec<TimedMove> = solve.moves.iter().map(|mv| turn(&rename(&mv.turn), mv.at.as_millis() as u64)).collect();
+            let reconstruction = Reconstruction::analyze(&rename(SCRAMBLE), &moves).unwrap();
+            for (step, expected) in reconstruction.steps.iter().zip(&expected.steps) {
+                assert_eq!(step.moves.join(" "), rename(&expected.moves.join(" ")), "{} {}", faces, step.name);
+            }
+        }
+    }
+
+    #[test]
+    fn move_logs_must_be_timed_turns() {
+        assert!(ReplaySource::from_log("# comment\n0 R\n120 U2'\n").is_ok());
+        assert!(ReplaySource::from_log("R 0").is_err());
+        assert!(ReplaySource::from_log("0 M").is_err());
+    }
+}
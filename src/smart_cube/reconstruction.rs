@@ -0,0 +1,194 @@
+// CFOP breakdown, move count and TPS of a solve recorded from a smart cube
+use crate::TimedMove;
+use crate::scramble::cube::{self, CubieCube};
+use std::time::Duration;
+
+// CFOP steps in solving order
+const STEP_NAMES: [&str; 4] = ["Cross", "F2L", "OLL", "PLL"];
+
+// One CFOP step of a solve
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub name: &'static str, // Cross, F2L, OLL or PLL
+    pub moves: Vec<String>, // Turns of the step, consecutive turns of a face merged (R R becomes R2)
+    pub duration: Duration, // Time from the end of the previous step to the step's last turn
+}
+
+// A solve broken into CFOP steps
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reconstruction {
+    pub steps: Vec<Step>, // Cross, F2L, OLL and PLL, empty for skipped steps
+    pub time: Duration,   // Time from the first to the last turn
+}
+
+// Whole-cube rotations as cubie permutations: 120 degrees around the URF-DBL diagonal, 180
+// degrees around F and 90 degrees around U. Together they make up all 24 rotations.
+const ROTATION_GENERATORS: [CubieCube; 3] = [
+    CubieCube {
+        cp: [0, 4, 5, 1, 3, 7, 6, 2],
+        co: [1, 2, 1, 2, 2, 1, 2, 1],
+        ep: [1, 8, 5, 9, 3, 11, 7, 10, 0, 4, 6, 2],
+        eo: [1, 0, 1, 0, 1, 0, 1, 0, 1, 1, 1, 1],
+    },
+    CubieCube {
+        cp: [5, 4, 7, 6, 1, 0, 3, 2],
+        co: [0; 8],
+        ep: [6, 5, 4, 7, 2, 1, 0, 3, 9, 8, 11, 10],
+        eo: [0; 12],
+    },
+    CubieCube {
+        cp: [3, 0, 1, 2, 7, 4, 5, 6],
+        co: [0; 8],
+        ep: [3, 0, 1, 2, 7, 4, 5, 6, 11, 8, 9, 10],
+        eo: [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1],
+    },
+];
+
+// Lists every rotation of the cube, starting with holding it as it is
+fn rotations() -> Vec<CubieCube> {
+    let mut rotations = vec![CubieCube::default()];
+    let mut i = 0;
+    while i < rotations.len() {
+        for generator in &ROTATION_GENERATORS {
+            let mut next = rotations[i];
+            next.multiply(generator);
+            if !rotations.contains(&next) {
+                rotations.push(next);
+            }
+        }
+        i += 1;
+    }
+    rotations
+}
+
+// Way of holding the cube that brings the cross down. Smart cubes report turns by center color
+// without rotations, so the cross can be on any face.
+#[derive(Debug, Clone, Copy)]
+struct View {
+    rotation: CubieCube,
+    inverse: CubieCube,
+}
+
+impl View {
+    // Every way of holding the cube, the cube as reported first
+    fn all() -> Vec<View> {
+        let rotations = rotations();
+        rotations
+            .iter()
+            .map(|&rotation| {
+                let inverse = *rotations
+                    .iter()
+                    .find(|inverse| {
+                        let mut product = rotation;
+                        product.multiply(inverse);
+                        product.is_solved()
+                    })
+                    .unwrap_or(&rotation);
+                View { rotation, inverse }
+            })
+            .collect()
+    }
+
+    // The state as seen when holding the cube this way
+    fn look(self, state: &CubieCube) -> CubieCube {
+        let mut seen = self.inverse;
+        seen.multiply(state);
+        seen.multiply(&self.rotation);
+        seen
+    }
+
+    // Returns true if the step and every step before it are complete, with the cross on D
+    fn step_done(self, step: usize, state: &CubieCube) -> bool {
+        let state = self.look(state);
+        let edge = |i: usize| usize::from(state.ep[i]) == i && state.eo[i] == 0;
+        let corner = |i: usize| usize::from(state.cp[i]) == i && state.co[i] == 0;
+        match step {
+            0 => (4..8).all(edge),
+            1 => (4..8).all(edge) && (4..8).all(corner) && (8..12).all(edge),
+            2 => (4..12).all(edge) && (4..8).all(corner) && (0..4).all(|i| state.co[i] == 0 && state.eo[i] == 0),
+            _ => state.is_solved(),
+        }
+    }
+}
+
+impl Reconstruction {
+    // Breaks a solve into CFOP steps, None if the scramble or a turn is not a plain face turn
+    pub fn analyze(scramble: &str, moves: &[TimedMove]) -> Option<Self> {
+        let mut state = CubieCube::from_moves(scramble)?;
+        let turns: Vec<usize> = moves.iter().map(|mv| cube::parse_move(&mv.turn)).collect::<Option<_>>()?;
+
+        // Turns after which each step was first complete, steps never reached end with the solve
+        let mut ends = [turns.len(); 4];
+        let views = View::all();
+        let mut view = None;
+        let mut step = 0;
+        for count in 0..=turns.len() {
+            if count > 0 {
+                state.apply_move(turns[count - 1]);
+            }
+            view = view.or_else(|| views.iter().copied().find(|view| view.step_done(0, &state)));
+            if let Some(view) = view {
+                while step < ends.len() && view.step_done(step, &state) {
+                    ends[step] = count;
+                    step += 1;
+                }
+            }
+        }
+
+        // Time after the given number of turns, from the first turn
+        let first = moves.first().map(|mv| mv.at).unwrap_or_default();
+        let time_after = |count: usize| match count {
+            0 => Duration::ZERO,
+            count => moves[count - 1].at.saturating_sub(first),
+        };
+
+        let mut start = 0;
+        let steps = STEP_NAMES
+            .iter()
+            .zip(ends)
+            .map(|(&name, end)| {
+                let step = Step {
+                    name,
+                    moves: merge(&turns[start..end]),
+                    duration: time_after(end).saturating_sub(time_after(start)),
+                };
+                start = end;
+                step
+            })
+            .collect();
+        Some(Self { steps, time: time_after(turns.len()) })
+    }
+
+    // Number of moves in the half turn metric
+    pub fn move_count(&self) -> usize {
+        self.steps.iter().map(|step| step.moves.len()).sum()
+    }
+
+    // Turns per second over the whole solve
+    pub fn tps(&self) -> f64 {
+        if self.time.is_zero() {
+            0.0
+        } else {
+            self.move_count() as f64 / self.time.as_secs_f64()
+        }
+    }
+}
+
+// Writes turns in standard notation, merging consecutive turns of the same face
+fn merge(turns: &[usize]) -> Vec<String> {
+    // Face and clockwise quarter turns of each merged move
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for &turn in turns {
+        let (face, quarters) = (turn / 3, turn % 3 + 1);
+        match merged.last_mut() {
+            Some((last, total)) if *last == face => {
+                *total = (*total + quarters) % 4;
+                if *total == 0 {
+                    merged.pop();
+                }
+            }
+            _ => merged.push((face, quarters)),
+        }
+    }
+    merged.into_iter().map(|(face, quarters)| cube::format_move(face * 3 + quarters - 1)).collect()
+}
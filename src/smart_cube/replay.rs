@@ -0,0 +1,48 @@
+// Move source that replays a recorded move log
+use super::MoveSource;
+use crate::TimedMove;
+use crate::scramble::cube;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::time::Duration;
+
+// Replays turns from a log with one "<milliseconds> <turn>" pair per line, '#' starting a comment
+#[derive(Debug, Clone, Default)]
+pub struct ReplaySource {
+    moves: VecDeque<TimedMove>, // Turns not replayed yet
+}
+
+impl ReplaySource {
+    // Parses a move log
+    pub fn from_log(text: &str) -> Result<Self, String> {
+        let mut moves = VecDeque::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let invalid = || format!("Line {}: expected \"<milliseconds> <turn>\", found \"{}\"", number + 1, line);
+            let (millis, turn) = line.split_once(char::is_whitespace).ok_or_else(invalid)?;
+            let millis: u64 = millis.parse().map_err(|_| invalid())?;
+            let turn = turn.trim();
+            if cube::parse_move(turn).is_none() {
+                return Err(format!("Line {}: unknown turn \"{}\"", number + 1, turn));
+            }
+            moves.push_back(TimedMove { turn: turn.to_string(), at: Duration::from_millis(millis) });
+        }
+        Ok(Self { moves })
+    }
+
+    // Reads a move log from disk
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        Self::from_log(&text)
+    }
+}
+
+impl MoveSource for ReplaySource {
+    fn next_move(&mut self) -> Option<TimedMove> {
+        self.moves.pop_front()
+    }
+}
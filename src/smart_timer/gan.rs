@@ -0,0 +1,45 @@
+// GAN Smart Timer packets: 0xFE, the length, the event type, the state, then for stopped
+// solves the minutes, seconds and milliseconds (little-endian u16), and finally a
+// CRC-16/CCITT-FALSE of everything after the length, little-endian
+use super::SmartTimerEvent;
+use std::time::Duration;
+
+const MAGIC: u8 = 0xFE;
+
+// Parses one notification
+pub fn parse(packet: &[u8]) -> Option<SmartTimerEvent> {
+    if packet.len() < 6 || packet[0] != MAGIC {
+        return None;
+    }
+    let (body, crc) = packet.split_at(packet.len() - 2);
+    if u16::from_le_bytes([crc[0], crc[1]]) != crc16_ccitt(&body[2..]) {
+        return None;
+    }
+
+    match body[3] {
+        0 => Some(SmartTimerEvent::Disconnected),
+        1 => Some(SmartTimerEvent::GetSet),
+        2 => Some(SmartTimerEvent::HandsOff),
+        3 => Some(SmartTimerEvent::Running(None)),
+        4 => {
+            let time = body.get(4..8)?;
+            let millis = u64::from(time[0]) * 60_000
+                + u64::from(time[1]) * 1000
+                + u64::from(u16::from_le_bytes([time[2], time[3]]));
+            Some(SmartTimerEvent::Stopped(Duration::from_millis(millis)))
+        }
+        // 7 is sent when the display goes back to zero after a stopped time
+        5 | 7 => Some(SmartTimerEvent::Idle),
+        6 => Some(SmartTimerEvent::HandsOn),
+        _ => None,
+    }
+}
+
+// CRC-16/CCITT-FALSE: polynomial 0x1021, initial value 0xFFFF, not reflected
+fn crc16_ccitt(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0xFFFF, |crc, &byte| {
+        (0..8).fold(crc ^ (u16::from(byte) << 8), |crc, _| {
+            if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 }
+        })
+    })
+}
@@ -0,0 +1,166 @@
+// Protocol layer for Bluetooth smart timers, independent of how their packets arrive
+mod gan;
+mod qiyi;
+pub mod transport;
+
+pub use transport::{MockTransport, Transport};
+
+use crate::timing::{FinishedSolve, Timer};
+use std::time::Duration;
+
+// State changes a smart timer reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmartTimerEvent {
+    Idle,                      // Reset and waiting for hands
+    HandsOn,                   // Hands on the pads, not held long enough yet
+    GetSet,                    // Held long enough, lifting the hands starts the solve
+    HandsOff,                  // Hands lifted before the timer was ready
+    Running(Option<Duration>), // Solve running, with the elapsed time if the timer sends it
+    Stopped(Duration),         // Solve stopped, with the timer's own time
+    Disconnected,              // Timer switched off
+}
+
+// Packet formats of the supported timers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    Gan,  // GAN Smart Timer and GAN Halo
+    QiYi, // QiYi Smart Timer
+}
+
+impl Protocol {
+    // Parses one notification, None for damaged packets and ones that carry no state
+    pub fn parse(self, packet: &[u8]) -> Option<SmartTimerEvent> {
+        match self {
+            Protocol::Gan => gan::parse(packet),
+            Protocol::QiYi => qiyi::parse(packet),
+        }
+    }
+}
+
+impl std::fmt::Display for Protocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Protocol::Gan => write!(f, "GAN Smart Timer"),
+            Protocol::QiYi => write!(f, "QiYi Smart Timer"),
+        }
+    }
+}
+
+// Follows a smart timer's events with the app's timer, returning the solve when it stops.
+// Inspection started from the keyboard keeps running until the solve starts
+pub fn apply_event(timer: &mut Timer, event: SmartTimerEvent) -> Option<FinishedSolve> {
+    let now = timer.now();
+
+    match event {
+        SmartTimerEvent::HandsOn => timer.hands_on(now, false),
+        SmartTimerEvent::GetSet => timer.hands_on(now, true),
+        SmartTimerEvent::Running(elapsed) => timer.sync_running(now, elapsed),
+        SmartTimerEvent::Stopped(time) => return timer.stop_external(now, time),
+        SmartTimerEvent::Idle | SmartTimerEvent::HandsOff | SmartTimerEvent::Disconnected => timer.hands_off(),
+    }
+    None
+}
+
+// A smart timer whose notifications arrive through a transport
+pub struct SmartTimer<T: Transport> {
+    protocol: Protocol, // Packet format of the timer
+    transport: T,       // Source of the timer's notifications
+}
+
+impl<T: Transport> SmartTimer<T> {
+    // Creates a smart timer reading packets of the given format
+    pub fn new(protocol: Protocol, transport: T) -> Self {
+        Self { protocol, transport }
+    }
+
+    // Handles the packets received since the last poll, returning the solves they finished
+    pub fn poll(&mut self, timer: &mut Timer) -> Vec<FinishedSolve> {
+        let mut solves = Vec::new();
+        while let Some(packet) = self.transport.receive() {
+            if let Some(event) = self.protocol.parse(&packet) {
+                solves.extend(apply_event(timer, event));
+            }
+        }
+        solves
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timing::{ManualClock, TimerState};
+
+    macro_rules! capture {
+        ($name:literal) => {
+            MockTransport::from_capture(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/smart_timer/", $name))).unwrap()
+        };
+    }
+
+    fn events(protocol: Protocol, mut transport: MockTransport) -> Vec<SmartTimerEvent> {
+        std::iter::from_fn(|| transport.receive()).filter_map(|packet| protocol.parse(&packet)).collect()
+    }
+
+    #[test]
+    fn gan_packets_are_parsed() {
+        use SmartTimerEvent::*;
+        assert_eq!(
+            events(Protocol::Gan, capture!("gan_solve.txt")),
+            [Idle, HandsOn, GetSet, Running(None), Stopped(Duration::from_millis(62_345)), Idle, Idle]
+        );
+        // The stopped packet fails its checksum and the last one is cut short
+        assert_eq!(events(Protocol::Gan, capture!("gan_corrupt.txt")), [HandsOn, GetSet, Running(None)]);
+    }
+
+    #[test]
+    fn a_replayed_gan_solve_is_recorded() {
+        let clock = ManualClock::new();
+        let mut timer = Timer::new(clock.clone());
+        let mut transport = capture!("gan_solve.txt");
+
+        // Step through the capture to watch the timer follow it
+        let mut step = |timer: &mut Timer| {
+            let packet = transport.receive().unwrap();
+            apply_event(timer, Protocol::Gan.parse(&packet).unwrap())
+        };
+        step(&mut timer);
+        step(&mut timer);
+        assert_eq!(timer.state, TimerState::Preparing);
+        step(&mut timer);
+        assert!(timer.is_held_long_enough());
+        step(&mut timer);
+        assert_eq!(timer.state, TimerState::Running);
+
+        clock.advance(Duration::from_secs(62));
+        let solve = step(&mut timer).unwrap();
+        assert_eq!(solve.time, Duration::from_millis(62_345));
+        assert_eq!(timer.state, TimerState::Stopped);
+        step(&mut timer);
+        assert_eq!(timer.state, TimerState::Ready);
+    }
+
+    #[test]
+    fn damaged_packets_do_not_stop_the_solve() {
+        let mut timer = Timer::new(ManualClock::new());
+        let solves = SmartTimer::new(Protocol::Gan, capture!("gan_corrupt.txt")).poll(&mut timer);
+        assert!(solves.is_empty());
+        assert_eq!(timer.state, TimerState::Running);
+    }
+
+    #[test]
+    fn a_replayed_qiyi_solve_is_recorded() {
+        let clock = ManualClock::new();
+        let mut timer = Timer::new(clock.clone());
+        let solves = SmartTimer::new(Protocol::QiYi, capture!("qiyi_solve.txt")).poll(&mut timer);
+        assert_eq!(solves.len(), 1);
+        assert_eq!(solves[0].time, Duration::from_millis(8_765));
+        assert_eq!(timer.last_time, Some(Duration::from_millis(8_765)));
+        assert_eq!(timer.state, TimerState::Ready);
+    }
+
+    #[test]
+    fn captures_must_be_hex() {
+        assert!(MockTransport::from_capture("fe 06 01 05 9b 7e\n# comment\n\n").is_ok());
+        assert!(MockTransport::from_capture("fe 0g").is_err());
+        assert!(MockTransport::from_capture("fe0").is_err());
+    }
+}
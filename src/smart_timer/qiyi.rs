@@ -0,0 +1,42 @@
+// QiYi Smart Timer packets: 0xFE, the length, a sequence number, the command (0x1003 for
+// state changes), the state and the time in milliseconds, all big-endian, and finally a
+// CRC-16/MODBUS of everything before it, little-endian
+use super::SmartTimerEvent;
+use std::time::Duration;
+
+const MAGIC: u8 = 0xFE;
+const STATE_CHANGED: u16 = 0x1003;
+
+// Parses one notification
+pub fn parse(packet: &[u8]) -> Option<SmartTimerEvent> {
+    if packet.len() < 15 || packet[0] != MAGIC || usize::from(packet[1]) != packet.len() {
+        return None;
+    }
+    let (body, crc) = packet.split_at(packet.len() - 2);
+    if u16::from_le_bytes([crc[0], crc[1]]) != crc16_modbus(body) {
+        return None;
+    }
+    if u16::from_be_bytes([body[6], body[7]]) != STATE_CHANGED {
+        return None;
+    }
+
+    let time = Duration::from_millis(u64::from(u32::from_be_bytes([body[9], body[10], body[11], body[12]])));
+    match body[8] {
+        0 => Some(SmartTimerEvent::Idle),
+        1 => Some(SmartTimerEvent::HandsOn),
+        2 => Some(SmartTimerEvent::GetSet),
+        3 => Some(SmartTimerEvent::Running(Some(time))),
+        4 => Some(SmartTimerEvent::Stopped(time)),
+        5 => Some(SmartTimerEvent::HandsOff),
+        _ => None,
+    }
+}
+
+// CRC-16/MODBUS: polynomial 0x8005 reflected, initial value 0xFFFF
+fn crc16_modbus(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0xFFFF, |crc, &byte| {
+        (0..8).fold(crc ^ u16::from(byte), |crc, _| {
+            if crc & 1 != 0 { (crc >> 1) ^ 0xA001 } else { crc >> 1 }
+        })
+    })
+}
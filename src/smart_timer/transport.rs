@@ -0,0 +1,54 @@
+// Sources of smart timer notifications
+use std::collections::VecDeque;
+
+// Delivers the packets a smart timer sends, e.g. from a Bluetooth characteristic
+pub trait Transport {
+    // Returns the next packet received, None if none is waiting
+    fn receive(&mut self) -> Option<Vec<u8>>;
+}
+
+// Replays recorded packets, for testing without a timer
+#[derive(Debug, Clone, Default)]
+pub struct MockTransport {
+    packets: VecDeque<Vec<u8>>, // Packets not replayed yet
+}
+
+impl MockTransport {
+    // Replays the given packets in order
+    pub fn new(packets: Vec<Vec<u8>>) -> Self {
+        Self { packets: packets.into() }
+    }
+
+    // Parses a capture with one packet per line as hex bytes, '#' starting a comment
+    pub fn from_capture(text: &str) -> Result<Self, String> {
+        let mut packets = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let hex: String = line
+                .split('#')
+                .next()
+                .unwrap_or_default()
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect();
+            if hex.is_empty() {
+                continue;
+            }
+            if !hex.chars().all(|c| c.is_ascii_hexdigit()) || !hex.len().is_multiple_of(2) {
+                return Err(format!("Line {}: not a hex byte sequence", number + 1));
+            }
+
+            let packet = (0..hex.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap_or_default())
+                .collect();
+            packets.push(packet);
+        }
+        Ok(Self::new(packets))
+    }
+}
+
+impl Transport for MockTransport {
+    fn receive(&mut self) -> Option<Vec<u8>> {
+        self.packets.pop_front()
+    }
+}
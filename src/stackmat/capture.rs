@@ -0,0 +1,69 @@
+// Live Stackmat input from the default audio input device
+use super::{StackmatDecoder, StackmatPacket};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, Sample, SampleFormat, SizedSample};
+use std::sync::mpsc::{self, Receiver, Sender};
+
+// A running capture that decodes the input device on the audio thread
+pub struct StackmatCapture {
+    _stream: cpal::Stream,                // Input stream, stopped when dropped
+    packets: Receiver<StackmatPacket>,    // Packets decoded since the last poll
+    pub device_name: String,              // Name of the device being listened to
+}
+
+impl StackmatCapture {
+    // Starts listening to the default input device
+    pub fn start() -> Result<Self, String> {
+        let device = cpal::default_host()
+            .default_input_device()
+            .ok_or("No audio input device found")?;
+        let config = device
+            .default_input_config()
+            .map_err(|e| format!("Could not read the input device's format: {}", e))?;
+        let device_name = device.name().unwrap_or_else(|_| "Unknown device".to_string());
+        let (sender, packets) = mpsc::channel();
+
+        let stream = match config.sample_format() {
+            SampleFormat::F32 => build_stream::<f32>(&device, &config.config(), sender),
+            SampleFormat::I16 => build_stream::<i16>(&device, &config.config(), sender),
+            SampleFormat::U16 => build_stream::<u16>(&device, &config.config(), sender),
+            SampleFormat::I32 => build_stream::<i32>(&device, &config.config(), sender),
+            format => return Err(format!("Unsupported input sample format {}", format)),
+        }?;
+        stream.play().map_err(|e| format!("Could not start the audio input: {}", e))?;
+
+        Ok(Self { _stream: stream, packets, device_name })
+    }
+
+    // Takes the packets decoded since the last call
+    pub fn poll(&self) -> Vec<StackmatPacket> {
+        self.packets.try_iter().collect()
+    }
+}
+
+// Builds an input stream that decodes the first channel and sends the packets to the app
+fn build_stream<T>(device: &cpal::Device, config: &cpal::StreamConfig, sender: Sender<StackmatPacket>) -> Result<cpal::Stream, String>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = usize::from(config.channels);
+    let mut decoder = StackmatDecoder::new(config.sample_rate.0);
+    let mut samples = Vec::new();
+
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                samples.clear();
+                samples.extend(data.iter().step_by(channels).map(|&sample| f32::from_sample(sample)));
+                for packet in decoder.decode(&samples) {
+                    // The app has stopped listening once the receiver is gone
+                    let _ = sender.send(packet);
+                }
+            },
+            |error| eprintln!("Stackmat input error: {}", error),
+            None,
+        )
+        .map_err(|e| format!("Could not open the audio input: {}", e))
+}
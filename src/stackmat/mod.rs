@@ -0,0 +1,264 @@
+// Decoder for the signal Speedstacks Stackmat timers send over their headphone jack
+#[cfg(feature = "stackmat-audio")]
+pub mod capture;
+pub mod wav;
+
+use crate::timing::{FinishedSolve, Timer};
+use std::time::Duration;
+
+// Stackmats send 8N1 serial data at 1200 baud
+const BAUD_RATE: f32 = 1200.0;
+// Idle bits that end a packet, longer than any run of marks inside one
+const GAP_BITS: f32 = 20.0;
+// Samples closer to zero than this keep the previous level, so noise does not flip it
+const HYSTERESIS: f32 = 0.05;
+
+// What the Stackmat reports its pads and display are doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StackmatState {
+    Reset,     // 'I': display reset to zero
+    BothHands, // 'C': both hands on the pads, not held long enough yet
+    Ready,     // 'A': green light, lifting the hands starts the solve
+    LeftHand,  // 'L': only the left hand is on the pads
+    RightHand, // 'R': only the right hand is on the pads
+    Running,   // ' ': timing a solve
+    Stopped,   // 'S': stopped, showing the final time
+}
+
+impl StackmatState {
+    // Maps the status character that starts a packet
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            b'I' => Some(StackmatState::Reset),
+            b'C' => Some(StackmatState::BothHands),
+            b'A' => Some(StackmatState::Ready),
+            b'L' => Some(StackmatState::LeftHand),
+            b'R' => Some(StackmatState::RightHand),
+            b' ' => Some(StackmatState::Running),
+            b'S' => Some(StackmatState::Stopped),
+            _ => None,
+        }
+    }
+}
+
+// One packet of the display: state, time digits and checksum
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackmatPacket {
+    pub state: StackmatState, // State of the pads and display
+    pub digits: Vec<u8>,      // Minutes, seconds and fraction digits (5, or 6 on 4th generation timers)
+    pub checksum: u8,         // 64 plus the sum of the digits
+}
+
+impl StackmatPacket {
+    // Parses the bytes of a packet, rejecting ones that are malformed or fail the checksum
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let (&status, rest) = bytes.split_first()?;
+        let body = rest.strip_suffix(b"\n\r")?;
+        let (&checksum, digits) = body.split_last()?;
+        if !(digits.len() == 5 || digits.len() == 6) || !digits.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+
+        let digits: Vec<u8> = digits.iter().map(|d| d - b'0').collect();
+        let sum: u32 = digits.iter().map(|&d| u32::from(d)).sum();
+        if u32::from(checksum) != 64 + sum {
+            return None;
+        }
+
+        Some(Self {
+            state: StackmatState::from_byte(status)?,
+            digits,
+            checksum,
+        })
+    }
+
+    // Time shown on the display
+    pub fn time(&self) -> Duration {
+        let d = |i: usize| u64::from(self.digits.get(i).copied().unwrap_or(0));
+        let millis = d(0) * 60_000 + (d(1) * 10 + d(2)) * 1000 + d(3) * 100 + d(4) * 10 + d(5);
+        Duration::from_millis(millis)
+    }
+}
+
+// Turns PCM samples into Stackmat packets. The line polarity depends on the sound card,
+// so the level held between packets is taken as idle
+pub struct StackmatDecoder {
+    samples_per_bit: f32,  // Length of one bit in samples
+    level: Option<bool>,   // Current line level, true when positive
+    run: usize,            // Samples since the level last changed
+    idle: Option<bool>,    // Level between packets, once a gap has been seen
+    bits: Vec<bool>,       // Bits of the packet being received, true for idle (mark)
+}
+
+impl StackmatDecoder {
+    // Creates a decoder for samples recorded at the given rate
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            samples_per_bit: sample_rate as f32 / BAUD_RATE,
+            level: None,
+            run: 0,
+            idle: None,
+            bits: Vec::new(),
+        }
+    }
+
+    // Decodes the next samples of a mono signal, returning the packets they completed
+    pub fn decode(&mut self, samples: &[f32]) -> Vec<StackmatPacket> {
+        let gap = (self.samples_per_bit * GAP_BITS) as usize;
+        let mut packets = Vec::new();
+
+        for &sample in samples {
+            let level = if sample > HYSTERESIS {
+                true
+            } else if sample < -HYSTERESIS {
+                false
+            } else {
+                self.level.unwrap_or(false)
+            };
+
+            if self.level == Some(level) {
+                self.run += 1;
+                // A long enough idle run ends the packet without waiting for the next one
+                if self.run == gap {
+                    match self.idle {
+                        Some(idle) if idle == level => packets.extend(self.finish_packet()),
+                        Some(_) => self.bits.clear(),
+                        None => self.idle = Some(level),
+                    }
+                }
+                continue;
+            }
+
+            if let (Some(previous), Some(idle)) = (self.level, self.idle)
+                && self.run < gap
+            {
+                let count = (self.run as f32 / self.samples_per_bit).round() as usize;
+                self.bits.extend(std::iter::repeat_n(previous == idle, count.max(1)));
+            }
+            self.level = Some(level);
+            self.run = 1;
+        }
+        packets
+    }
+
+    // Reads the bytes out of the received bits and parses them as a packet
+    fn finish_packet(&mut self) -> Option<StackmatPacket> {
+        let bits = std::mem::take(&mut self.bits);
+        let mut bytes = Vec::new();
+        let mut position = 0;
+
+        while position < bits.len() {
+            // Skip idle bits up to the next start bit
+            if bits[position] {
+                position += 1;
+                continue;
+            }
+            let data = bits.get(position + 1..position + 9)?;
+            bytes.push(data.iter().rev().fold(0u8, |byte, &bit| (byte << 1) | u8::from(bit)));
+            // The stop bit of the last byte merges into the gap
+            if bits.get(position + 9) == Some(&false) {
+                return None;
+            }
+            position += 10;
+        }
+        StackmatPacket::parse(&bytes)
+    }
+}
+
+// Follows a Stackmat with the app's timer, returning the solve when the Stackmat stops.
+// Inspection started from the keyboard keeps running until the solve starts
+pub fn apply_packet(timer: &mut Timer, packet: &StackmatPacket) -> Option<FinishedSolve> {
+    let now = timer.now();
+
+    match packet.state {
+        StackmatState::Running => timer.sync_running(now, Some(packet.time())),
+        StackmatState::Stopped => return timer.stop_external(now, packet.time()),
+        StackmatState::BothHands => timer.hands_on(now, false),
+        StackmatState::Ready => timer.hands_on(now, true),
+        StackmatState::Reset | StackmatState::LeftHand | StackmatState::RightHand => timer.hands_off(),
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timing::{ManualClock, TimerState};
+
+    macro_rules! fixture {
+        ($name:literal) => {
+            include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/stackmat/", $name))
+        };
+    }
+
+    fn packet(state: StackmatState, digits: &[u8]) -> StackmatPacket {
+        let checksum = 64 + digits.iter().sum::<u8>();
+        StackmatPacket { state, digits: digits.to_vec(), checksum }
+    }
+
+    #[test]
+    fn packets_are_parsed_and_checked() {
+        let gen3 = StackmatPacket::parse(b"S01234J\n\r").unwrap();
+        assert_eq!(gen3.state, StackmatState::Stopped);
+        assert_eq!(gen3.time(), Duration::from_millis(12_340));
+
+        let gen4 = StackmatPacket::parse(b" 102503K\n\r").unwrap();
+        assert_eq!(gen4.state, StackmatState::Running);
+        assert_eq!(gen4.time(), Duration::from_millis(62_503));
+
+        assert_eq!(StackmatPacket::parse(b"S01234K\n\r"), None);
+        assert_eq!(StackmatPacket::parse(b"X01234J\n\r"), None);
+        assert_eq!(StackmatPacket::parse(b"S01234J"), None);
+    }
+
+    #[test]
+    fn a_recorded_solve_is_decoded() {
+        let wav = wav::read(fixture!("gen3_solve.wav")).unwrap();
+        let mut decoder = StackmatDecoder::new(wav.sample_rate);
+        // Feed the recording in chunks like an audio callback would
+        let packets: Vec<StackmatPacket> = wav.samples.chunks(512).flat_map(|chunk| decoder.decode(chunk)).collect();
+
+        let states: Vec<StackmatState> = packets.iter().map(|p| p.state).collect();
+        use StackmatState::*;
+        assert_eq!(states, [Reset, Reset, BothHands, Ready, Running, Running, Running, Running, Stopped, Stopped, Reset]);
+        assert_eq!(packets[6].time(), Duration::from_millis(8_760));
+        assert_eq!(packets[8].time(), Duration::from_millis(12_340));
+    }
+
+    #[test]
+    fn fourth_generation_packets_keep_milliseconds() {
+        let wav = wav::read(fixture!("gen4_stopped.wav")).unwrap();
+        assert_eq!(wav.sample_rate, 44_100);
+        let packets = StackmatDecoder::new(wav.sample_rate).decode(&wav.samples);
+        let times: Vec<Duration> = packets.iter().map(StackmatPacket::time).collect();
+        assert_eq!(times, [62_497, 62_503, 62_503].map(Duration::from_millis));
+    }
+
+    #[test]
+    fn packets_drive_the_timer() {
+        let clock = ManualClock::new();
+        let mut timer = Timer::new(clock.clone());
+
+        apply_packet(&mut timer, &packet(StackmatState::BothHands, &[0, 0, 0, 0, 0]));
+        assert_eq!(timer.state, TimerState::Preparing);
+        assert!(!timer.is_held_long_enough());
+        apply_packet(&mut timer, &packet(StackmatState::Ready, &[0, 0, 0, 0, 0]));
+        assert!(timer.is_held_long_enough());
+
+        apply_packet(&mut timer, &packet(StackmatState::Running, &[0, 0, 0, 0, 0]));
+        assert_eq!(timer.state, TimerState::Running);
+        clock.advance(Duration::from_secs(5));
+        apply_packet(&mut timer, &packet(StackmatState::Running, &[0, 0, 4, 9, 8]));
+        assert_eq!(timer.current_time, Duration::from_millis(4_980));
+
+        clock.advance(Duration::from_secs(7));
+        let solve = apply_packet(&mut timer, &packet(StackmatState::Stopped, &[0, 1, 2, 3, 4]));
+        assert_eq!(solve.map(|s| s.time), Some(Duration::from_millis(12_340)));
+        assert_eq!(timer.state, TimerState::Stopped);
+
+        // Repeated stopped packets do not record the solve again
+        assert_eq!(apply_packet(&mut timer, &packet(StackmatState::Stopped, &[0, 1, 2, 3, 4])), None);
+        apply_packet(&mut timer, &packet(StackmatState::Reset, &[0, 0, 0, 0, 0]));
+        assert_eq!(timer.state, TimerState::Ready);
+    }
+}
@@ -0,0 +1,99 @@
+// Minimal reader for WAV recordings of a Stackmat signal
+use std::path::Path;
+
+// The first channel of a WAV file, scaled to -1.0..1.0
+#[derive(Debug, Clone)]
+pub struct Wav {
+    pub sample_rate: u32,  // Samples per second
+    pub samples: Vec<f32>, // Samples of the first channel
+}
+
+// Sample encodings the reader understands
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Encoding {
+    Pcm,   // Integer samples, 8-bit unsigned or 16/24/32-bit signed
+    Float, // 32-bit IEEE floats
+}
+
+// Reads a WAV file from disk
+pub fn read_file(path: &Path) -> Result<Wav, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    read(&bytes)
+}
+
+// Parses the contents of a PCM or float WAV file
+pub fn read(bytes: &[u8]) -> Result<Wav, String> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err("Not a WAV file".to_string());
+    }
+
+    let mut format = None;
+    let mut position = 12;
+    while position + 8 <= bytes.len() {
+        let id = &bytes[position..position + 4];
+        let size = u32_at(bytes, position + 4) as usize;
+        // Recordings that were cut off keep whatever data they have
+        let body = &bytes[position + 8..(position + 8 + size).min(bytes.len())];
+
+        match id {
+            b"fmt " => format = Some(parse_format(body)?),
+            b"data" => {
+                let (encoding, channels, sample_rate, bits) = format.ok_or("WAV data comes before its format")?;
+                let frame = usize::from(channels) * usize::from(bits / 8);
+                let samples = body
+                    .chunks_exact(frame)
+                    .map(|frame| sample(&frame[..usize::from(bits / 8)], encoding))
+                    .collect();
+                return Ok(Wav { sample_rate, samples });
+            }
+            _ => {}
+        }
+        // Chunks are padded to an even length
+        position += 8 + size + size % 2;
+    }
+    Err("WAV file has no data".to_string())
+}
+
+// Reads the encoding, channel count, sample rate and bits per sample of a fmt chunk
+fn parse_format(body: &[u8]) -> Result<(Encoding, u16, u32, u16), String> {
+    if body.len() < 16 {
+        return Err("WAV format is truncated".to_string());
+    }
+    let mut tag = u16_at(body, 0);
+    // WAVE_FORMAT_EXTENSIBLE keeps the real tag at the start of its sub-format GUID
+    if tag == 0xFFFE && body.len() >= 26 {
+        tag = u16_at(body, 24);
+    }
+    let channels = u16_at(body, 2);
+    let sample_rate = u32_at(body, 4);
+    let bits = u16_at(body, 14);
+
+    let encoding = match (tag, bits) {
+        (1, 8 | 16 | 24 | 32) => Encoding::Pcm,
+        (3, 32) => Encoding::Float,
+        _ => return Err(format!("Unsupported WAV encoding (format {}, {} bits)", tag, bits)),
+    };
+    if channels == 0 || sample_rate == 0 {
+        return Err("WAV format has no channels".to_string());
+    }
+    Ok((encoding, channels, sample_rate, bits))
+}
+
+// Scales one little-endian sample to -1.0..1.0
+fn sample(bytes: &[u8], encoding: Encoding) -> f32 {
+    match (encoding, bytes.len()) {
+        (Encoding::Float, _) => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        (Encoding::Pcm, 1) => (f32::from(bytes[0]) - 128.0) / 128.0,
+        (Encoding::Pcm, 2) => f32::from(i16::from_le_bytes([bytes[0], bytes[1]])) / 32_768.0,
+        (Encoding::Pcm, 3) => (i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8) as f32 / 8_388_608.0,
+        (Encoding::Pcm, _) => i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32 / 2_147_483_648.0,
+    }
+}
+
+fn u16_at(bytes: &[u8], position: usize) -> u16 {
+    u16::from_le_bytes([bytes[position], bytes[position + 1]])
+}
+
+fn u32_at(bytes: &[u8], position: usize) -> u32 {
+    u32::from_le_bytes([bytes[position], bytes[position + 1], bytes[position + 2], bytes[position + 3]])
+}
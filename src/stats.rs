@@ -0,0 +1,449 @@
+// Statistics over solve results: stat columns, trimmed averages and personal bests
+use crate::{CubeEvent, Penalty, SolveResult, StandardEvent, TimeRecord};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// How a stat column combines its attempts
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum StatKind {
+    Average, // Trimmed average, e.g. Ao5
+    Mean,    // Plain mean of every attempt, e.g. Mo3
+}
+
+// A rolling statistic shown in the quick-stat cards, side-panel chips and graph
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StatColumn {
+    pub kind: StatKind,    // Average or mean
+    pub size: usize,       // Number of attempts covered
+    pub trim_percent: u32, // Share of attempts trimmed from each end of an average, rounded up
+}
+
+impl StatColumn {
+    // Creates a WCA-style average, trimming 5% (at least one attempt) from each end
+    pub fn average(size: usize) -> Self {
+        Self { kind: StatKind::Average, size, trim_percent: 5 }
+    }
+
+    // Creates a mean of `size` attempts
+    pub fn mean(size: usize) -> Self {
+        Self { kind: StatKind::Mean, size, trim_percent: 0 }
+    }
+
+    // Default columns for an event (6x6, 7x7 and BLD are officially Mo3 events)
+    pub fn defaults_for(event: &CubeEvent) -> Vec<Self> {
+        let mo3_event = matches!(
+            event,
+            CubeEvent::Standard(StandardEvent::Cube6x6 | StandardEvent::Cube7x7 | StandardEvent::Blindfolded)
+        );
+        let mut columns: Vec<Self> = [5, 12, 50, 100, 1000].into_iter().map(Self::average).collect();
+        if mo3_event {
+            columns.insert(0, Self::mean(3));
+            columns.pop();
+        }
+        columns
+    }
+
+    // Short label such as Ao5, Mo3 or Ao50 (10%) for a non-standard trim
+    pub fn label(&self) -> String {
+        match self.kind {
+            StatKind::Mean => format!("Mo{}", self.size),
+            StatKind::Average if self.trim_percent == 5 => format!("Ao{}", self.size),
+            StatKind::Average => format!("Ao{} ({}%)", self.size, self.trim_percent),
+        }
+    }
+
+    // Number of attempts removed from each end
+    pub fn trim_count(&self) -> usize {
+        match self.kind {
+            StatKind::Average => (self.size * self.trim_percent as usize).div_ceil(100),
+            StatKind::Mean => 0,
+        }
+    }
+
+    // Calculates the statistic over exactly `size` attempts
+    pub fn calculate(&self, results: &[SolveResult]) -> Option<SolveResult> {
+        if results.len() != self.size {
+            return None;
+        }
+        let mut sorted = results.to_vec();
+        sorted.sort();
+        self.calculate_sorted(&sorted)
+    }
+
+    // Calculates the statistic over attempts that are already sorted
+    pub fn calculate_sorted(&self, sorted: &[SolveResult]) -> Option<SolveResult> {
+        trimmed_mean(sorted, self.trim_count())
+    }
+}
+
+// Best rolling value of one stat column over an event's whole history
+#[derive(Debug, Clone)]
+pub struct PersonalBest {
+    pub column: StatColumn,         // Statistic this is the best of
+    pub average: Duration,          // Best average found (DNF averages never count)
+    pub first_solve: usize,         // 1-based number of the first covered solve in the event
+    pub record_indices: Vec<usize>, // Indices into the records of the covered solves
+}
+
+// Aggregates statistical data for solves
+#[derive(Debug, Clone, Default)]
+pub struct Statistics {
+    pub best: Option<Duration>,            // Fastest finished solve
+    pub worst: Option<SolveResult>,        // Slowest result, DNF if any solve was a DNF
+    pub current: Vec<(StatColumn, Option<SolveResult>)>, // Latest value of each stat column
+    pub rolling: Vec<(StatColumn, Vec<Option<SolveResult>>)>, // Value of each stat column after every solve
+    pub mean: Option<Duration>,            // Mean of all finished solves
+    pub personal_bests: Vec<PersonalBest>, // Best rolling averages of the event
+    pub phases: Vec<PhaseStatistics>,      // Averages of each phase of split solves
+}
+
+// Averages of one phase over the split solves
+#[derive(Debug, Clone)]
+pub struct PhaseStatistics {
+    pub name: String,                                    // Name of the phase, e.g. F2L
+    pub mean: Option<Duration>,                          // Mean time of the phase
+    pub current: Vec<(StatColumn, Option<SolveResult>)>, // Latest value of each stat column for the phase
+}
+
+impl Statistics {
+    // Updates best, worst, mean and the latest and rolling values of each stat column
+    pub fn calculate(&mut self, results: &[SolveResult], columns: Vec<StatColumn>) {
+        let finished: Vec<Duration> = results
+            .iter()
+            .filter_map(|r| match r {
+                SolveResult::Time(time) => Some(*time),
+                SolveResult::DNF => None,
+            })
+            .collect();
+
+        self.best = finished.iter().min().copied();
+        self.worst = results.iter().max().copied();
+        self.mean = if finished.is_empty() {
+            None
+        } else {
+            Some(finished.iter().sum::<Duration>() / finished.len() as u32)
+        };
+        self.current = columns
+            .iter()
+            .map(|column| (column.clone(), calculate_last_average(results, column)))
+            .collect();
+        self.rolling = columns
+            .into_iter()
+            .map(|column| {
+                let values = rolling_averages(results, &column);
+                (column, values)
+            })
+            .collect();
+    }
+
+    // Calculates per-phase averages over the solves split into exactly the given phases.
+    // DNF solves are left out, since their phases may not have been finished.
+    pub fn calculate_phases(&mut self, records: &[&TimeRecord], names: &[String], columns: &[StatColumn]) {
+        let split: Vec<&[Duration]> = records
+            .iter()
+            .filter(|r| r.penalty != Some(Penalty::DNF) && r.splits.len() == names.len())
+            .map(|r| r.splits.as_slice())
+            .collect();
+        if names.len() < 2 || split.is_empty() {
+            self.phases = Vec::new();
+            return;
+        }
+
+        self.phases = names
+            .iter()
+            .enumerate()
+            .map(|(phase, name)| {
+                let times: Vec<Duration> = split.iter().map(|splits| splits[phase]).collect();
+                let results: Vec<SolveResult> = times.iter().copied().map(SolveResult::Time).collect();
+                PhaseStatistics {
+                    name: name.clone(),
+                    mean: Some(times.iter().sum::<Duration>() / times.len() as u32),
+                    current: columns
+                        .iter()
+                        .map(|column| (column.clone(), calculate_last_average(&results, column)))
+                        .collect(),
+                }
+            })
+            .collect();
+    }
+
+    // Recalculates the personal bests over an event's full history, where `indices`
+    // are the record indices of the event's `results`
+    pub fn recalculate_personal_bests(&mut self, results: &[SolveResult], indices: &[usize], columns: Vec<StatColumn>) {
+        self.personal_bests = columns
+            .into_iter()
+            .filter_map(|column| {
+                let (average, start) = best_rolling_average(results, &column)?;
+                Some(PersonalBest {
+                    record_indices: indices[start..start + column.size].to_vec(),
+                    column,
+                    average,
+                    first_solve: start + 1,
+                })
+            })
+            .collect();
+    }
+
+    // Updates the personal bests with the averages ending at the newest solve
+    pub fn update_personal_bests(&mut self, results: &[SolveResult], indices: &[usize], columns: &[StatColumn]) {
+        for column in columns {
+            let size = column.size;
+            if size == 0 || results.len() < size {
+                continue;
+            }
+            let start = results.len() - size;
+            let Some(SolveResult::Time(average)) = column.calculate(&results[start..]) else {
+                continue;
+            };
+
+            let pb = PersonalBest {
+                column: column.clone(),
+                average,
+                first_solve: start + 1,
+                record_indices: indices[start..].to_vec(),
+            };
+            match self.personal_bests.iter_mut().find(|pb| pb.column == *column) {
+                Some(existing) if average < existing.average => *existing = pb,
+                Some(_) => {}
+                None => {
+                    self.personal_bests.push(pb);
+                    // Keep the same order as the event's columns
+                    self.personal_bests.sort_by_key(|pb| columns.iter().position(|c| *c == pb.column));
+                }
+            }
+        }
+    }
+}
+
+// Calculates a stat column over the last attempts, if there are enough
+pub fn calculate_last_average(results: &[SolveResult], column: &StatColumn) -> Option<SolveResult> {
+    if results.len() < column.size {
+        return None;
+    }
+    column.calculate(&results[results.len() - column.size..])
+}
+
+// Calculates the mean of sorted attempts after removing `remove_count` from each end.
+// DNFs count as the worst results, so the result is only DNF when more DNFs remain
+// than are trimmed.
+pub fn trimmed_mean(sorted: &[SolveResult], remove_count: usize) -> Option<SolveResult> {
+    if sorted.is_empty() || remove_count * 2 >= sorted.len() {
+        return None;
+    }
+
+    let mut sum = Duration::ZERO;
+    for result in &sorted[remove_count..sorted.len() - remove_count] {
+        match result {
+            SolveResult::Time(time) => sum += *time,
+            SolveResult::DNF => return Some(SolveResult::DNF),
+        }
+    }
+    Some(SolveResult::Time(sum / (sorted.len() - 2 * remove_count) as u32))
+}
+
+// Calculates a stat column over the attempts ending at each attempt, None until there are enough
+pub fn rolling_averages(results: &[SolveResult], column: &StatColumn) -> Vec<Option<SolveResult>> {
+    let size = column.size;
+    if size == 0 || results.len() < size {
+        return vec![None; results.len()];
+    }
+
+    // Keep the window sorted so every step only inserts and removes one attempt
+    let mut window = results[..size].to_vec();
+    window.sort();
+    let mut values = vec![None; size - 1];
+
+    for end in size - 1..results.len() {
+        if end >= size {
+            if let Ok(pos) = window.binary_search(&results[end - size]) {
+                window.remove(pos);
+            }
+            let added = results[end];
+            let pos = window.binary_search(&added).unwrap_or_else(|pos| pos);
+            window.insert(pos, added);
+        }
+        values.push(column.calculate_sorted(&window));
+    }
+    values
+}
+
+// Finds the best value of a stat column over consecutive attempts and the index of its first attempt
+pub fn best_rolling_average(results: &[SolveResult], column: &StatColumn) -> Option<(Duration, usize)> {
+    let mut best: Option<(Duration, usize)> = None;
+    for (end, value) in rolling_averages(results, column).into_iter().enumerate() {
+        if let Some(SolveResult::Time(average)) = value
+            && best.is_none_or(|(best_average, _)| average < best_average)
+        {
+            best = Some((average, end + 1 - column.size));
+        }
+    }
+    best
+}
+
+// Formats a result, showing DNF for unfinished solves and averages
+pub fn format_result(result: SolveResult) -> String {
+    match result {
+        SolveResult::Time(time) => format_time(time),
+        SolveResult::DNF => "DNF".to_string(),
+    }
+}
+
+// Formats a duration into a readable time string
+pub fn format_time(duration: Duration) -> String {
+    let total_millis = duration.as_millis();
+    let minutes = total_millis / 60000;
+    let seconds = (total_millis % 60000) / 1000;
+    let millis = total_millis % 1000;
+
+    if minutes > 0 {
+        format!("{}:{:02}.{:03}", minutes, seconds, millis)
+    } else {
+        format!("{}.{:03}", seconds, millis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(millis: u64) -> SolveResult {
+        SolveResult::Time(Duration::from_millis(millis))
+    }
+
+    #[test]
+    fn average_of_5_trims_best_and_worst() {
+        let results = [time(10_000), time(12_000), time(8_000), time(11_000), time(30_000)];
+        assert_eq!(StatColumn::average(5).calculate(&results), Some(time(11_000)));
+    }
+
+    #[test]
+    fn one_dnf_is_trimmed_but_two_make_a_dnf_average() {
+        let one = [time(10_000), SolveResult::DNF, time(8_000), time(11_000), time(12_000)];
+        assert_eq!(StatColumn::average(5).calculate(&one), Some(time(11_000)));
+
+        let two = [time(10_000), SolveResult::DNF, time(8_000), SolveResult::DNF, time(12_000)];
+        assert_eq!(StatColumn::average(5).calculate(&two), Some(SolveResult::DNF));
+    }
+
+    #[test]
+    fn means_trim_nothing() {
+        let results = [time(9_000), time(10_000), time(14_000)];
+        assert_eq!(StatColumn::mean(3).calculate(&results), Some(time(11_000)));
+        assert_eq!(StatColumn::mean(3).calculate(&[time(9_000), SolveResult::DNF, time(10_000)]), Some(SolveResult::DNF));
+    }
+
+    #[test]
+    fn trim_count_rounds_up() {
+        assert_eq!(StatColumn::average(5).trim_count(), 1);
+        assert_eq!(StatColumn::average(12).trim_count(), 1);
+        assert_eq!(StatColumn::average(100).trim_count(), 5);
+        assert_eq!(StatColumn { kind: StatKind::Average, size: 50, trim_percent: 10 }.trim_count(), 5);
+        assert_eq!(StatColumn::mean(3).trim_count(), 0);
+    }
+
+    #[test]
+    fn trimmed_mean_needs_attempts_left_over() {
+        assert_eq!(trimmed_mean(&[], 0), None);
+        assert_eq!(trimmed_mean(&[time(1_000), time(2_000)], 1), None);
+    }
+
+    #[test]
+    fn last_average_needs_enough_attempts() {
+        let results = [time(10_000), time(12_000), time(8_000), time(10_000)];
+        assert_eq!(calculate_last_average(&results, &StatColumn::average(5)), None);
+        assert_eq!(calculate_last_average(&results, &StatColumn::mean(3)), Some(time(10_000)));
+    }
+
+    #[test]
+    fn best_rolling_average_finds_the_fastest_window() {
+        let results = [time(20_000), time(10_000), time(11_000), time(12_000), time(30_000), SolveResult::DNF];
+        assert_eq!(best_rolling_average(&results, &StatColumn::mean(3)), Some((Duration::from_millis(11_000), 1)));
+        // Windows whose mean is a DNF never count
+        assert_eq!(best_rolling_average(&results[3..], &StatColumn::mean(3)), None);
+    }
+
+    #[test]
+    fn rolling_averages_match_the_last_average_after_every_solve() {
+        let results = [time(20_000), time(10_000), SolveResult::DNF, time(12_000), time(30_000), time(9_000), time(11_000)];
+        let column = StatColumn::average(5);
+        let rolling = rolling_averages(&results, &column);
+
+        assert_eq!(rolling.len(), results.len());
+        for (end, value) in rolling.iter().enumerate() {
+            assert_eq!(*value, calculate_last_average(&results[..=end], &column));
+        }
+        assert_eq!(rolling_averages(&results[..2], &column), vec![None, None]);
+    }
+
+    #[test]
+    fn statistics_cover_best_worst_mean_and_columns() {
+        let results = [time(10_000), SolveResult::DNF, time(8_000), time(12_000), time(11_000)];
+        let mut statistics = Statistics::default();
+        statistics.calculate(&results, vec![StatColumn::average(5), StatColumn::average(12)]);
+
+        assert_eq!(statistics.best, Some(Duration::from_millis(8_000)));
+        assert_eq!(statistics.worst, Some(SolveResult::DNF));
+        assert_eq!(statistics.mean, Some(Duration::from_millis(10_250)));
+        assert_eq!(statistics.current[0], (StatColumn::average(5), Some(time(11_000))));
+        assert_eq!(statistics.current[1], (StatColumn::average(12), None));
+    }
+
+    #[test]
+    fn personal_bests_update_with_each_new_solve() {
+        let columns = vec![StatColumn::mean(3)];
+        let mut results = vec![time(12_000), time(12_000), time(12_000)];
+        let mut indices = vec![0, 2, 4];
+        let mut statistics = Statistics::default();
+        statistics.recalculate_personal_bests(&results, &indices, columns.clone());
+        assert_eq!(statistics.personal_bests[0].average, Duration::from_millis(12_000));
+
+        // A slower mean leaves the PB alone
+        results.push(time(15_000));
+        indices.push(5);
+        statistics.update_personal_bests(&results, &indices, &columns);
+        assert_eq!(statistics.personal_bests[0].first_solve, 1);
+
+        results.extend([time(6_000), time(6_000)]);
+        indices.extend([6, 7]);
+        statistics.update_personal_bests(&results, &indices, &columns);
+        let pb = &statistics.personal_bests[0];
+        assert_eq!(pb.average, Duration::from_millis(9_000));
+        assert_eq!(pb.first_solve, 4);
+        assert_eq!(pb.record_indices, [5, 6, 7]);
+    }
+
+    #[test]
+    fn times_are_formatted_with_milliseconds() {
+        assert_eq!(format_time(Duration::from_millis(9_876)), "9.876");
+        assert_eq!(format_time(Duration::from_millis(83_045)), "1:23.045");
+        assert_eq!(format_result(SolveResult::DNF), "DNF");
+    }
+
+    #[test]
+    fn phases_are_averaged_over_split_solves() {
+        let solve = |splits: &[u64], penalty| TimeRecord {
+            penalty,
+            splits: splits.iter().copied().map(Duration::from_millis).collect(),
+            ..TimeRecord::new(CubeEvent::Standard(StandardEvent::Cube3x3), Duration::from_millis(splits.iter().sum()), chrono::Local::now())
+        };
+        let records = [
+            solve(&[2_000, 8_000], None),
+            solve(&[4_000, 6_000], Some(Penalty::Plus2)),
+            solve(&[1_000, 1_000], Some(Penalty::DNF)),
+            solve(&[10_000], None),
+        ];
+        let records: Vec<&TimeRecord> = records.iter().collect();
+        let names = ["Memo".to_string(), "Execution".to_string()];
+
+        let mut statistics = Statistics::default();
+        statistics.calculate_phases(&records, &names, &[StatColumn::mean(2), StatColumn::average(5)]);
+        assert_eq!(statistics.phases.len(), 2);
+        assert_eq!(statistics.phases[0].name, "Memo");
+        assert_eq!(statistics.phases[0].mean, Some(Duration::from_millis(3_000)));
+        assert_eq!(statistics.phases[1].current[0].1, Some(time(7_000)));
+        assert_eq!(statistics.phases[1].current[1].1, None);
+
+        statistics.calculate_phases(&records, &[], &[]);
+        assert!(statistics.phases.is_empty());
+    }
+}
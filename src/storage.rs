@@ -0,0 +1,353 @@
+// Crash-safe reading and writing of the JSON data files
+use chrono::Local;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+const BACKUP_DIR: &str = "backups";
+const MAX_BACKUPS: usize = 10; // Backups kept per data file
+const BACKUP_INTERVAL: Duration = Duration::from_secs(60 * 60); // Minimum age of the newest backup before another is made
+
+// Returns the directory all data files are stored in
+pub fn app_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("cube-timer"))
+}
+
+// Upgrades the data of one file from the version at the same index to the next version
+type Migration = fn(name: &str, data: Value) -> Result<Value, String>;
+
+// Every migration in order; the current version is the number of migrations. Adding a field
+// needs a new version even when it has a default, since older builds would drop it on save.
+const MIGRATIONS: [Migration; 2] = [migrate_v0_to_v1, migrate_v1_to_v2];
+pub const SCHEMA_VERSION: u64 = MIGRATIONS.len() as u64;
+
+// Version 0 files were written bare, without an envelope. Records written before
+// sessions existed have no session id, so they get the "unassigned" id 0.
+fn migrate_v0_to_v1(name: &str, mut data: Value) -> Result<Value, String> {
+    if name == "records.json" {
+        let records = data.as_array_mut().ok_or("records are not a list")?;
+        for record in records {
+            if let Some(record) = record.as_object_mut() {
+                record.entry("session").or_insert(json!(0));
+            }
+        }
+    }
+    Ok(data)
+}
+
+// Version 2 added the splits, smart cube moves, trainer case and scramble type of solves, the
+// phases, cases and scramble type of event settings, the suffixes, weights, axes, lines,
+// separator, prefix and suffix of custom events, the theme's color scheme and typing mode.
+// Version 1 files have none of them, which the defaults stand for, so only the version changes.
+fn migrate_v1_to_v2(_name: &str, data: Value) -> Result<Value, String> {
+    Ok(data)
+}
+
+// Splits a file into its version and data; files without an envelope are version 0
+fn unwrap_envelope(file: Value) -> Result<(u64, Value), String> {
+    if let Value::Object(map) = &file
+        && map.len() == 2
+        && let (Some(version), Some(data)) = (map.get("version"), map.get("data"))
+    {
+        let version = version.as_u64().ok_or("invalid version")?;
+        return Ok((version, data.clone()));
+    }
+    Ok((0, file))
+}
+
+// Parses the contents of a data file of any known version
+pub fn parse<T: DeserializeOwned>(name: &str, contents: &str) -> Result<T, String> {
+    let file: Value = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+    let (version, mut data) = unwrap_envelope(file)?;
+    if version > SCHEMA_VERSION {
+        return Err(format!("written by a newer version of LSTimer (data version {})", version));
+    }
+    for migration in &MIGRATIONS[version as usize..] {
+        data = migration(name, data)?;
+    }
+    serde_json::from_value(data).map_err(|e| e.to_string())
+}
+
+// Outcome of reading a data file
+pub enum Loaded<T> {
+    Missing,         // The file does not exist yet
+    Data(T),         // The file was read and parsed
+    Corrupt(String), // The file exists but could not be read or parsed
+}
+
+// Reads a JSON data file, upgrading it from older versions
+pub fn load<T: DeserializeOwned>(path: &Path) -> Loaded<T> {
+    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    match std::fs::read_to_string(path) {
+        Ok(data) => match parse(name, &data) {
+            Ok(value) => Loaded::Data(value),
+            Err(e) => Loaded::Corrupt(e),
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Loaded::Missing,
+        Err(e) => Loaded::Corrupt(e.to_string()),
+    }
+}
+
+// Serializes a value inside a versioned envelope and writes it atomically
+pub fn save<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let data = serde_json::to_value(value).map_err(|e| e.to_string())?;
+    let json = json!({ "version": SCHEMA_VERSION, "data": data }).to_string();
+    write_atomic(path, json.as_bytes()).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
+// Writes to a temporary file first and renames it over the target, so a crash or full
+// disk leaves either the old or the new contents but never a truncated file
+pub fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let mut file = std::fs::File::create(&temp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+    std::fs::rename(&temp_path, path)
+}
+
+// Prefix shared by the backups of a data file, e.g. "records-" for records.json
+fn backup_prefix(file_name: &str) -> String {
+    let stem = file_name.strip_suffix(".json").unwrap_or(file_name);
+    format!("{}-", stem)
+}
+
+// Lists the backups of a data file, newest first
+pub fn list_backups(app_dir: &Path, file_name: &str) -> Vec<PathBuf> {
+    let prefix = backup_prefix(file_name);
+    let mut backups: Vec<PathBuf> = std::fs::read_dir(app_dir.join(BACKUP_DIR))
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| {
+                    path.file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(".json"))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    // Timestamps in the names sort chronologically
+    backups.sort();
+    backups.reverse();
+    backups
+}
+
+// Copies a data file into the backup directory unless a recent backup exists,
+// then removes the oldest backups beyond the limit
+pub fn backup_if_due(app_dir: &Path, file_name: &str) -> std::io::Result<()> {
+    let source = app_dir.join(file_name);
+    if !source.exists() {
+        return Ok(());
+    }
+
+    let backups = list_backups(app_dir, file_name);
+    let newest_age = backups
+        .first()
+        .and_then(|newest| std::fs::metadata(newest).ok()?.modified().ok())
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok());
+    if newest_age.is_some_and(|age| age < BACKUP_INTERVAL) {
+        return Ok(());
+    }
+
+    let backup_dir = app_dir.join(BACKUP_DIR);
+    std::fs::create_dir_all(&backup_dir)?;
+    let name = format!("{}{}.json", backup_prefix(file_name), Local::now().format("%Y%m%d-%H%M%S"));
+    let contents = std::fs::read(&source)?;
+    write_atomic(&backup_dir.join(name), &contents)?;
+
+    for old in list_backups(app_dir, file_name).iter().skip(MAX_BACKUPS) {
+        std::fs::remove_file(old)?;
+    }
+    Ok(())
+}
+
+// Renames a damaged file out of the way so it is kept for manual recovery
+pub fn set_aside(path: &Path) -> std::io::Result<PathBuf> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".corrupt-{}", Local::now().format("%Y%m%d-%H%M%S")));
+    let target = path.with_file_name(name);
+    std::fs::rename(path, &target)?;
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scramble::ScrambleType;
+    use crate::trainer::AlgSet;
+    use crate::{CubeEvent, CustomEvent, EventSettings, Penalty, SessionData, StandardEvent, TimeRecord};
+    use std::collections::HashMap;
+
+    macro_rules! fixture {
+        ($version:literal, $name:literal) => {
+            include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/", $version, "/", $name))
+        };
+    }
+
+    #[test]
+    fn loads_version_0_records_without_sessions() {
+        let records: Vec<TimeRecord> = parse("records.json", fixture!("v0", "records.json")).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].time, Duration::from_millis(12345));
+        assert_eq!(records[0].event, CubeEvent::Standard(StandardEvent::Cube3x3));
+        assert_eq!(records[1].penalty, Some(Penalty::Plus2));
+        assert_eq!(records[2].penalty, Some(Penalty::DNF));
+        assert_eq!(records[2].event, CubeEvent::Custom("Mirror".to_string()));
+        assert!(records.iter().all(|r| r.session == 0));
+    }
+
+    #[test]
+    fn loads_version_0_custom_events() {
+        let custom_events: HashMap<String, CustomEvent> =
+            parse("custom_events.json", fixture!("v0", "custom_events.json")).unwrap();
+        assert_eq!(custom_events["Mirror"].moves, ["R", "U", "F"]);
+    }
+
+    #[test]
+    fn loads_version_1_files() {
+        let records: Vec<TimeRecord> = parse("records.json", fixture!("v1", "records.json")).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].session, 1);
+        assert_eq!(records[1].session, 2);
+
+        let sessions: SessionData = parse("sessions.json", fixture!("v1", "sessions.json")).unwrap();
+        assert_eq!(sessions.sessions.len(), 2);
+        assert_eq!(sessions.active["3x3x3"], 1);
+        assert_eq!(sessions.next_id, 3);
+
+        let settings: HashMap<String, EventSettings> =
+            parse("event_settings.json", fixture!("v1", "event_settings.json")).unwrap();
+        assert!(!settings["3x3 BLD"].inspection);
+        assert_eq!(settings["3x3 BLD"].stat_columns[0].label(), "Mo3");
+        assert!(settings["3x3x3"].stat_columns.is_empty());
+
+        let custom_events: HashMap<String, CustomEvent> =
+            parse("custom_events.json", fixture!("v1", "custom_events.json")).unwrap();
+        assert_eq!(custom_events["Mirror"].scramble_length, 20);
+    }
+
+    #[test]
+    fn loads_version_2_files() {
+        let records: Vec<TimeRecord> = parse("records.json", fixture!("v2", "records.json")).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].splits, [Duration::from_millis(2100), Duration::from_millis(7770)]);
+        assert_eq!(records[0].scramble_type, ScrambleType::LastLayer);
+        assert_eq!(records[1].moves.len(), 2);
+        assert_eq!(records[1].moves[1].turn, "U'");
+        assert_eq!(records[2].case.as_deref(), Some("T"));
+        assert_eq!(records[2].scramble_type, ScrambleType::RandomState);
+        assert_eq!(records[2].event, CubeEvent::Training(AlgSet::Pll));
+
+        let settings: HashMap<String, EventSettings> =
+            parse("event_settings.json", fixture!("v2", "event_settings.json")).unwrap();
+        assert_eq!(settings["3x3x3"].phases, ["Cross", "F2L", "LL"]);
+        assert_eq!(settings["3x3x3"].scramble_type, ScrambleType::CrossSolved);
+        assert_eq!(settings["PLL Trainer"].cases, ["T", "Jb"]);
+
+        let custom_events: HashMap<String, CustomEvent> =
+            parse("custom_events.json", fixture!("v2", "custom_events.json")).unwrap();
+        let floppy = &custom_events["Floppy"];
+        assert_eq!(floppy.weight_of("R"), 2);
+        assert_eq!(floppy.modifiers_of("U"), ["2"]);
+        assert_eq!(floppy.lines.as_ref().map(|lines| lines.length), Some(4));
+        assert!(floppy.validate().is_ok());
+    }
+
+    #[test]
+    fn version_1_solves_need_their_session() {
+        // Sessions came with version 1, so only the upgrade from version 0 may fill them in
+        let solve = json!({ "time": { "secs": 9, "nanos": 0 }, "event": { "Standard": "Cube3x3" }, "scramble": "R", "timestamp": "2025-01-10T09:00:00+01:00", "penalty": null, "comment": "" });
+        assert!(parse::<Vec<TimeRecord>>("records.json", &json!([solve]).to_string()).is_ok());
+        assert!(parse::<Vec<TimeRecord>>("records.json", &json!({ "version": 1, "data": [solve] }).to_string()).is_err());
+    }
+
+    #[test]
+    fn saved_files_use_the_current_version() {
+        let dir = std::env::temp_dir().join(format!("lstimer-storage-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("records.json");
+        let records: Vec<TimeRecord> = parse("records.json", fixture!("v0", "records.json")).unwrap();
+        save(&path, &records).unwrap();
+
+        let file: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(file["version"], SCHEMA_VERSION);
+        match load::<Vec<TimeRecord>>(&path) {
+            Loaded::Data(loaded) => assert_eq!(loaded.len(), records.len()),
+            _ => panic!("saved records did not load"),
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // A data directory holding records.json, cleared of earlier runs
+    fn backup_test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("lstimer-storage-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join(BACKUP_DIR)).unwrap();
+        std::fs::write(dir.join("records.json"), "[]").unwrap();
+        dir
+    }
+
+    // Writes a backup that looks `age` old
+    fn old_backup(dir: &Path, name: &str, age: Duration) -> PathBuf {
+        let path = dir.join(BACKUP_DIR).join(name);
+        std::fs::write(&path, "[]").unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() - age).unwrap();
+        path
+    }
+
+    #[test]
+    fn backups_are_made_at_most_once_per_interval() {
+        let dir = backup_test_dir("interval");
+        backup_if_due(&dir, "records.json").unwrap();
+        backup_if_due(&dir, "records.json").unwrap();
+        let backups = list_backups(&dir, "records.json");
+        assert_eq!(backups.len(), 1);
+
+        // Once the newest backup is older than the interval, the next save backs up again
+        std::fs::remove_file(&backups[0]).unwrap();
+        old_backup(&dir, "records-20200101-000000.json", BACKUP_INTERVAL - Duration::from_secs(60));
+        backup_if_due(&dir, "records.json").unwrap();
+        assert_eq!(list_backups(&dir, "records.json").len(), 1);
+        old_backup(&dir, "records-20200101-000000.json", BACKUP_INTERVAL + Duration::from_secs(60));
+        backup_if_due(&dir, "records.json").unwrap();
+        assert_eq!(list_backups(&dir, "records.json").len(), 2);
+
+        // Other files keep backups of their own
+        std::fs::write(dir.join("sessions.json"), "{}").unwrap();
+        backup_if_due(&dir, "sessions.json").unwrap();
+        assert_eq!(list_backups(&dir, "sessions.json").len(), 1);
+        backup_if_due(&dir, "theme.json").unwrap();
+        assert!(list_backups(&dir, "theme.json").is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn only_the_newest_backups_are_kept() {
+        let dir = backup_test_dir("rotation");
+        let old: Vec<PathBuf> = (0..MAX_BACKUPS + 2)
+            .map(|i| old_backup(&dir, &format!("records-20200101-0000{:02}.json", i), BACKUP_INTERVAL * 2))
+            .collect();
+        backup_if_due(&dir, "records.json").unwrap();
+
+        let backups = list_backups(&dir, "records.json");
+        assert_eq!(backups.len(), MAX_BACKUPS);
+        // The new backup is kept along with the newest old ones
+        assert!(!old.contains(&backups[0]));
+        assert_eq!(backups[1..], old[3..].iter().rev().cloned().collect::<Vec<_>>());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let contents = json!({ "version": SCHEMA_VERSION + 1, "data": [] }).to_string();
+        assert!(parse::<Vec<TimeRecord>>("records.json", &contents).is_err());
+    }
+}
@@ -0,0 +1,600 @@
+// Timer state machine driven by space presses, with WCA inspection and split phases
+use crate::{EventSettings, Penalty, TimedMove};
+use crate::stats::format_time;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+// WCA inspection limits (regulations A3a1, A4b, A4d)
+pub const INSPECTION_TIME: Duration = Duration::from_secs(15);
+pub const INSPECTION_PLUS2_LIMIT: Duration = Duration::from_secs(17);
+pub const INSPECTION_FIRST_WARNING: Duration = Duration::from_secs(8);
+pub const INSPECTION_SECOND_WARNING: Duration = Duration::from_secs(12);
+
+// Source of the current time, so the timer can be driven by a fake clock in tests
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+// Reads the system's monotonic clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+// A clock that only moves when advanced; clones share the same time
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Rc<Cell<Instant>>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self { now: Rc::new(Cell::new(Instant::now())) }
+    }
+
+    // Moves the clock forward
+    pub fn advance(&self, by: Duration) {
+        self.now.set(self.now.get() + by);
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.now.get()
+    }
+}
+
+// Represents the possible states of the timer
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimerState {
+    Ready,      // Timer is idle and ready to start
+    Inspection, // WCA inspection countdown is running
+    Preparing,  // User is holding space to prepare
+    Running,    // Timer is actively counting
+    Stopped,    // Timer has stopped after a solve
+}
+
+// A solve the timer has just finished
+#[derive(Debug, Clone, PartialEq)]
+pub struct FinishedSolve {
+    pub time: Duration,           // Time from start to stop
+    pub penalty: Option<Penalty>, // Penalty earned during inspection
+    pub splits: Vec<Duration>,    // Time of each phase of a split solve, empty otherwise
+    pub moves: Vec<TimedMove>,    // Turns a smart cube recorded during the solve, empty otherwise
+}
+
+// The timing state machine: space presses and releases move it between states,
+// presses while running end the solve's phases, and stopping it hands back the finished solve
+pub struct Timer {
+    pub state: TimerState,              // Current state of the timer
+    pub start_time: Option<Instant>,    // Start time of the current solve
+    pub current_time: Duration,         // Current running time
+    pub last_time: Option<Duration>,    // Last recorded solve time
+    pub space_pressed: bool,            // Space key state
+    pub space_hold_start: Option<Instant>, // Time when space key was pressed
+    pub key_preparation_time: Duration, // Minimum hold time to start timer
+    pub inspection_start: Option<Instant>, // Start of the current inspection
+    pub inspection_penalty: Option<Penalty>, // Penalty earned during inspection
+    pub splits: Vec<Duration>,          // Elapsed time at the end of each phase of the current or last solve
+    clock: Box<dyn Clock>,              // Source of the current time
+}
+
+impl Default for Timer {
+    fn default() -> Self {
+        Self::new(SystemClock)
+    }
+}
+
+impl Timer {
+    // Creates an idle timer reading time from the given clock
+    pub fn new(clock: impl Clock + 'static) -> Self {
+        Self {
+            state: TimerState::Ready,
+            start_time: None,
+            current_time: Duration::ZERO,
+            last_time: None,
+            space_pressed: false,
+            space_hold_start: None,
+            key_preparation_time: Duration::from_millis(300),
+            inspection_start: None,
+            inspection_penalty: None,
+            splits: Vec::new(),
+            clock: Box::new(clock),
+        }
+    }
+
+    // Returns the current time of the timer's clock
+    pub fn now(&self) -> Instant {
+        self.clock.now()
+    }
+
+    // Handles space key input, returning the solve if it stopped the timer
+    pub fn handle_space_key(&mut self, pressed: bool, settings: &EventSettings) -> Option<FinishedSolve> {
+        let now = self.now();
+
+        if pressed && !self.space_pressed {
+            return self.handle_space_press(now, settings);
+        } else if !pressed && self.space_pressed {
+            self.handle_space_release(now);
+        }
+        None
+    }
+
+    // Processes space key press, following the event's inspection and phase settings
+    pub fn handle_space_press(&mut self, now: Instant, settings: &EventSettings) -> Option<FinishedSolve> {
+        self.space_pressed = true;
+        self.space_hold_start = Some(now);
+
+        match self.state {
+            TimerState::Ready | TimerState::Stopped => {
+                if settings.inspection {
+                    self.start_inspection(now);
+                } else {
+                    self.state = TimerState::Preparing;
+                }
+            }
+            TimerState::Inspection => {
+                self.state = TimerState::Preparing;
+            }
+            // Every press but the last of a split solve ends a phase
+            TimerState::Running if self.splits.len() + 1 < settings.phase_count() => {
+                self.record_split(now);
+            }
+            TimerState::Running => {
+                return self.stop_timer(now);
+            }
+            _ => {}
+        }
+        None
+    }
+
+    // Processes space key release
+    pub fn handle_space_release(&mut self, now: Instant) {
+        self.space_pressed = false;
+
+        match self.state {
+            TimerState::Preparing => {
+                self.try_start_timer(now);
+            }
+            TimerState::Stopped => {
+                self.state = TimerState::Ready;
+            }
+            _ => {}
+        }
+
+        self.space_hold_start = None;
+    }
+
+    // Presses and releases space at once, counting as a full hold, for input
+    // that cannot report key releases
+    pub fn tap(&mut self, now: Instant, settings: &EventSettings) -> Option<FinishedSolve> {
+        let solve = self.handle_space_press(now, settings);
+        self.space_hold_start = now.checked_sub(self.key_preparation_time).or(Some(now));
+        self.handle_space_release(now);
+        solve
+    }
+
+    // Begins the WCA inspection countdown
+    pub fn start_inspection(&mut self, now: Instant) {
+        self.state = TimerState::Inspection;
+        self.inspection_start = Some(now);
+        self.inspection_penalty = None;
+    }
+
+    // Attempts to start the timer based on hold duration
+    pub fn try_start_timer(&mut self, now: Instant) {
+        if let Some(hold_start) = self.space_hold_start {
+            let hold_duration = now.duration_since(hold_start);
+
+            if hold_duration >= self.key_preparation_time {
+                self.start_timer(now);
+            } else if self.inspection_start.is_some() {
+                self.state = TimerState::Inspection;
+            } else {
+                self.state = TimerState::Ready;
+            }
+        }
+    }
+
+    // Starts the timer
+    pub fn start_timer(&mut self, now: Instant) {
+        if let Some(inspection_start) = self.inspection_start.take() {
+            self.inspection_penalty = inspection_penalty_for(now.duration_since(inspection_start));
+        }
+        self.state = TimerState::Running;
+        self.start_time = Some(now);
+        self.current_time = Duration::ZERO;
+        self.splits.clear();
+    }
+
+    // Ends the current phase of a split solve
+    pub fn record_split(&mut self, now: Instant) {
+        if let Some(start_time) = self.start_time {
+            self.splits.push(now.duration_since(start_time));
+        }
+    }
+
+    // Stops the timer and returns the finished solve
+    pub fn stop_timer(&mut self, now: Instant) -> Option<FinishedSolve> {
+        let solve = self.start_time.map(|start_time| {
+            self.current_time = now.duration_since(start_time);
+            self.last_time = Some(self.current_time);
+            if !self.splits.is_empty() {
+                self.splits.push(self.current_time);
+            }
+            FinishedSolve {
+                time: self.current_time,
+                penalty: self.inspection_penalty.take(),
+                splits: self.phase_times(),
+                moves: Vec::new(),
+            }
+        });
+        self.state = TimerState::Stopped;
+        self.start_time = None;
+        solve
+    }
+
+    // Hands placed on an external timer's pads, `ready` once it shows that lifting them starts the solve
+    pub fn hands_on(&mut self, now: Instant, ready: bool) {
+        if !matches!(self.state, TimerState::Running | TimerState::Preparing) {
+            self.state = TimerState::Preparing;
+            self.space_hold_start = Some(now);
+        }
+        if ready {
+            self.space_hold_start = now.checked_sub(self.key_preparation_time).or(Some(now));
+        }
+    }
+
+    // Hands lifted from an external timer's pads, or the timer reset, without a solve starting
+    pub fn hands_off(&mut self) {
+        if matches!(self.state, TimerState::Preparing | TimerState::Stopped) {
+            self.state = if self.inspection_start.is_some() {
+                TimerState::Inspection
+            } else {
+                TimerState::Ready
+            };
+            self.space_hold_start = None;
+        }
+    }
+
+    // An external timer is running, for `elapsed` if it reports it. Its time wins over the
+    // clock, which only fills in between its updates
+    pub fn sync_running(&mut self, now: Instant, elapsed: Option<Duration>) {
+        let start = elapsed.map(|elapsed| now.checked_sub(elapsed).unwrap_or(now));
+        if !matches!(self.state, TimerState::Running) {
+            self.start_timer(start.unwrap_or(now));
+        }
+        if start.is_some() {
+            self.start_time = start;
+        }
+        self.update_running_time(now);
+    }
+
+    // An external timer stopped at `time`, finishing the solve if one was running
+    pub fn stop_external(&mut self, now: Instant, time: Duration) -> Option<FinishedSolve> {
+        if !matches!(self.state, TimerState::Running) {
+            return None;
+        }
+        self.start_time = Some(now.checked_sub(time).unwrap_or(now));
+        self.stop_timer(now)
+    }
+
+    // Updates the displayed time of a running solve
+    pub fn update_running_time(&mut self, now: Instant) {
+        if matches!(self.state, TimerState::Running)
+            && let Some(start_time) = self.start_time
+        {
+            self.current_time = now.duration_since(start_time);
+        }
+    }
+
+    // Durations of the phases finished so far in the current or last split solve
+    pub fn phase_times(&self) -> Vec<Duration> {
+        let mut previous = Duration::ZERO;
+        self.splits
+            .iter()
+            .map(|&split| {
+                let phase = split - previous;
+                previous = split;
+                phase
+            })
+            .collect()
+    }
+
+    // Time spent inspecting so far, if an inspection is running
+    pub fn inspection_elapsed(&self) -> Option<Duration> {
+        self.inspection_start.map(|start| self.now().saturating_duration_since(start))
+    }
+
+    // Returns true once space has been held long enough for the release to start the timer
+    pub fn is_held_long_enough(&self) -> bool {
+        matches!(self.state, TimerState::Preparing)
+            && self.space_hold_start
+                .is_some_and(|hold_start| self.now().saturating_duration_since(hold_start) >= self.key_preparation_time)
+    }
+
+    // Gets the timer display text
+    pub fn display_text(&self) -> String {
+        if let Some(elapsed) = self.inspection_elapsed() {
+            format_inspection(elapsed)
+        } else if matches!(self.state, TimerState::Running) {
+            format_time(self.current_time)
+        } else if let Some(last_time) = self.last_time {
+            format_time(last_time)
+        } else {
+            "0.000".to_string()
+        }
+    }
+}
+
+// Returns the penalty earned for an inspection of the given length
+pub fn inspection_penalty_for(elapsed: Duration) -> Option<Penalty> {
+    if elapsed > INSPECTION_PLUS2_LIMIT {
+        Some(Penalty::DNF)
+    } else if elapsed > INSPECTION_TIME {
+        Some(Penalty::Plus2)
+    } else {
+        None
+    }
+}
+
+// Formats the inspection countdown shown in place of the timer
+pub fn format_inspection(elapsed: Duration) -> String {
+    match inspection_penalty_for(elapsed) {
+        Some(Penalty::DNF) => "DNF".to_string(),
+        Some(Penalty::Plus2) => "+2".to_string(),
+        None => {
+            let remaining = INSPECTION_TIME - elapsed;
+            format!("{}", remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0))
+        }
+    }
+}
+
+// Parses a result typed in by hand: "12.34", "1:02.50", "DNF(12.34)", "DNF" or "12.34+" for a +2
+pub fn parse_typed_solve(text: &str) -> Result<FinishedSolve, String> {
+    let text = text.trim();
+    let invalid = || format!("\"{}\" is not a time (try 12.34, 1:02.50, DNF(12.34) or 12.34+)", text);
+
+    let (time_text, penalty) = if text.get(..3).is_some_and(|start| start.eq_ignore_ascii_case("DNF")) {
+        let rest = text[3..].trim();
+        let inner = if rest.is_empty() {
+            rest
+        } else {
+            rest.strip_prefix('(').and_then(|r| r.strip_suffix(')')).ok_or_else(invalid)?
+        };
+        (inner.trim(), Some(Penalty::DNF))
+    } else if let Some(time) = text.strip_suffix("+2").or_else(|| text.strip_suffix('+')) {
+        (time.trim(), Some(Penalty::Plus2))
+    } else {
+        (text, None)
+    };
+
+    let time = match parse_clock_time(time_text) {
+        Some(time) if !time.is_zero() => time,
+        // A bare DNF has no time to record
+        _ if time_text.is_empty() && penalty == Some(Penalty::DNF) => Duration::ZERO,
+        _ => return Err(invalid()),
+    };
+    Ok(FinishedSolve { time, penalty, splits: Vec::new(), moves: Vec::new() })
+}
+
+// Parses "[[h:]m:]s[.fraction]" into a duration, keeping millisecond precision
+fn parse_clock_time(text: &str) -> Option<Duration> {
+    let is_number = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit());
+    let mut parts = text.rsplit(':');
+    let seconds = parts.next()?;
+    let (whole, fraction) = seconds.split_once('.').unwrap_or((seconds, ""));
+    if !is_number(whole) || !(fraction.is_empty() || is_number(fraction)) {
+        return None;
+    }
+
+    let fraction = &fraction[..fraction.len().min(3)];
+    let mut millis = whole.parse::<u64>().ok()? * 1000 + format!("{:0<3}", fraction).parse::<u64>().ok()?;
+    for (position, part) in parts.enumerate() {
+        // Minutes and hours only, and seconds or minutes below them must stay under 60
+        if position > 1 || !is_number(part) || millis >= 60_000 * 60u64.pow(position as u32) {
+            return None;
+        }
+        millis += part.parse::<u64>().ok()? * 60_000 * 60u64.pow(position as u32);
+    }
+    Some(Duration::from_millis(millis))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scramble::ScrambleType;
+
+    fn timer() -> (Timer, ManualClock) {
+        let clock = ManualClock::new();
+        (Timer::new(clock.clone()), clock)
+    }
+
+    fn settings(inspection: bool) -> EventSettings {
+        EventSettings { inspection, stat_columns: Vec::new(), phases: Vec::new(), cases: Vec::new(), scramble_type: ScrambleType::RandomState }
+    }
+
+    // Holds space for `hold` and releases it
+    fn hold(timer: &mut Timer, clock: &ManualClock, hold: Duration, inspection: bool) -> Option<FinishedSolve> {
+        let solve = timer.handle_space_key(true, &settings(inspection));
+        clock.advance(hold);
+        timer.handle_space_key(false, &settings(inspection));
+        solve
+    }
+
+    #[test]
+    fn a_long_enough_hold_starts_the_timer() {
+        let (mut timer, clock) = timer();
+        timer.handle_space_key(true, &settings(false));
+        assert_eq!(timer.state, TimerState::Preparing);
+        assert!(!timer.is_held_long_enough());
+
+        clock.advance(Duration::from_millis(300));
+        assert!(timer.is_held_long_enough());
+        timer.handle_space_key(false, &settings(false));
+        assert_eq!(timer.state, TimerState::Running);
+        assert_eq!(timer.start_time, Some(clock.now()));
+    }
+
+    #[test]
+    fn a_short_hold_returns_to_ready() {
+        let (mut timer, clock) = timer();
+        hold(&mut timer, &clock, Duration::from_millis(100), false);
+        assert_eq!(timer.state, TimerState::Ready);
+        assert_eq!(timer.start_time, None);
+    }
+
+    #[test]
+    fn pressing_while_running_stops_with_the_elapsed_time() {
+        let (mut timer, clock) = timer();
+        hold(&mut timer, &clock, Duration::from_millis(500), false);
+        clock.advance(Duration::from_millis(9_876));
+        timer.update_running_time(clock.now());
+        assert_eq!(timer.display_text(), "9.876");
+
+        let solve = timer.handle_space_key(true, &settings(false));
+        assert_eq!(solve, Some(FinishedSolve { time: Duration::from_millis(9_876), penalty: None, splits: Vec::new(), moves: Vec::new() }));
+        assert_eq!(timer.state, TimerState::Stopped);
+        assert_eq!(timer.last_time, Some(Duration::from_millis(9_876)));
+
+        // Releasing after the stop makes the timer ready without starting inspection
+        timer.handle_space_key(false, &settings(false));
+        assert_eq!(timer.state, TimerState::Ready);
+    }
+
+    #[test]
+    fn stopping_a_timer_that_never_started_finishes_nothing() {
+        let (mut timer, clock) = timer();
+        assert_eq!(timer.stop_timer(clock.now()), None);
+        assert_eq!(timer.state, TimerState::Stopped);
+    }
+
+    #[test]
+    fn repeated_presses_are_ignored() {
+        let (mut timer, clock) = timer();
+        timer.handle_space_key(true, &settings(false));
+        clock.advance(Duration::from_millis(200));
+        timer.handle_space_key(true, &settings(false));
+        clock.advance(Duration::from_millis(200));
+        timer.handle_space_key(false, &settings(false));
+        assert_eq!(timer.state, TimerState::Running);
+    }
+
+    #[test]
+    fn inspection_runs_until_the_solve_starts() {
+        let (mut timer, clock) = timer();
+        hold(&mut timer, &clock, Duration::from_millis(100), true);
+        assert_eq!(timer.state, TimerState::Inspection);
+        assert_eq!(timer.display_text(), "15");
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(timer.display_text(), "10");
+
+        // A short hold during inspection goes back to inspecting
+        hold(&mut timer, &clock, Duration::from_millis(100), true);
+        assert_eq!(timer.state, TimerState::Inspection);
+
+        hold(&mut timer, &clock, Duration::from_millis(400), true);
+        assert_eq!(timer.state, TimerState::Running);
+        assert_eq!(timer.inspection_start, None);
+
+        clock.advance(Duration::from_secs(10));
+        let solve = timer.handle_space_key(true, &settings(true)).unwrap();
+        assert_eq!(solve.penalty, None);
+    }
+
+    #[test]
+    fn late_starts_get_inspection_penalties() {
+        let (mut timer, clock) = timer();
+        hold(&mut timer, &clock, Duration::ZERO, true);
+        clock.advance(Duration::from_secs(16));
+        hold(&mut timer, &clock, Duration::from_millis(300), true);
+        let solve = timer.handle_space_key(true, &settings(true)).unwrap();
+        assert_eq!(solve.penalty, Some(Penalty::Plus2));
+
+        timer.handle_space_key(false, &settings(true));
+        hold(&mut timer, &clock, Duration::ZERO, true);
+        clock.advance(Duration::from_secs(18));
+        hold(&mut timer, &clock, Duration::from_millis(300), true);
+        let solve = timer.handle_space_key(true, &settings(true)).unwrap();
+        assert_eq!(solve.penalty, Some(Penalty::DNF));
+    }
+
+    #[test]
+    fn inspection_penalties_follow_the_wca_limits() {
+        assert_eq!(inspection_penalty_for(Duration::from_secs(15)), None);
+        assert_eq!(inspection_penalty_for(Duration::from_millis(15_001)), Some(Penalty::Plus2));
+        assert_eq!(inspection_penalty_for(Duration::from_secs(17)), Some(Penalty::Plus2));
+        assert_eq!(inspection_penalty_for(Duration::from_millis(17_001)), Some(Penalty::DNF));
+        assert_eq!(format_inspection(Duration::from_millis(7_500)), "8");
+    }
+
+    #[test]
+    fn a_tap_counts_as_a_full_hold() {
+        let (mut timer, clock) = timer();
+        timer.tap(clock.now(), &settings(false));
+        assert_eq!(timer.state, TimerState::Running);
+        clock.advance(Duration::from_secs(3));
+        let solve = timer.tap(clock.now(), &settings(false));
+        assert_eq!(solve.map(|s| s.time), Some(Duration::from_secs(3)));
+        assert_eq!(timer.state, TimerState::Ready);
+    }
+
+    #[test]
+    fn split_solves_stop_on_the_last_phase() {
+        let (mut timer, clock) = timer();
+        let cfop = EventSettings {
+            phases: ["Cross", "F2L", "OLL", "PLL"].map(String::from).to_vec(),
+            ..settings(false)
+        };
+        timer.handle_space_key(true, &cfop);
+        clock.advance(Duration::from_millis(500));
+        timer.handle_space_key(false, &cfop);
+
+        for phase in [2_000, 6_000, 2_500] {
+            clock.advance(Duration::from_millis(phase));
+            assert_eq!(timer.handle_space_key(true, &cfop), None);
+            timer.handle_space_key(false, &cfop);
+            assert_eq!(timer.state, TimerState::Running);
+        }
+        assert_eq!(timer.phase_times(), [2_000, 6_000, 2_500].map(Duration::from_millis));
+
+        clock.advance(Duration::from_millis(1_500));
+        let solve = timer.handle_space_key(true, &cfop).unwrap();
+        assert_eq!(solve.time, Duration::from_secs(12));
+        assert_eq!(solve.splits, [2_000, 6_000, 2_500, 1_500].map(Duration::from_millis));
+        assert_eq!(timer.state, TimerState::Stopped);
+    }
+
+    #[test]
+    fn typed_times_are_parsed_with_their_penalty() {
+        let solve = |text| parse_typed_solve(text).map(|s| (s.time, s.penalty));
+        assert_eq!(solve("12.34"), Ok((Duration::from_millis(12_340), None)));
+        assert_eq!(solve(" 1:02.50 "), Ok((Duration::from_millis(62_500), None)));
+        assert_eq!(solve("1:00:00"), Ok((Duration::from_secs(3_600), None)));
+        assert_eq!(solve("9"), Ok((Duration::from_secs(9), None)));
+        assert_eq!(solve("12.3456"), Ok((Duration::from_millis(12_345), None)));
+        assert_eq!(solve("DNF(12.34)"), Ok((Duration::from_millis(12_340), Some(Penalty::DNF))));
+        assert_eq!(solve("dnf"), Ok((Duration::ZERO, Some(Penalty::DNF))));
+        assert_eq!(solve("12.34+"), Ok((Duration::from_millis(12_340), Some(Penalty::Plus2))));
+        assert_eq!(solve("12.34+2"), Ok((Duration::from_millis(12_340), Some(Penalty::Plus2))));
+    }
+
+    #[test]
+    fn malformed_typed_times_are_rejected() {
+        for text in ["", "abc", "0", "1:75.00", "1:2:3:4", "12.-3", "DNF 12.34", "DNF(12.34", "+", "12..3", ":30", "é12"] {
+            assert!(parse_typed_solve(text).is_err(), "{:?} should not parse", text);
+        }
+    }
+}
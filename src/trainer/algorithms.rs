@@ -0,0 +1,176 @@
+// Named OLL and PLL algorithms, and applying algorithms with wide turns, slices and rotations
+// to the cubie model
+use crate::scramble::cube::{CubieCube, FACE_NAMES};
+
+// The 57 OLL cases in their usual numbering
+pub const OLL: [(&str, &str); 57] = [
+    ("1", "R U2 R2 F R F' U2 R' F R F'"),
+    ("2", "F R U R' U' F' f R U R' U' f'"),
+    ("3", "f R U R' U' f' U' F R U R' U' F'"),
+    ("4", "f R U R' U' f' U F R U R' U' F'"),
+    ("5", "r' U2 R U R' U r"),
+    ("6", "r U2 R' U' R U' r'"),
+    ("7", "r U R' U R U2 r'"),
+    ("8", "l' U' L U' L' U2 l"),
+    ("9", "R U R' U' R' F R2 U R' U' F'"),
+    ("10", "R U R' U R' F R F' R U2 R'"),
+    ("11", "r U R' U R' F R F' R U2 r'"),
+    ("12", "M' R' U' R U' R' U2 R U' M"),
+    ("13", "F U R U' R2 F' R U R U' R'"),
+    ("14", "R' F R U R' F' R F U' F'"),
+    ("15", "r' U' r R' U' R U r' U r"),
+    ("16", "r U r' R U R' U' r U' r'"),
+    ("17", "R U R' U R' F R F' U2 R' F R F'"),
+    ("18", "r U R' U R U2 r2 U' R U' R' U2 r"),
+    ("19", "r' R U R U R' U' M' R' F R F'"),
+    ("20", "r U R' U' M2 U R U' R' U' M'"),
+    ("21", "R U2 R' U' R U R' U' R U' R'"),
+    ("22", "R U2 R2 U' R2 U' R2 U2 R"),
+    ("23", "R2 D' R U2 R' D R U2 R"),
+    ("24", "r U R' U' r' F R F'"),
+    ("25", "F' r U R' U' r' F R"),
+    ("26", "R U2 R' U' R U' R'"),
+    ("27", "R U R' U R U2 R'"),
+    ("28", "r U R' U' r' R U R U' R'"),
+    ("29", "R U R' U' R U' R' F' U' F R U R'"),
+    ("30", "F R' F R2 U' R' U' R U R' F2"),
+    ("31", "R' U' F U R U' R' F' R"),
+    ("32", "L U F' U' L' U L F L'"),
+    ("33", "R U R' U' R' F R F'"),
+    ("34", "R U R2 U' R' F R U R U' F'"),
+    ("35", "R U2 R2 F R F' R U2 R'"),
+    ("36", "L' U' L U' L' U L U L F' L' F"),
+    ("37", "F R' F' R U R U' R'"),
+    ("38", "R U R' U R U' R' U' R' F R F'"),
+    ("39", "L F' L' U' L U F U' L'"),
+    ("40", "R' F R U R' U' F' U R"),
+    ("41", "R U R' U R U2 R' F R U R' U' F'"),
+    ("42", "R' U' R U' R' U2 R F R U R' U' F'"),
+    ("43", "F' U' L' U L F"),
+    ("44", "F U R U' R' F'"),
+    ("45", "F R U R' U' F'"),
+    ("46", "R' U' R' F R F' U R"),
+    ("47", "R' U' R' F R F' R' F R F' U R"),
+    ("48", "F R U R' U' R U R' U' F'"),
+    ("49", "r U' r2 U r2 U r2 U' r"),
+    ("50", "r' U r2 U' r2 U' r2 U r'"),
+    ("51", "F U R U' R' U R U' R' F'"),
+    ("52", "R U R' U R U' B U' B' R'"),
+    ("53", "r' U2 R U R' U' R U R' U r"),
+    ("54", "r U2 R' U' R U R' U' R U' r'"),
+    ("55", "R' F R U R U' R2 F' R2 U' R' U R U R'"),
+    ("56", "r' U' r U' R' U R U' R' U R r' U r"),
+    ("57", "R U R' U' M' U R U' r'"),
+];
+
+// The 21 PLL cases
+pub const PLL: [(&str, &str); 21] = [
+    ("Aa", "x R' U R' D2 R U' R' D2 R2 x'"),
+    ("Ab", "x R2 D2 R U R' D2 R U' R x'"),
+    ("E", "x' R U' R' D R U R' D' R U R' D R U' R' D' x"),
+    ("F", "R' U' F' R U R' U' R' F R2 U' R' U' R U R' U R"),
+    ("Ga", "R2 U R' U R' U' R U' R2 U' D R' U R D'"),
+    ("Gb", "R' U' R U D' R2 U R' U R U' R U' R2 D"),
+    ("Gc", "R2 U' R U' R U R' U R2 U D' R U' R' D"),
+    ("Gd", "R U R' U' D R2 U' R U' R' U R' U R2 D'"),
+    ("H", "M2 U M2 U2 M2 U M2"),
+    ("Ja", "R' U L' U2 R U' R' U2 R L"),
+    ("Jb", "R U R' F' R U R' U' R' F R2 U' R'"),
+    ("Na", "R U R' U R U R' F' R U R' U' R' F R2 U' R' U2 R U' R'"),
+    ("Nb", "R' U R U' R' F' U' F R U R' F R' F' R U' R"),
+    ("Ra", "R U' R' U' R U R D R' U' R D' R' U2 R'"),
+    ("Rb", "R2 F R U R U' R' F' R U2 R' U2 R"),
+    ("T", "R U R' U' R' F R2 U' R' U' R U R' F'"),
+    ("Ua", "M2 U M U2 M' U M2"),
+    ("Ub", "M2 U' M U2 M' U' M2"),
+    ("V", "R U' R U R' D R D' R U' D R2 U R2 D' R2"),
+    ("Y", "F R U' R' U' R U R' F' R U R' U' R' F R F'"),
+    ("Z", "M' U M2 U M2 U M' U2 M2"),
+];
+
+// Faces in FACE_NAMES order
+const U: usize = 0;
+const R: usize = 1;
+const F: usize = 2;
+const D: usize = 3;
+const L: usize = 4;
+const B: usize = 5;
+
+// Each rotation as the face turn it follows and the faces it cycles, each taking the
+// place of the next
+const ROTATIONS: [(char, usize, [usize; 4]); 3] = [('x', R, [F, U, B, D]), ('y', U, [R, F, L, B]), ('z', F, [L, U, R, D])];
+
+// Applies an algorithm. The cubie model has no centers, so rotations change which face each
+// letter turns instead of moving the pieces. Fails on unknown moves and on algorithms that
+// leave the cube rotated
+pub fn apply(cube: &mut CubieCube, algorithm: &str) -> Result<(), String> {
+    let mut frame = [U, R, F, D, L, B]; // Face each letter turns
+    for token in algorithm.split_whitespace() {
+        let unknown = || format!("Unknown move \"{}\"", token);
+        let (body, turns) = split_turns(token).ok_or_else(unknown)?;
+        let mut letters = body.chars();
+        let (first, rest) = (letters.next().ok_or_else(unknown)?, letters.as_str());
+        let letter_face = FACE_NAMES.iter().position(|name| name.starts_with(first.to_ascii_uppercase()));
+
+        // Wide turns and slices are outer turns combined with a rotation
+        let (outer, rotation) = match (first, rest, letter_face) {
+            (_, "", Some(face)) if first.is_ascii_uppercase() => (vec![(face, turns)], None),
+            (_, "w" | "", Some(face)) => {
+                let opposite = (face + 3) % 6;
+                let &(axis, follows, _) = ROTATIONS
+                    .iter()
+                    .find(|(_, follows, _)| *follows == face || *follows == opposite)
+                    .ok_or_else(unknown)?;
+                (vec![(opposite, turns)], Some((axis, if follows == face { turns } else { 4 - turns })))
+            }
+            ('M', "", _) => (vec![(R, turns), (L, 4 - turns)], Some(('x', 4 - turns))),
+            ('E', "", _) => (vec![(U, turns), (D, 4 - turns)], Some(('y', 4 - turns))),
+            ('S', "", _) => (vec![(F, 4 - turns), (B, turns)], Some(('z', turns))),
+            ('x' | 'y' | 'z', "", _) => (Vec::new(), Some((first, turns))),
+            _ => return Err(unknown()),
+        };
+
+        for (face, turns) in outer {
+            cube.apply_move(frame[face] * 3 + turns - 1);
+        }
+        if let Some((axis, turns)) = rotation {
+            let (_, _, cycle) = ROTATIONS.iter().find(|(name, _, _)| *name == axis).ok_or_else(unknown)?;
+            for _ in 0..turns {
+                let taken = cycle.map(|face| frame[face]);
+                for i in 0..4 {
+                    frame[cycle[(i + 1) % 4]] = taken[i];
+                }
+            }
+        }
+    }
+    if frame != [U, R, F, D, L, B] {
+        return Err("The algorithm leaves the cube rotated".to_string());
+    }
+    Ok(())
+}
+
+// Reverses an algorithm, so applying it undoes the original
+pub fn invert(algorithm: &str) -> String {
+    algorithm
+        .split_whitespace()
+        .rev()
+        .map(|token| match token.strip_suffix('\'') {
+            Some(turn) => turn.to_string(),
+            None if token.ends_with('2') => token.to_string(),
+            None => format!("{}'", token),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Splits a move into its letters and clockwise quarter turns: R 1, R2 2, R' 3
+fn split_turns(token: &str) -> Option<(&str, usize)> {
+    let (body, turns) = match token.strip_suffix('\'') {
+        Some(body) => (body.strip_suffix('2').unwrap_or(body), if body.ends_with('2') { 2 } else { 3 }),
+        None => match token.strip_suffix('2') {
+            Some(body) => (body, 2),
+            None => (token, 1),
+        },
+    };
+    (!body.is_empty()).then_some((body, turns))
+}
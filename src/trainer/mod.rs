@@ -0,0 +1,516 @@
+// Last layer trainers: scrambles for chosen OLL, PLL, ZBLL, CLL and EG cases, pictures of
+// the cases and how each case is going
+mod algorithms;
+
+pub use algorithms::invert;
+
+use crate::puzzle::{Diagram, Polygon, Rgb};
+use crate::scramble::cube::{CubieCube, permutation_parity};
+use crate::{SolveResult, TimeRecord};
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+// Color of the stickers OLL pictures leave out
+const IGNORED: Rgb = [110, 110, 120];
+
+// Faces of each corner and edge, in the cubie model's order with the U or D face first
+const CORNER_FACES: [[usize; 3]; 8] = [[0, 1, 2], [0, 2, 4], [0, 4, 5], [0, 5, 1], [3, 2, 1], [3, 4, 2], [3, 5, 4], [3, 1, 5]];
+const EDGE_FACES: [[usize; 2]; 12] = [
+    [0, 1], [0, 2], [0, 4], [0, 5], [3, 1], [3, 2], [3, 4], [3, 5], [2, 1], [2, 4], [5, 4], [5, 1],
+];
+const U_MOVE: usize = 0;
+const D_MOVE: usize = 9;
+
+// A set of cases the trainer draws scrambles from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AlgSet {
+    Oll,
+    Pll,
+    Zbll,
+    Cll,
+    Eg1,
+    Eg2,
+}
+
+impl AlgSet {
+    pub const ALL: [AlgSet; 6] = [AlgSet::Oll, AlgSet::Pll, AlgSet::Zbll, AlgSet::Cll, AlgSet::Eg1, AlgSet::Eg2];
+
+    // Layers of the cube the set is for
+    pub fn size(self) -> usize {
+        match self {
+            AlgSet::Oll | AlgSet::Pll | AlgSet::Zbll => 3,
+            AlgSet::Cll | AlgSet::Eg1 | AlgSet::Eg2 => 2,
+        }
+    }
+
+    // Every case of the set, grouped and in a stable order
+    pub fn cases(self) -> &'static [Case] {
+        static CASES: OnceLock<Vec<Vec<Case>>> = OnceLock::new();
+        let all = CASES.get_or_init(|| AlgSet::ALL.iter().map(|&set| build_cases(set)).collect());
+        &all[AlgSet::ALL.iter().position(|&set| set == self).unwrap_or(0)]
+    }
+
+    // The case with the given id
+    pub fn case(self, id: &str) -> Option<&'static Case> {
+        self.cases().iter().find(|case| case.id == id)
+    }
+}
+
+impl std::fmt::Display for AlgSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            AlgSet::Oll => write!(f, "OLL"),
+            AlgSet::Pll => write!(f, "PLL"),
+            AlgSet::Zbll => write!(f, "ZBLL"),
+            AlgSet::Cll => write!(f, "CLL"),
+            AlgSet::Eg1 => write!(f, "EG-1"),
+            AlgSet::Eg2 => write!(f, "EG-2"),
+        }
+    }
+}
+
+// A last layer case
+#[derive(Debug, Clone)]
+pub struct Case {
+    pub id: String,                // Unique across sets and stored on solves, e.g. "PLL T"
+    pub name: String,              // Name within the set, e.g. "T"
+    pub group: String,             // Cases are picked and shown by group
+    pub algorithm: Option<String>, // Algorithm solving the case, for the named OLL and PLL cases
+    set: AlgSet,
+    state: CubieCube, // The case as set up from a solved cube
+}
+
+impl Case {
+    // Draws the last layer from above with the top row of each side around it, back at the
+    // top. OLL cases only show which stickers face up
+    pub fn diagram(&self, colors: &[Rgb; 6]) -> Diagram {
+        let n = self.set.size();
+        let last = (n - 1) as f32;
+        let (gap, depth) = (0.08, 0.3);
+        let mut polygons = Vec::new();
+        let mut sticker = |face: usize, col: f32, row: f32, color: usize| {
+            let color = if self.set == AlgSet::Oll && color != 0 { IGNORED } else { colors[color] };
+            let (x, y, width, height) = match face {
+                1 => (n as f32 + gap, row, depth, 1.0),
+                2 => (col, n as f32 + gap, 1.0, depth),
+                4 => (-gap - depth, row, depth, 1.0),
+                5 => (col, -gap - depth, 1.0, depth),
+                _ => (col, row, 1.0, 1.0),
+            };
+            let (x, y, width, height) = (x + 0.03, y + 0.03, width - 0.06, height - 0.06);
+            polygons.push(Polygon { points: vec![[x, y], [x + width, y], [x + width, y + height], [x, y + height]], color });
+        };
+
+        // URF, UFL, ULB and UBR, then UR, UF, UL and UB
+        let corner_cells = [(last, last), (0.0, last), (0.0, 0.0), (last, 0.0)];
+        for (position, &(col, row)) in corner_cells.iter().enumerate() {
+            let (piece, twist) = (self.state.cp[position] as usize, self.state.co[position] as usize);
+            for (slot, &face) in CORNER_FACES[position].iter().enumerate() {
+                sticker(face, col, row, CORNER_FACES[piece][(slot + 3 - twist) % 3]);
+            }
+        }
+        if n == 3 {
+            let edge_cells = [(2.0, 1.0), (1.0, 2.0), (0.0, 1.0), (1.0, 0.0)];
+            for (position, &(col, row)) in edge_cells.iter().enumerate() {
+                let (piece, flip) = (self.state.ep[position] as usize, self.state.eo[position] as usize);
+                for (slot, &face) in EDGE_FACES[position].iter().enumerate() {
+                    sticker(face, col, row, EDGE_FACES[piece][(slot + flip) % 2]);
+                }
+            }
+            sticker(0, 1.0, 1.0, 0);
+        }
+        Diagram::new(polygons)
+    }
+}
+
+// Picks a case from the selected ids, or from the whole set when none of them are in it
+pub fn pick_case(rng: &mut impl Rng, set: AlgSet, selected: &[String]) -> &'static Case {
+    let cases = set.cases();
+    let chosen: Vec<&Case> = cases.iter().filter(|case| selected.contains(&case.id)).collect();
+    match chosen.choose(rng) {
+        Some(case) => case,
+        None => &cases[rng.gen_range(0..cases.len())],
+    }
+}
+
+// Generates a scramble for a case: the case between random AUFs, with a random permutation
+// of the last layer for OLL and a random D layer turn on 2x2x2, reached by a random-state
+// solve so the scramble does not give the algorithm away
+pub fn scramble(rng: &mut impl Rng, case: &Case) -> String {
+    let mut state = CubieCube::default();
+    if case.set == AlgSet::Oll {
+        state.cp[..4].shuffle(rng);
+        state.ep[..4].shuffle(rng);
+    }
+    (0..rng.gen_range(0..4)).for_each(|_| state.apply_move(U_MOVE));
+    state.multiply(&case.state);
+    (0..rng.gen_range(0..4)).for_each(|_| state.apply_move(U_MOVE));
+    if case.set.size() == 2 {
+        (0..rng.gen_range(0..4)).for_each(|_| state.apply_move(D_MOVE));
+    }
+    // The 2x2x2 has no edges, so they only have to make the state solvable
+    if permutation_parity(&state.cp) != permutation_parity(&state.ep) {
+        state.ep.swap(0, 1);
+    }
+    crate::scramble::state_scramble(&state)
+}
+
+// How a case has gone over the solves tagged with it
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaseStats {
+    pub case: String,
+    pub solves: usize,
+    pub dnfs: usize,
+    pub mean: Option<Duration>, // Mean of the finished solves, None when all were DNF
+}
+
+// Collects the stats of every case the solves were tagged with, in case id order
+pub fn case_stats<'a>(records: impl IntoIterator<Item = &'a TimeRecord>) -> Vec<CaseStats> {
+    let mut results: BTreeMap<&str, Vec<SolveResult>> = BTreeMap::new();
+    for record in records {
+        if let Some(case) = &record.case {
+            results.entry(case).or_default().push(record.result());
+        }
+    }
+    results
+        .into_iter()
+        .map(|(case, results)| {
+            let times: Vec<Duration> = results
+                .iter()
+                .filter_map(|result| match result {
+                    SolveResult::Time(time) => Some(*time),
+                    SolveResult::DNF => None,
+                })
+                .collect();
+            CaseStats {
+                case: case.to_string(),
+                solves: results.len(),
+                dnfs: results.len() - times.len(),
+                mean: (!times.is_empty()).then(|| times.iter().sum::<Duration>() / times.len() as u32),
+            }
+        })
+        .collect()
+}
+
+// Cases with the slowest mean first
+pub fn slowest(stats: &[CaseStats]) -> Vec<&CaseStats> {
+    let mut timed: Vec<&CaseStats> = stats.iter().filter(|stats| stats.mean.is_some()).collect();
+    timed.sort_by_key(|stats| std::cmp::Reverse(stats.mean));
+    timed
+}
+
+// Cases with the most DNFs first, ties going to the higher DNF rate
+pub fn most_failed(stats: &[CaseStats]) -> Vec<&CaseStats> {
+    let mut failed: Vec<&CaseStats> = stats.iter().filter(|stats| stats.dnfs > 0).collect();
+    failed.sort_by(|a, b| b.dnfs.cmp(&a.dnfs).then((b.dnfs * a.solves).cmp(&(a.dnfs * b.solves))));
+    failed
+}
+
+// The state after `pre` U turns, the case and `post` U turns
+fn with_aufs(state: &CubieCube, pre: usize, post: usize) -> CubieCube {
+    let mut cube = CubieCube::default();
+    (0..pre).for_each(|_| cube.apply_move(U_MOVE));
+    cube.multiply(state);
+    (0..post).for_each(|_| cube.apply_move(U_MOVE));
+    cube
+}
+
+// The last layer of a state, the same for states that only differ in the rest of the cube.
+// Without edges only the corners count, as on the 2x2x2
+fn layer(state: &CubieCube, edges: bool) -> Vec<u8> {
+    let mut key = [&state.cp[..4], &state.co[..4]].concat();
+    if edges {
+        key.extend_from_slice(&state.ep[..4]);
+        key.extend_from_slice(&state.eo[..4]);
+    }
+    key
+}
+
+// Identifies a case regardless of the AUFs around it
+fn case_key(state: &CubieCube, edges: bool) -> Vec<u8> {
+    (0..16).map(|i| layer(&with_aufs(state, i / 4, i % 4), edges)).min().unwrap_or_default()
+}
+
+// Identifies the corner orientation shape of a case
+fn shape_key(state: &CubieCube) -> [u8; 4] {
+    (0..4).map(|post| with_aufs(state, 0, post).co[..4].try_into().unwrap_or_default()).min().unwrap_or_default()
+}
+
+// The state an algorithm solves
+fn case_state(algorithm: &str) -> CubieCube {
+    let mut state = CubieCube::default();
+    algorithms::apply(&mut state, &invert(algorithm)).unwrap_or_default();
+    state
+}
+
+// Name of the corner orientation shape, from the OCLL cases among the OLLs
+fn shape_name(state: &CubieCube) -> &'static str {
+    const SHAPES: [(&str, &str); 7] = [("21", "H"), ("22", "Pi"), ("23", "U"), ("24", "T"), ("25", "L"), ("26", "Antisune"), ("27", "Sune")];
+    let key = shape_key(state);
+    if key == [0; 4] {
+        return "Oriented";
+    }
+    SHAPES
+        .iter()
+        .find(|(number, _)| {
+            let algorithm = algorithms::OLL.iter().find(|(name, _)| name == number).map_or("", |(_, algorithm)| algorithm);
+            shape_key(&case_state(algorithm)) == key
+        })
+        .map_or("", |(_, name)| name)
+}
+
+// The named cases of an algorithm table, grouped by what `group` says of their state
+fn named_cases(set: AlgSet, table: &[(&str, &str)], group: impl Fn(&CubieCube) -> String) -> Vec<Case> {
+    table
+        .iter()
+        .map(|(name, algorithm)| {
+            let state = case_state(algorithm);
+            Case {
+                id: format!("{} {}", set, name),
+                name: name.to_string(),
+                group: group(&state),
+                algorithm: Some(algorithm.to_string()),
+                set,
+                state,
+            }
+        })
+        .collect()
+}
+
+// Every last layer state of the set, one case per class of states the AUFs turn into each
+// other, numbered within their corner orientation shape
+fn enumerated_cases(set: AlgSet) -> Vec<Case> {
+    let mut permutations: Vec<[u8; 4]> = Vec::new();
+    for a in 0..4u8 {
+        for b in 0..4u8 {
+            for c in 0..4u8 {
+                for d in 0..4u8 {
+                    let permutation = [a, b, c, d];
+                    if (0..4u8).all(|piece| permutation.contains(&piece)) {
+                        permutations.push(permutation);
+                    }
+                }
+            }
+        }
+    }
+    let twists: Vec<[u8; 4]> = (0..81u8)
+        .map(|i| [i % 3, i / 3 % 3, i / 9 % 3, i / 27])
+        .filter(|twist| twist.iter().sum::<u8>() % 3 == 0)
+        .collect();
+    // EG solves a D layer whose back or diagonal corners are swapped with the last layer
+    let bottom = match set {
+        AlgSet::Eg1 => Some((6, 7)),
+        AlgSet::Eg2 => Some((4, 6)),
+        _ => None,
+    };
+
+    let edges = set == AlgSet::Zbll;
+    let solved = case_key(&CubieCube::default(), edges);
+    let mut states: BTreeMap<Vec<u8>, CubieCube> = BTreeMap::new();
+    for corners in &permutations {
+        for twist in &twists {
+            let edge_permutations: &[[u8; 4]] = if edges { &permutations } else { &[[0, 1, 2, 3]] };
+            for edge_permutation in edge_permutations {
+                let mut state = CubieCube::default();
+                state.cp[..4].copy_from_slice(corners);
+                state.co[..4].copy_from_slice(twist);
+                state.ep[..4].copy_from_slice(edge_permutation);
+                if let Some((a, b)) = bottom {
+                    state.cp.swap(a, b);
+                }
+                let valid = match set {
+                    AlgSet::Zbll => twist != &[0; 4] && permutation_parity(corners) == permutation_parity(edge_permutation),
+                    AlgSet::Cll => case_key(&state, edges) != solved,
+                    _ => true,
+                };
+                if valid {
+                    let key = case_key(&state, edges);
+                    let shown = with_aufs_matching(&state, &key, edges);
+                    states.entry(key).or_insert(shown);
+                }
+            }
+        }
+    }
+
+    let mut numbers: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut cases: Vec<Case> = states
+        .into_values()
+        .map(|state| {
+            let shape = shape_name(&state);
+            let number = numbers.entry(shape).or_default();
+            *number += 1;
+            let name = format!("{} {}", shape, number);
+            Case { id: format!("{} {}", set, name), name, group: shape.to_string(), algorithm: None, set, state }
+        })
+        .collect();
+    cases.sort_by_key(|case| SHAPE_ORDER.iter().position(|shape| *shape == case.group));
+    cases
+}
+
+// Order the corner orientation shapes are shown in
+const SHAPE_ORDER: [&str; 8] = ["Oriented", "Sune", "Antisune", "H", "Pi", "U", "T", "L"];
+
+// The member of a state's class that has the given key, so each case is shown the same way
+fn with_aufs_matching(state: &CubieCube, key: &[u8], edges: bool) -> CubieCube {
+    (0..16).map(|i| with_aufs(state, i / 4, i % 4)).find(|cube| layer(cube, edges) == key).unwrap_or(*state)
+}
+
+fn build_cases(set: AlgSet) -> Vec<Case> {
+    match set {
+        AlgSet::Oll => named_cases(set, &algorithms::OLL, |state| {
+            match state.eo[..4].iter().filter(|&&flip| flip == 0).count() {
+                4 => "Cross",
+                2 if state.eo[0] == state.eo[2] => "Line",
+                2 => "Angle",
+                _ => "Dot",
+            }
+            .to_string()
+        }),
+        AlgSet::Pll => {
+            // Corner permutations up to AUF, from a case of each kind
+            let corners = |state: &CubieCube| case_key(state, false);
+            let adjacent = corners(&case_state(algorithms::PLL[15].1));
+            let diagonal = corners(&case_state(algorithms::PLL[19].1));
+            named_cases(set, &algorithms::PLL, |state| {
+                match corners(state) {
+                    key if key == adjacent => "Adjacent corner swap",
+                    key if key == diagonal => "Diagonal corner swap",
+                    _ => "Corners solved",
+                }
+                .to_string()
+            })
+        }
+        _ => enumerated_cases(set),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CubeEvent, Penalty, StandardEvent};
+    use chrono::Local;
+    use std::collections::HashSet;
+
+    // Identifies an OLL case, where only which way the pieces face counts
+    fn orientation_key(state: &CubieCube) -> [u8; 8] {
+        (0..4)
+            .map(|post| {
+                let cube = with_aufs(state, 0, post);
+                let mut key = [0; 8];
+                key[..4].copy_from_slice(&cube.co[..4]);
+                key[4..].copy_from_slice(&cube.eo[..4]);
+                key
+            })
+            .min()
+            .unwrap_or_default()
+    }
+
+    // The first two layers of a state are solved
+    fn keeps_first_two_layers(state: &CubieCube) -> bool {
+        (4..8).all(|i| state.cp[i] == i as u8 && state.co[i] == 0)
+            && (4..12).all(|i| state.ep[i] == i as u8 && state.eo[i] == 0)
+    }
+
+    #[test]
+    fn every_named_algorithm_solves_a_different_case() {
+        for (set, table) in [(AlgSet::Oll, &algorithms::OLL[..]), (AlgSet::Pll, &algorithms::PLL[..])] {
+            let mut seen = HashSet::new();
+            for (name, algorithm) in table {
+                let mut state = CubieCube::default();
+                algorithms::apply(&mut state, &invert(algorithm)).unwrap_or_else(|e| panic!("{} {}: {}", set, name, e));
+                assert!(keeps_first_two_layers(&state), "{} {}", set, name);
+                let key = if set == AlgSet::Oll { orientation_key(&state).to_vec() } else { case_key(&state, true) };
+                assert!(seen.insert(key), "{} {} repeats another case", set, name);
+                assert_eq!(set == AlgSet::Pll, orientation_key(&state) == [0; 8], "{} {}", set, name);
+                assert!(!state.is_solved(), "{} {}", set, name);
+            }
+        }
+
+        // Wide turns, slices and rotations match their outer turn equivalents
+        let mut wide = CubieCube::default();
+        algorithms::apply(&mut wide, "r U x' M' U x' E R2 y S U z'").unwrap();
+        let mut outer = CubieCube::default();
+        algorithms::apply(&mut outer, "L F R' L F U D' F2 F' B L").unwrap();
+        assert_eq!(wide, outer);
+        assert!(algorithms::apply(&mut wide, "R y").is_err());
+        assert!(algorithms::apply(&mut wide, "R Q").is_err());
+    }
+
+    #[test]
+    fn enumerated_sets_have_the_usual_case_counts() {
+        let count = |set: AlgSet, group: &str| set.cases().iter().filter(|case| case.group == group).count();
+        assert_eq!(AlgSet::Oll.cases().len(), 57);
+        assert_eq!(AlgSet::Pll.cases().len(), 21);
+        assert_eq!(AlgSet::Zbll.cases().len(), 472);
+        for shape in ["Sune", "Antisune", "Pi", "U", "T", "L"] {
+            assert_eq!(count(AlgSet::Zbll, shape), 72, "{}", shape);
+        }
+        assert_eq!(count(AlgSet::Zbll, "H"), 40);
+        assert_eq!(AlgSet::Cll.cases().len(), 42);
+        assert_eq!(count(AlgSet::Cll, "H"), 4);
+        // EG also has the cases with an oriented and permuted top, solved by swapping the bottom
+        assert_eq!(AlgSet::Eg1.cases().len(), 43);
+        assert_eq!(AlgSet::Eg2.cases().len(), 43);
+        assert_eq!(count(AlgSet::Pll, "Corners solved"), 4);
+
+        let ids: HashSet<&str> = AlgSet::ALL.iter().flat_map(|set| set.cases()).map(|case| case.id.as_str()).collect();
+        assert_eq!(ids.len(), AlgSet::ALL.iter().map(|set| set.cases().len()).sum::<usize>());
+    }
+
+    #[test]
+    fn scrambles_lead_to_the_chosen_case() {
+        let mut rng = rand::rngs::mock::StepRng::new(7, 0x9e37_79b9_7f4a_7c15);
+        for set in AlgSet::ALL {
+            let case = pick_case(&mut rng, set, &[set.cases()[3].id.clone()]);
+            assert_eq!(case.id, set.cases()[3].id);
+            let scramble = scramble(&mut rng, case);
+            let mut state = CubieCube::from_moves(&scramble).unwrap();
+            let edges = set.size() == 3;
+            // The 2x2x2 bottom comes back with a D turn
+            for _ in 0..4 {
+                if edges || state.cp[4..] == case.state.cp[4..] {
+                    break;
+                }
+                state.apply_move(D_MOVE);
+            }
+            if set == AlgSet::Oll {
+                assert_eq!(orientation_key(&state), orientation_key(&case.state), "{} {}", case.id, scramble);
+            } else {
+                assert_eq!(case_key(&state, edges), case_key(&case.state, edges), "{} {}", case.id, scramble);
+            }
+        }
+    }
+
+    #[test]
+    fn case_stats_report_the_slowest_and_most_failed_cases() {
+        let solve = |case: &str, millis: u64, penalty| TimeRecord {
+            penalty,
+            case: Some(case.to_string()),
+            ..TimeRecord::new(CubeEvent::Training(AlgSet::Pll), Duration::from_millis(millis), Local::now())
+        };
+        let mut untagged = solve("", 900, None);
+        untagged.case = None;
+        untagged.event = CubeEvent::Standard(StandardEvent::Cube3x3);
+        let records = [
+            solve("PLL T", 1500, None),
+            solve("PLL T", 2500, Some(Penalty::Plus2)),
+            solve("PLL Ga", 4000, None),
+            solve("PLL Ga", 3000, Some(Penalty::DNF)),
+            solve("PLL Ja", 1000, Some(Penalty::DNF)),
+            untagged,
+        ];
+
+        let stats = case_stats(&records);
+        assert_eq!(stats.len(), 3);
+        let t = stats.iter().find(|stats| stats.case == "PLL T").unwrap();
+        assert_eq!((t.solves, t.dnfs, t.mean), (2, 0, Some(Duration::from_millis(3000))));
+        let slowest: Vec<&str> = slowest(&stats).iter().map(|stats| stats.case.as_str()).collect();
+        assert_eq!(slowest, ["PLL Ga", "PLL T"]);
+        let failed: Vec<&str> = most_failed(&stats).iter().map(|stats| stats.case.as_str()).collect();
+        assert_eq!(failed, ["PLL Ja", "PLL Ga"]);
+    }
+}
@@ -0,0 +1,363 @@
+// Terminal front end that drives the same timer, scrambles and data files as the window
+use crate::CubeTimer;
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags,
+    PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use crossterm::style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor};
+use crossterm::{cursor, execute, queue, terminal};
+use egui::Color32;
+use lstimer::stats::{format_result, format_time};
+use lstimer::{Penalty, TimerState};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+const FRAME_TIME: Duration = Duration::from_millis(30); // Redraw interval while waiting for input
+const RECENT_TIMES: usize = 12; // Number of recent solves listed
+// Longest gap between the space presses of a held key; covers the usual auto-repeat delay
+const REPEAT_GAP: Duration = Duration::from_millis(700);
+
+// Tracks whether space is still held on terminals that cannot report key releases, where a
+// held key sends a stream of presses
+#[derive(Debug, Default)]
+struct HeldSpace {
+    last_press: Option<Instant>, // Time of the last space press, None after another key
+}
+
+// Runs the timer in the terminal until the user quits
+pub fn run() -> std::io::Result<()> {
+    let mut app = CubeTimer::load();
+    let mut stdout = std::io::stdout();
+
+    terminal::enable_raw_mode()?;
+    execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+
+    // Key releases are needed for hold-to-start but only some terminals report them
+    let releases = terminal::supports_keyboard_enhancement().unwrap_or(false);
+    if releases {
+        execute!(stdout, PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES))?;
+    }
+
+    let result = event_loop(&mut app, &mut stdout, releases);
+
+    if releases {
+        let _ = execute!(stdout, PopKeyboardEnhancementFlags);
+    }
+    let _ = execute!(stdout, cursor::Show, terminal::LeaveAlternateScreen);
+    let _ = terminal::disable_raw_mode();
+    app.save_data();
+    result
+}
+
+// Reads keys and redraws until the user quits
+fn event_loop(app: &mut CubeTimer, stdout: &mut std::io::Stdout, releases: bool) -> std::io::Result<()> {
+    let mut held = HeldSpace::default();
+    loop {
+        let now = app.timer.now();
+        app.timer.update_running_time(now);
+        app.autosave(now);
+        draw(app, stdout, releases)?;
+
+        if !event::poll(FRAME_TIME)? {
+            continue;
+        }
+        if let Event::Key(key) = event::read()?
+            && !handle_key(app, key, releases, &mut held)
+        {
+            return Ok(());
+        }
+    }
+}
+
+// Applies a key event to the timer, returning false when the user quits
+fn handle_key(app: &mut CubeTimer, key: KeyEvent, releases: bool, held: &mut HeldSpace) -> bool {
+    // Without release events, space presses that follow the previous one too closely are the
+    // held key repeating, which must not start inspection, the solve or the next attempt
+    if !releases && key.kind == KeyEventKind::Press {
+        let now = app.timer.now();
+        let repeating = key.code == KeyCode::Char(' ')
+            && held.last_press.is_some_and(|last| now.duration_since(last) < REPEAT_GAP);
+        held.last_press = (key.code == KeyCode::Char(' ')).then_some(now);
+        if repeating {
+            return true;
+        }
+    }
+
+    // Any key stops a running solve, like touching a stackmat
+    if key.kind == KeyEventKind::Press && matches!(app.timer.state, TimerState::Running) {
+        app.handle_space_key(true);
+        return true;
+    }
+
+    if key.code == KeyCode::Char(' ') {
+        match key.kind {
+            KeyEventKind::Press if releases => app.handle_space_key(true),
+            KeyEventKind::Release => app.handle_space_key(false),
+            // Without release events a tap counts as a full hold, so the timer starts on the press
+            KeyEventKind::Press => app.tap_space(),
+            KeyEventKind::Repeat => {}
+        }
+        return true;
+    }
+
+    if key.kind == KeyEventKind::Release || !matches!(app.timer.state, TimerState::Ready | TimerState::Stopped) {
+        return true;
+    }
+
+    let last_index = app.data.records.iter().rposition(|r| app.is_in_scope(r));
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => return false,
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+        KeyCode::Char('n') => app.generate_new_scramble(),
+        KeyCode::Right | KeyCode::Left => {
+            let count = app.data.available_events.len();
+            let position = app.data.available_events.iter().position(|e| *e == app.current_event).unwrap_or(0);
+            let next = if key.code == KeyCode::Right { (position + 1) % count } else { (position + count - 1) % count };
+            app.switch_event(app.data.available_events[next].clone());
+        }
+        KeyCode::Char('1') => {
+            if let Some(index) = last_index {
+                app.apply_penalty(index, None);
+            }
+        }
+        KeyCode::Char('2') => {
+            if let Some(index) = last_index {
+                app.apply_penalty(index, Some(Penalty::Plus2));
+            }
+        }
+        KeyCode::Char('3') => {
+            if let Some(index) = last_index {
+                app.apply_penalty(index, Some(Penalty::DNF));
+            }
+        }
+        KeyCode::Char('x') => {
+            if let Some(index) = last_index {
+                app.delete_time(index);
+            }
+        }
+        _ => {}
+    }
+    true
+}
+
+// Converts a theme color for the terminal
+fn color(color: Color32) -> Color {
+    Color::Rgb { r: color.r(), g: color.g(), b: color.b() }
+}
+
+// Wraps a scramble into lines of at most `width` characters, keeping Megaminx line breaks
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for row in text.lines() {
+        let mut line = String::new();
+        for token in row.split_whitespace() {
+            if !line.is_empty() && line.len() + 1 + token.len() > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(token);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+// Writes successive lines of the screen, dropping whatever does not fit
+struct Screen<'a> {
+    stdout: &'a mut std::io::Stdout,
+    row: u16,
+    width: usize,
+    height: u16,
+}
+
+impl Screen<'_> {
+    // Writes one line in the given color
+    fn line(&mut self, text: &str, fg: Color32, bold: bool) -> std::io::Result<()> {
+        if self.row >= self.height {
+            return Ok(());
+        }
+        let text: String = text.chars().take(self.width).collect();
+        queue!(self.stdout, cursor::MoveTo(0, self.row), SetForegroundColor(color(fg)))?;
+        if bold {
+            queue!(self.stdout, SetAttribute(Attribute::Bold))?;
+        }
+        queue!(self.stdout, Print(text), SetAttribute(Attribute::Reset), ResetColor)?;
+        self.row += 1;
+        Ok(())
+    }
+
+    // Writes an empty line
+    fn blank(&mut self) {
+        self.row += 1;
+    }
+}
+
+// Draws the whole screen
+fn draw(app: &CubeTimer, stdout: &mut std::io::Stdout, releases: bool) -> std::io::Result<()> {
+    let (width, height) = terminal::size()?;
+    queue!(stdout, terminal::Clear(terminal::ClearType::All))?;
+    let mut screen = Screen { stdout, row: 0, width: width.max(20) as usize, height };
+    let theme = &app.theme;
+
+    screen.line(&format!("LSTimer  ·  {}", app.scope_description()), theme.accent_primary_color(), true)?;
+    screen.blank();
+    for scramble_line in wrap(&app.current_scramble, screen.width) {
+        screen.line(&scramble_line, theme.text_primary_color(), false)?;
+    }
+    screen.blank();
+
+    let timer_color = app.get_timer_color();
+    screen.line(&format!("    {}", app.get_timer_text()), timer_color, true)?;
+    if let Some(splits) = app.split_text() {
+        screen.line(&format!("    {}", splits), theme.text_secondary_color(), false)?;
+    }
+    let (state_text, state_color) = app.state_indicator();
+    let state_text = if releases {
+        state_text.to_string()
+    } else {
+        state_text.replace("hold SPACE", "press SPACE").replace("Hold SPACE", "Press SPACE")
+    };
+    screen.line(&format!("    {}", state_text), state_color, false)?;
+    screen.blank();
+
+    let mut stats = Vec::new();
+    if let Some(best) = app.statistics.best {
+        stats.push(format!("Best {}", format_time(best)));
+    }
+    for (column, value) in &app.statistics.current {
+        if let Some(value) = value {
+            stats.push(format!("{} {}", column.label(), format_result(*value)));
+        }
+    }
+    if let Some(mean) = app.statistics.mean {
+        stats.push(format!("Mean {}", format_time(mean)));
+    }
+    screen.line(&stats.join("   "), theme.accent_secondary_color(), false)?;
+    screen.blank();
+
+    let records: Vec<_> = app.data.records.iter().filter(|r| app.is_in_scope(r)).collect();
+    for (number, record) in records.iter().enumerate().rev().take(RECENT_TIMES) {
+        let (time, fg) = match record.penalty {
+            Some(Penalty::Plus2) => (format!("{}+", format_result(record.result())), theme.warning_color()),
+            Some(Penalty::DNF) => (format!("DNF({})", format_time(record.time)), theme.error_color()),
+            None => (format_time(record.time), theme.text_primary_color()),
+        };
+        let text = format!("{:>5}. {:>12}   {}", number + 1, time, record.timestamp.format("%Y-%m-%d %H:%M"));
+        screen.line(&text, fg, false)?;
+    }
+
+    screen.row = screen.row.max(height.saturating_sub(1));
+    screen.line(
+        "space start/stop · n new scramble · ←/→ event · 1/2/3 OK/+2/DNF · x delete last · q quit",
+        theme.text_secondary_color(),
+        false,
+    )?;
+    screen.stdout.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lstimer::{CubeEvent, EventSettings, ManualClock, StandardEvent, Timer};
+
+    fn space() -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE)
+    }
+
+    // A timer without inspection driven by a manual clock
+    fn app() -> (CubeTimer, ManualClock) {
+        app_with_inspection(false)
+    }
+
+    fn app_with_inspection(inspection: bool) -> (CubeTimer, ManualClock) {
+        let clock = ManualClock::new();
+        let mut app = CubeTimer { timer: Timer::new(clock.clone()), ..CubeTimer::default() };
+        let event = CubeEvent::Standard(StandardEvent::Cube3x3);
+        let settings = EventSettings { inspection, ..EventSettings::for_event(&event) };
+        app.data.event_settings.insert(event.to_string(), settings);
+        app.current_event = event;
+        (app, clock)
+    }
+
+    #[test]
+    fn held_space_without_releases_does_not_stop_the_solve() {
+        let (mut app, clock) = app();
+        let mut held = HeldSpace::default();
+
+        assert!(handle_key(&mut app, space(), false, &mut held));
+        assert_eq!(app.timer.state, TimerState::Running);
+
+        // The terminal's auto-repeat: a first repeat after half a second, then a quick stream
+        clock.advance(Duration::from_millis(500));
+        assert!(handle_key(&mut app, space(), false, &mut held));
+        clock.advance(Duration::from_millis(30));
+        assert!(handle_key(&mut app, space(), false, &mut held));
+        assert_eq!(app.timer.state, TimerState::Running);
+        assert!(app.data.records.is_empty());
+
+        // A press after a gap is the stop
+        clock.advance(Duration::from_secs(9));
+        assert!(handle_key(&mut app, space(), false, &mut held));
+        assert_eq!(app.timer.state, TimerState::Stopped);
+        assert_eq!(app.data.records.len(), 1);
+        assert_eq!(app.data.records[0].time, Duration::from_millis(9530));
+    }
+
+    #[test]
+    fn held_space_without_releases_does_not_end_inspection() {
+        let (mut app, clock) = app_with_inspection(true);
+        let mut held = HeldSpace::default();
+
+        handle_key(&mut app, space(), false, &mut held);
+        assert_eq!(app.timer.state, TimerState::Inspection);
+        clock.advance(Duration::from_millis(500));
+        handle_key(&mut app, space(), false, &mut held);
+        clock.advance(Duration::from_millis(30));
+        handle_key(&mut app, space(), false, &mut held);
+        assert_eq!(app.timer.state, TimerState::Inspection);
+
+        // A press after a gap starts the solve
+        clock.advance(Duration::from_secs(5));
+        handle_key(&mut app, space(), false, &mut held);
+        assert_eq!(app.timer.state, TimerState::Running);
+    }
+
+    #[test]
+    fn held_space_after_a_stop_does_not_start_the_next_attempt() {
+        let (mut app, clock) = app_with_inspection(true);
+        let mut held = HeldSpace::default();
+
+        handle_key(&mut app, space(), false, &mut held);
+        clock.advance(Duration::from_secs(5));
+        handle_key(&mut app, space(), false, &mut held);
+        clock.advance(Duration::from_secs(10));
+        handle_key(&mut app, space(), false, &mut held);
+        assert_eq!(app.timer.state, TimerState::Stopped);
+
+        // The stopping press is held down
+        clock.advance(Duration::from_millis(500));
+        handle_key(&mut app, space(), false, &mut held);
+        clock.advance(Duration::from_millis(30));
+        handle_key(&mut app, space(), false, &mut held);
+        assert_eq!(app.timer.state, TimerState::Stopped);
+        assert_eq!(app.data.records.len(), 1);
+
+        clock.advance(Duration::from_secs(2));
+        handle_key(&mut app, space(), false, &mut held);
+        assert_eq!(app.timer.state, TimerState::Inspection);
+    }
+
+    #[test]
+    fn other_keys_stop_the_solve_right_away() {
+        let (mut app, clock) = app();
+        let mut held = HeldSpace::default();
+
+        handle_key(&mut app, space(), false, &mut held);
+        clock.advance(Duration::from_millis(300));
+        handle_key(&mut app, KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE), false, &mut held);
+        assert_eq!(app.data.records.len(), 1);
+        assert_eq!(app.data.records[0].time, Duration::from_millis(300));
+    }
+}